@@ -0,0 +1,115 @@
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use options_arbitrage::{
+    exchanges::parse_okex_levels,
+    orderbook::{ArbitrageDetector, Exchange, OrderBook, OrderLevel},
+};
+use rust_decimal::Decimal;
+use std::hint::black_box;
+
+const DEPTHS: [i64; 5] = [1, 10, 50, 100, 500];
+
+/// `levels` bids and asks spreading out from `mid` one tick at a time.
+fn book(exchange: Exchange, mid: Decimal, levels: i64) -> OrderBook {
+    let mut book = OrderBook::new("BTC-USD-251031-140000-P".to_string(), exchange);
+    let tick = Decimal::new(1, 4);
+    book.update_bids(
+        (0..levels)
+            .map(|i| OrderLevel {
+                price: mid - tick * Decimal::from(i + 1),
+                quantity: Decimal::TEN,
+            })
+            .collect(),
+    );
+    book.update_asks(
+        (0..levels)
+            .map(|i| OrderLevel {
+                price: mid + tick * Decimal::from(i + 1),
+                quantity: Decimal::TEN,
+            })
+            .collect(),
+    );
+    book
+}
+
+fn detection_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_arbitrage_detection");
+    for depth in DEPTHS {
+        // Every level of Okex's bids sits above every Deribit ask, so the walk crosses
+        // the whole book.
+        let okex = book(Exchange::Okex, Decimal::ONE, depth);
+        let deribit = book(Exchange::Deribit, Decimal::new(9, 1), depth);
+        group.bench_with_input(BenchmarkId::new("full_cross", depth), &depth, |b, _| {
+            b.iter(|| {
+                ArbitrageDetector::unlimited()
+                    .detect_arbitrage(black_box(&okex), black_box(&deribit))
+            })
+        });
+
+        // Only the best levels cross, the common live case.
+        let deribit = book(Exchange::Deribit, Decimal::ONE - Decimal::new(3, 4), depth);
+        group.bench_with_input(BenchmarkId::new("top_cross", depth), &depth, |b, _| {
+            b.iter(|| {
+                ArbitrageDetector::unlimited()
+                    .detect_arbitrage(black_box(&okex), black_box(&deribit))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn update_benchmarks(c: &mut Criterion) {
+    let base = book(Exchange::Okex, Decimal::ONE, 400);
+    let tick = Decimal::new(1, 4);
+    let levels = |n: i64| -> Vec<OrderLevel> {
+        (0..n)
+            .map(|i| OrderLevel {
+                price: Decimal::ONE - tick * Decimal::from(i * 3 + 1),
+                quantity: Decimal::from(i % 5),
+            })
+            .collect()
+    };
+
+    let mut group = c.benchmark_group("update_bids");
+    for n in [1, 100] {
+        let update = levels(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &update, |b, update| {
+            b.iter_batched(
+                || (base.clone(), update.clone()),
+                |(mut book, update)| {
+                    book.update_bids(update);
+                    book
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn parse_benchmarks(c: &mut Criterion) {
+    let raw: Vec<Vec<String>> = (0..20)
+        .map(|i| {
+            vec![
+                format!("0.{:04}", 1400 - i),
+                format!("{}", 10 + i),
+                "0".to_string(),
+                "3".to_string(),
+            ]
+        })
+        .collect();
+    c.bench_function("parse_okex_levels_20", |b| {
+        b.iter_batched(
+            || raw.clone(),
+            |raw| parse_okex_levels(black_box(raw)),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    detection_benchmarks,
+    update_benchmarks,
+    parse_benchmarks
+);
+criterion_main!(benches);
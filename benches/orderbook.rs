@@ -0,0 +1,113 @@
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use options_arbitrage::{
+    orderbook::{Exchange, OrderBook, OrderBookUpdate, OrderLevel},
+    recorder::{RecordingReader, RecordingWriter},
+};
+use rust_decimal::Decimal;
+use std::{hint::black_box, time::Instant};
+
+fn deep_book(levels: i64) -> OrderBook {
+    let mut book = OrderBook::new("BTC-USD-251031-140000-P".to_string(), Exchange::Okex);
+    let tick = Decimal::new(1, 4);
+    book.update_bids(
+        (0..levels)
+            .map(|i| OrderLevel {
+                price: Decimal::ONE - tick * Decimal::from(i + 1),
+                quantity: Decimal::TEN,
+            })
+            .collect(),
+    );
+    book.update_asks(
+        (0..levels)
+            .map(|i| OrderLevel {
+                price: Decimal::ONE + tick * Decimal::from(i + 1),
+                quantity: Decimal::TEN,
+            })
+            .collect(),
+    );
+    book
+}
+
+fn clone_benchmarks(c: &mut Criterion) {
+    let book = deep_book(1000);
+    let mut group = c.benchmark_group("clone_1000_levels");
+    group.bench_function("clone", |b| b.iter(|| black_box(&book).clone()));
+    group.bench_function("clone_top_n_20", |b| {
+        b.iter(|| black_box(&book).clone_top_n(20))
+    });
+    group.finish();
+}
+
+/// One simulated second of Okex traffic per channel, shaped as each channel pushes it.
+fn okex_channel_recording(channel: &str) -> Vec<u8> {
+    let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+    let level = |i: i64| OrderLevel {
+        price: Decimal::ONE + Decimal::new(i % 50, 4),
+        quantity: Decimal::from(i % 7),
+    };
+    let (messages, levels_per_message, snapshot) = match channel {
+        // An update per book change
+        "books-l2-tbt" => (1000, 1, false),
+        // Changes batched every 100ms
+        "books" => (10, 100, false),
+        "books5" => (10, 5, true),
+        "bbo-tbt" => (1000, 1, true),
+        _ => unreachable!(),
+    };
+    for m in 0..messages {
+        let levels: Vec<OrderLevel> = (0..levels_per_message).map(|i| level(m + i)).collect();
+        let update = if snapshot {
+            OrderBookUpdate::Snapshot {
+                exchange: Exchange::Okex,
+                symbol: "BTC-USD-251031-140000-P".to_string(),
+                bids: levels.clone(),
+                asks: levels,
+                received_at: Instant::now(),
+            }
+        } else {
+            OrderBookUpdate::Asks {
+                exchange: Exchange::Okex,
+                symbol: "BTC-USD-251031-140000-P".to_string(),
+                levels,
+                received_at: Instant::now(),
+            }
+        };
+        writer.write(&update).unwrap();
+    }
+    writer.into_inner()
+}
+
+fn replay_into_book(mut book: OrderBook, recording: &[u8]) -> OrderBook {
+    for record in RecordingReader::new(recording).unwrap() {
+        match record.unwrap().1 {
+            OrderBookUpdate::Bids { levels, .. } => book.update_bids(levels),
+            OrderBookUpdate::Asks { levels, .. } => book.update_asks(levels),
+            OrderBookUpdate::Snapshot { bids, asks, .. } => {
+                book.clear();
+                book.update_bids(bids);
+                book.update_asks(asks);
+            }
+            _ => {}
+        }
+    }
+    book
+}
+
+fn okex_channel_benchmarks(c: &mut Criterion) {
+    let book = deep_book(400);
+    let mut group = c.benchmark_group("replay_okex_channel_1s");
+    for channel in ["books", "books-l2-tbt", "books5", "bbo-tbt"] {
+        let recording = okex_channel_recording(channel);
+        group.bench_function(channel, |b| {
+            b.iter_batched(
+                || book.clone(),
+                |book| replay_into_book(book, black_box(&recording)),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, clone_benchmarks, okex_channel_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,82 @@
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use options_arbitrage::{
+    orderbook::Exchange,
+    parsing_utils::{
+        InstrumentValidator, MONTH_ABBREVS, month_from_abbrev, parse_deribit_date_with,
+    },
+};
+use std::{collections::HashMap, hint::black_box};
+
+/// Symbols parsed per iteration; criterion's run adds up to millions of parses.
+const BATCH: usize = 10_000;
+
+/// A batch of Deribit expiries cycling through every month, in mixed case.
+fn deribit_dates() -> Vec<String> {
+    (0..BATCH)
+        .map(|i| {
+            let (month, _) = MONTH_ABBREVS[i % 12];
+            let month = if i % 2 == 0 {
+                month.to_string()
+            } else {
+                month.to_lowercase()
+            };
+            format!("{:02}{month}{:02}", i % 28 + 1, i % 10 + 24)
+        })
+        .collect()
+}
+
+fn deribit_symbol_benchmarks(c: &mut Criterion) {
+    let symbols: Vec<String> = deribit_dates()
+        .into_iter()
+        .enumerate()
+        .map(|(i, date)| format!("BTC-{}-{}-P", date.to_uppercase(), 50_000 + i))
+        .collect();
+    let mut group = c.benchmark_group("parse_deribit_symbol");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("batch", |b| {
+        b.iter(|| {
+            for symbol in &symbols {
+                let _ = black_box(InstrumentValidator::parse_symbol(
+                    Some(&Exchange::Deribit),
+                    black_box(symbol),
+                ));
+            }
+        })
+    });
+    group.finish();
+}
+
+/// The `HashMap` lookup `parse_deribit_date` used to build on every call. The key is
+/// upper-cased on the stack so, like the const array scan, it does not allocate for that.
+fn hashmap_month(abbrev: &str) -> Option<u32> {
+    let months: HashMap<&str, u32> = MONTH_ABBREVS.into_iter().collect();
+    let mut key = [0u8; 3];
+    key.copy_from_slice(abbrev.as_bytes().get(..3)?);
+    key.make_ascii_uppercase();
+    months.get(std::str::from_utf8(&key).ok()?).copied()
+}
+
+/// The real `DDMMMYY` parser with each month lookup swapped in.
+fn month_lookup_benchmarks(c: &mut Criterion) {
+    let dates = deribit_dates();
+    let mut group = c.benchmark_group("parse_deribit_date");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    group.bench_function("const_array", |b| {
+        b.iter(|| {
+            for date in &dates {
+                let _ = black_box(parse_deribit_date_with(black_box(date), month_from_abbrev));
+            }
+        })
+    });
+    group.bench_function("hashmap", |b| {
+        b.iter(|| {
+            for date in &dates {
+                let _ = black_box(parse_deribit_date_with(black_box(date), hashmap_month));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deribit_symbol_benchmarks, month_lookup_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+//! Stamps the binary with when it was built, for the startup log line.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock is before 1970")
+        .as_secs();
+    println!("cargo:rustc-env=BUILD_TIMESTAMP={timestamp}");
+}
@@ -0,0 +1,41 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use options_arbitrage::{
+    orderbook::Exchange,
+    parsing_utils::{InstrumentValidator, OptionType},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // Errors are expected for most inputs; any panic fails the run. Every error is an
+    // `InstrumentParseError` by construction, so there is nothing further to check on them.
+    let _ = InstrumentValidator::parse_symbol(Some(&Exchange::Okex), input);
+    let _ = InstrumentValidator::parse_symbol(Some(&Exchange::Binance), input);
+    let _ = InstrumentValidator::parse_symbol(None, input);
+    if let Some((okex, deribit)) = input.split_once(',') {
+        let _ = InstrumentValidator::are_same_instrument(okex, deribit);
+    }
+
+    // `are_same_instrument` takes an Okex symbol first, so a Deribit symbol is compared against
+    // its own Okex spelling: the same instrument must always match itself.
+    if let Ok(parsed) = InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), input) {
+        let okex = format!(
+            "{}-USD-{}-{}-{}",
+            parsed.underlying,
+            parsed.expiry_date.format("%y%m%d"),
+            parsed.strike,
+            match parsed.option_type {
+                OptionType::Call => "C",
+                OptionType::Put => "P",
+            }
+        );
+        assert!(
+            matches!(InstrumentValidator::are_same_instrument(&okex, input), Ok(true)),
+            "{input:?} does not match its Okex form {okex:?}"
+        );
+    }
+});
@@ -0,0 +1,122 @@
+use crate::orderbook::ArbitrageOpportunity;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 2;
+/// At most one webhook per symbol within this window.
+const MIN_INTERVAL_PER_SYMBOL: Duration = Duration::from_secs(1);
+
+/// JSON body POSTed to `--alert-webhook`.
+#[derive(Debug, Serialize)]
+pub struct WebhookPayload {
+    /// RFC 3339 detection time.
+    pub timestamp: String,
+    pub opportunity: ArbitrageOpportunity,
+}
+
+/// Fire-and-forget POSTs of detected opportunities, rate-limited per symbol.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    last_sent: HashMap<String, Instant>,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> reqwest::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            client,
+            url,
+            last_sent: HashMap::new(),
+        })
+    }
+
+    /// Posts `opp` on a spawned task unless its symbol was alerted less than a second ago.
+    /// Failures are logged, never retried.
+    pub fn notify(&mut self, opp: &ArbitrageOpportunity) {
+        if !self.should_send(&opp.symbol, Instant::now()) {
+            return;
+        }
+        let payload = WebhookPayload {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            opportunity: opp.clone(),
+        };
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(error = %e, "Failed to serialize webhook payload");
+                return;
+            }
+        };
+        let request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body);
+        tokio::spawn(async move {
+            match request
+                .send()
+                .await
+                .and_then(|resp| resp.error_for_status())
+            {
+                Ok(_) => {}
+                Err(e) => warn!(error = %e, "Alert webhook failed"),
+            }
+        });
+    }
+
+    fn should_send(&mut self, symbol: &str, now: Instant) -> bool {
+        if let Some(last) = self.last_sent.get(symbol)
+            && now.duration_since(*last) < MIN_INTERVAL_PER_SYMBOL
+        {
+            return false;
+        }
+        self.last_sent.insert(symbol.to_string(), now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::Exchange;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rate_limits_per_symbol() {
+        let mut notifier = WebhookNotifier::new("http://127.0.0.1:9/alerts".to_string()).unwrap();
+        let start = Instant::now();
+
+        assert!(notifier.should_send("BTC-31OCT25-140000-P", start));
+        assert!(!notifier.should_send("BTC-31OCT25-140000-P", start + Duration::from_millis(500)));
+        assert!(notifier.should_send("ETH-31OCT25-3500-C", start + Duration::from_millis(500)));
+        assert!(notifier.should_send("BTC-31OCT25-140000-P", start + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_payload_serialization() {
+        let payload = WebhookPayload {
+            timestamp: "2025-10-17T12:00:00+00:00".to_string(),
+            opportunity: ArbitrageOpportunity {
+                buy_exchange: Exchange::Okex,
+                sell_exchange: Exchange::Deribit,
+                symbol: "BTC-31OCT25-140000-P".to_string(),
+                trades: Vec::new(),
+                total_profit: dec!(1.5),
+                total_volume: dec!(100),
+            },
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&payload).unwrap()).unwrap();
+        assert_eq!(json["timestamp"], "2025-10-17T12:00:00+00:00");
+        assert_eq!(json["opportunity"]["symbol"], "BTC-31OCT25-140000-P");
+        assert_eq!(json["opportunity"]["buy_exchange"], "okex");
+        assert_eq!(json["opportunity"]["total_profit"], 1.5);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::orderbook::{Exchange, OrderBook};
+use crate::parsing_utils::{OptionType, ParsedInstrument};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// Annual rate at which a shorter-dated option may trade above a longer-dated one
+/// (e.g. a deep put, whose early premium is worth more than waiting).
+pub const CALENDAR_CARRY_RATE: Decimal = dec!(0.05);
+
+const DAYS_PER_YEAR: Decimal = dec!(365);
+
+/// Buy the longer-dated option and sell the shorter-dated one with the same underlying,
+/// strike and type, when the short leg's bid tops the long leg's ask by more than carry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarSpreadOpportunity {
+    pub buy_exchange: Exchange,
+    pub sell_exchange: Exchange,
+    pub buy_expiry: NaiveDate,
+    pub sell_expiry: NaiveDate,
+    pub strike: Decimal,
+    pub underlying: String,
+    /// Short-dated bid minus long-dated ask minus carry, per contract.
+    pub spread_value: Decimal,
+}
+
+/// Books sharing an underlying, strike and option type, keyed by those.
+type ExpiryGroups<'a> =
+    HashMap<(&'a str, Decimal, &'a OptionType), Vec<(&'a ParsedInstrument, &'a OrderBook)>>;
+
+pub struct CalendarSpreadDetector;
+
+impl CalendarSpreadDetector {
+    /// Opportunities across `books`, best `spread_value` first. Books whose symbol does not
+    /// parse are ignored.
+    pub fn detect(
+        books: &HashMap<(Exchange, String), OrderBook>,
+    ) -> Vec<CalendarSpreadOpportunity> {
+        let mut groups = ExpiryGroups::new();
+        for book in books.values() {
+            if let Some(instrument) = &book.instrument {
+                groups
+                    .entry((
+                        &instrument.underlying,
+                        instrument.strike,
+                        &instrument.option_type,
+                    ))
+                    .or_default()
+                    .push((instrument, book));
+            }
+        }
+
+        let mut opportunities = Vec::new();
+        for legs in groups.values() {
+            for (short, short_book) in legs {
+                for (long, long_book) in legs {
+                    if long.expiry_date <= short.expiry_date {
+                        continue;
+                    }
+                    if let Some(opp) = Self::check_pair(short, short_book, long, long_book) {
+                        opportunities.push(opp);
+                    }
+                }
+            }
+        }
+        opportunities.sort_by_key(|opp| std::cmp::Reverse(opp.spread_value));
+        opportunities
+    }
+
+    fn check_pair(
+        short: &ParsedInstrument,
+        short_book: &OrderBook,
+        long: &ParsedInstrument,
+        long_book: &OrderBook,
+    ) -> Option<CalendarSpreadOpportunity> {
+        let bid = short_book.best_bid()?.price;
+        let ask = long_book.best_ask()?.price;
+        let days = Decimal::from((long.expiry_date - short.expiry_date).num_days());
+        let carry = bid * CALENDAR_CARRY_RATE * days / DAYS_PER_YEAR;
+        let spread_value = bid - ask - carry;
+        (spread_value > Decimal::ZERO).then(|| CalendarSpreadOpportunity {
+            buy_exchange: long_book.exchange.clone(),
+            sell_exchange: short_book.exchange.clone(),
+            buy_expiry: long.expiry_date,
+            sell_expiry: short.expiry_date,
+            strike: short.strike,
+            underlying: short.underlying.clone(),
+            spread_value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderLevel;
+
+    fn book(exchange: Exchange, symbol: &str, bid: Decimal, ask: Decimal) -> OrderBook {
+        OrderBook::from_levels(
+            symbol.to_string(),
+            exchange,
+            vec![OrderLevel {
+                price: bid,
+                quantity: dec!(10),
+            }],
+            vec![OrderLevel {
+                price: ask,
+                quantity: dec!(10),
+            }],
+        )
+    }
+
+    fn books(list: Vec<OrderBook>) -> HashMap<(Exchange, String), OrderBook> {
+        list.into_iter()
+            .map(|book| ((book.exchange.clone(), book.symbol.clone()), book))
+            .collect()
+    }
+
+    #[test]
+    fn test_detects_short_dated_bid_above_long_dated_ask() {
+        let books = books(vec![
+            book(
+                Exchange::Deribit,
+                "BTC-31OCT25-140000-C",
+                dec!(0.20),
+                dec!(0.21),
+            ),
+            book(
+                Exchange::Okex,
+                "BTC-USD-251128-140000-C",
+                dec!(0.14),
+                dec!(0.15),
+            ),
+        ]);
+
+        let opps = CalendarSpreadDetector::detect(&books);
+        assert_eq!(opps.len(), 1);
+        let opp = &opps[0];
+        assert_eq!(opp.buy_exchange, Exchange::Okex);
+        assert_eq!(opp.sell_exchange, Exchange::Deribit);
+        assert_eq!(
+            opp.buy_expiry,
+            NaiveDate::from_ymd_opt(2025, 11, 28).unwrap()
+        );
+        assert_eq!(
+            opp.sell_expiry,
+            NaiveDate::from_ymd_opt(2025, 10, 31).unwrap()
+        );
+        assert_eq!(opp.strike, dec!(140000));
+        assert_eq!(opp.underlying, "BTC");
+        // 0.20 - 0.15 - 0.20 * 0.05 * 28 / 365
+        assert_eq!(opp.spread_value.round_dp(6), dec!(0.049233));
+    }
+
+    #[test]
+    fn test_carry_absorbs_small_inversions() {
+        let books = books(vec![
+            book(
+                Exchange::Deribit,
+                "BTC-31OCT25-140000-P",
+                dec!(0.1505),
+                dec!(0.16),
+            ),
+            book(
+                Exchange::Deribit,
+                "BTC-26DEC25-140000-P",
+                dec!(0.14),
+                dec!(0.15),
+            ),
+        ]);
+        assert!(CalendarSpreadDetector::detect(&books).is_empty());
+    }
+
+    #[test]
+    fn test_ignores_different_strikes_types_and_unparsed_books() {
+        let books = books(vec![
+            book(
+                Exchange::Deribit,
+                "BTC-31OCT25-140000-C",
+                dec!(0.20),
+                dec!(0.21),
+            ),
+            book(
+                Exchange::Deribit,
+                "BTC-28NOV25-150000-C",
+                dec!(0.10),
+                dec!(0.11),
+            ),
+            book(
+                Exchange::Deribit,
+                "BTC-28NOV25-140000-P",
+                dec!(0.10),
+                dec!(0.11),
+            ),
+            book(Exchange::Deribit, "TEST", dec!(0.10), dec!(0.11)),
+        ]);
+        assert!(CalendarSpreadDetector::detect(&books).is_empty());
+    }
+}
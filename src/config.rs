@@ -0,0 +1,137 @@
+use crate::parsing_utils::SymbolPair;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Settings loaded from `--config`. Any field left out of the file takes the same default as the
+/// matching CLI flag; flags given explicitly on the command line override the file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    pub okex_fee_bps: f64,
+    pub deribit_fee_bps: f64,
+    pub binance_fee_bps: f64,
+    pub bybit_fee_bps: f64,
+    pub min_profit: f64,
+    pub min_dte: i32,
+    pub max_position_size: f64,
+    pub reconnect_max_attempts: u32,
+    pub output_format: String,
+    pub symbols: Vec<SymbolPair>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            okex_fee_bps: 3.0,
+            deribit_fee_bps: 3.0,
+            binance_fee_bps: 3.0,
+            bybit_fee_bps: 3.0,
+            min_profit: 0.0,
+            min_dte: 0,
+            max_position_size: 0.0,
+            reconnect_max_attempts: 0,
+            output_format: "text".to_string(),
+            symbols: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid config file: {0}")]
+    Toml(#[from] toml::de::Error),
+}
+
+impl AppConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(contents)?)
+    }
+}
+
+/// Reference template printed by `--generate-config`.
+pub const EXAMPLE_CONFIG: &str = r#"# Taker fees per exchange, in basis points of the traded price (3.0 = 0.03%).
+okex_fee_bps = 3.0
+deribit_fee_bps = 3.0
+binance_fee_bps = 3.0
+bybit_fee_bps = 3.0
+
+# Minimum total profit required to report an opportunity, in units of the underlying.
+min_profit = 0.0
+
+# Skip instruments with fewer than this many days to expiry.
+min_dte = 2
+
+# Largest position to take per opportunity, in contracts (0 = unlimited).
+max_position_size = 0.0
+
+# Give up reconnecting after this many failed attempts in a row (0 = retry forever).
+reconnect_max_attempts = 0
+
+# One of "text", "json" or "csv".
+output_format = "text"
+
+# Instruments to monitor. `binance` and `bybit` are optional.
+[[symbols]]
+okex = "BTC-USD-261225-140000-P"
+deribit = "BTC-25DEC26-140000-P"
+
+[[symbols]]
+okex = "BTC-USD-261225-100000-C"
+deribit = "BTC-25DEC26-100000-C"
+binance = "BTC-261225-100000-C"
+bybit = "BTC-25DEC26-100000-C"
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_config_has_all_fields() {
+        let config = AppConfig::parse(EXAMPLE_CONFIG).unwrap();
+
+        assert_eq!(config.okex_fee_bps, 3.0);
+        assert_eq!(config.deribit_fee_bps, 3.0);
+        assert_eq!(config.binance_fee_bps, 3.0);
+        assert_eq!(config.bybit_fee_bps, 3.0);
+        assert_eq!(config.min_profit, 0.0);
+        assert_eq!(config.min_dte, 2);
+        assert_eq!(config.max_position_size, 0.0);
+        assert_eq!(config.reconnect_max_attempts, 0);
+        assert_eq!(config.output_format, "text");
+        assert_eq!(
+            config.symbols,
+            vec![
+                SymbolPair {
+                    okex: Some("BTC-USD-261225-140000-P".to_string()),
+                    deribit: Some("BTC-25DEC26-140000-P".to_string()),
+                    binance: None,
+                    bybit: None,
+                },
+                SymbolPair {
+                    okex: Some("BTC-USD-261225-100000-C".to_string()),
+                    deribit: Some("BTC-25DEC26-100000-C".to_string()),
+                    binance: Some("BTC-261225-100000-C".to_string()),
+                    bybit: Some("BTC-25DEC26-100000-C".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let config = AppConfig::parse("min_profit = 0.5").unwrap();
+        assert_eq!(config.min_profit, 0.5);
+        assert_eq!(config.okex_fee_bps, AppConfig::default().okex_fee_bps);
+        assert!(config.symbols.is_empty());
+
+        assert!(AppConfig::parse("min_profit = \"lots\"").is_err());
+        assert!(AppConfig::parse("unknown_field = 1").is_err());
+    }
+}
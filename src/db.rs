@@ -0,0 +1,151 @@
+use crate::orderbook::ArbitrageOpportunity;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS opportunities (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    buy_exchange TEXT NOT NULL,
+    sell_exchange TEXT NOT NULL,
+    total_profit TEXT NOT NULL,
+    total_volume TEXT NOT NULL,
+    trade_count INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    opportunity_id INTEGER NOT NULL REFERENCES opportunities(id),
+    step INTEGER NOT NULL,
+    buy_price TEXT NOT NULL,
+    sell_price TEXT NOT NULL,
+    quantity TEXT NOT NULL,
+    gross_profit TEXT NOT NULL,
+    fee_cost TEXT NOT NULL,
+    profit TEXT NOT NULL
+);
+";
+
+pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+pub fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)
+}
+
+/// Inserts the opportunity and all of its trade levels in a single transaction, returning the
+/// new opportunity row id.
+pub fn insert_opportunity(
+    conn: &mut Connection,
+    opp: &ArbitrageOpportunity,
+    timestamp: DateTime<Utc>,
+) -> rusqlite::Result<i64> {
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO opportunities \
+         (timestamp, symbol, buy_exchange, sell_exchange, total_profit, total_volume, trade_count) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            timestamp.to_rfc3339(),
+            opp.symbol,
+            opp.buy_exchange.to_string(),
+            opp.sell_exchange.to_string(),
+            opp.total_profit.to_string(),
+            opp.total_volume.to_string(),
+            opp.trades.len() as i64,
+        ],
+    )?;
+    let opportunity_id = tx.last_insert_rowid();
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO trades \
+             (opportunity_id, step, buy_price, sell_price, quantity, gross_profit, fee_cost, profit) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for (i, trade) in opp.trades.iter().enumerate() {
+            stmt.execute(params![
+                opportunity_id,
+                (i + 1) as i64,
+                trade.buy_price.to_string(),
+                trade.sell_price.to_string(),
+                trade.quantity.to_string(),
+                trade.gross_profit.to_string(),
+                trade.fee_cost.to_string(),
+                trade.profit.to_string(),
+            ])?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(opportunity_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::{Exchange, TradeLevel};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_insert_opportunity_with_trades() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+
+        let opp = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "BTC-USD-251031-140000-P".to_string(),
+            trades: vec![
+                TradeLevel {
+                    buy_price: dec!(0.140),
+                    sell_price: dec!(0.150),
+                    quantity: dec!(10),
+                    normalized_quantity: dec!(10),
+                    gross_profit: dec!(0.100),
+                    fee_cost: dec!(0),
+                    profit: dec!(0.100),
+                },
+                TradeLevel {
+                    buy_price: dec!(0.145),
+                    sell_price: dec!(0.150),
+                    quantity: dec!(5),
+                    normalized_quantity: dec!(5),
+                    gross_profit: dec!(0.025),
+                    fee_cost: dec!(0),
+                    profit: dec!(0.025),
+                },
+            ],
+            total_profit: dec!(0.125),
+            total_volume: dec!(15),
+        };
+
+        let id = insert_opportunity(&mut conn, &opp, Utc::now()).unwrap();
+
+        let (symbol, total_profit, trade_count): (String, String, i64) = conn
+            .query_row(
+                "SELECT symbol, total_profit, trade_count FROM opportunities WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(symbol, opp.symbol);
+        assert_eq!(total_profit, "0.125");
+        assert_eq!(trade_count, 2);
+
+        let stored_trades: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM trades WHERE opportunity_id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(stored_trades, 2);
+    }
+}
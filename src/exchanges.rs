@@ -1,23 +1,347 @@
-use crate::orderbook::{Exchange, OrderBookUpdate, OrderLevel};
+#[cfg(feature = "auth")]
+use crate::auth::{self, OkexCredentials};
+use crate::{
+    metrics::Metrics,
+    orderbook::{Exchange, OrderBook, OrderBookUpdate, OrderLevel},
+    rest,
+};
 use anyhow::Result;
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, StreamExt, future::BoxFuture};
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use std::{str::FromStr, sync::Arc, time::Instant};
 use tokio::{
-    sync::mpsc,
+    sync::mpsc::{self, error::TrySendError},
     time::{Duration, sleep},
 };
 use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, info, warn};
 
 const OKEX_PING_INTERVAL_SECS: u64 = 15;
+/// Bybit closes connections that stay silent for 30 seconds.
+const BYBIT_PING_INTERVAL_SECS: u64 = 20;
+/// Levels per side in the `orderbook.{depth}.{symbol}` option channel.
+const BYBIT_BOOK_DEPTH: u32 = 25;
+
+/// Depths Deribit accepts in `book.{instrument}.{group}.{depth}.{interval}` channels.
+pub const DERIBIT_DEPTHS: [u32; 6] = [1, 5, 10, 20, 50, 100];
+
+/// clap value parser for `--deribit-depth`.
+pub fn parse_deribit_depth(s: &str) -> Result<u32, String> {
+    let depth: u32 = s.parse().map_err(|e| format!("{e}"))?;
+    if DERIBIT_DEPTHS.contains(&depth) {
+        Ok(depth)
+    } else {
+        Err(format!("must be one of {DERIBIT_DEPTHS:?}"))
+    }
+}
+
+/// Okex order book channels, from full incremental depth down to top of book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OkexBookChannel {
+    /// 400 levels, snapshot then incremental updates
+    #[default]
+    Books,
+    /// 400 levels, snapshot then an incremental update for every change (strict sequence checks)
+    #[value(name = "books-l2-tbt")]
+    BooksL2Tbt,
+    /// Top 5 levels, pushed as a full snapshot every time
+    Books5,
+    /// Best bid and offer, pushed as a full snapshot every time
+    BboTbt,
+}
+
+impl OkexBookChannel {
+    pub fn name(self) -> &'static str {
+        match self {
+            OkexBookChannel::Books => "books",
+            OkexBookChannel::BooksL2Tbt => "books-l2-tbt",
+            OkexBookChannel::Books5 => "books5",
+            OkexBookChannel::BboTbt => "bbo-tbt",
+        }
+    }
+
+    /// Whether every push replaces the book rather than patching it.
+    fn pushes_snapshots(self) -> bool {
+        matches!(self, OkexBookChannel::Books5 | OkexBookChannel::BboTbt)
+    }
+
+    /// Tick-by-tick deltas are only safe to apply with an unbroken `prevSeqId` chain.
+    fn strict_sequencing(self) -> bool {
+        self == OkexBookChannel::BooksL2Tbt
+    }
+}
+
+/// Per-venue subscription settings shared by every feed of that venue.
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    pub okex_channel: OkexBookChannel,
+    pub deribit_depth: u32,
+    /// Failed connection attempts in a row before a feed gives up; 0 retries forever.
+    pub max_reconnect_attempts: u32,
+    /// Log in to Okex before subscribing.
+    #[cfg(feature = "auth")]
+    pub okex_credentials: Option<Arc<OkexCredentials>>,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            okex_channel: OkexBookChannel::default(),
+            deribit_depth: 20,
+            max_reconnect_attempts: 0,
+            #[cfg(feature = "auth")]
+            okex_credentials: None,
+        }
+    }
+}
+
+/// What became of an update handed to `send_update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Sent {
+    Queued,
+    /// Book data was dropped because the channel was full.
+    Dropped,
+    /// The receiver is gone.
+    Closed,
+}
+
+/// Queues `update` for the main loop. Book data is never waited on: when the loop has fallen a
+/// full channel behind, it is dropped and counted against its exchange instead, and a feed that
+/// only gets a snapshot on subscribe has to resync. Control messages (`Clear`, confirmations
+/// and errors) wait for room, since losing one leaves the book in the wrong state for good.
+pub(crate) async fn send_update(
+    tx: &mpsc::Sender<OrderBookUpdate>,
+    update: OrderBookUpdate,
+    metrics: &Metrics,
+) -> Sent {
+    if !update.is_book_data() {
+        return match tx.send(update).await {
+            Ok(()) => Sent::Queued,
+            Err(_) => Sent::Closed,
+        };
+    }
+    match tx.try_send(update) {
+        Ok(()) => Sent::Queued,
+        Err(TrySendError::Full(update)) => {
+            let exchange = update.exchange();
+            let dropped = metrics.record_dropped_update(exchange);
+            warn!(%exchange, dropped, "update channel full, dropping update");
+            Sent::Dropped
+        }
+        Err(TrySendError::Closed(_)) => Sent::Closed,
+    }
+}
+
+/// A venue we can stream order books from. `connect` drives the connection (including
+/// reconnects) and pushes every book change into `tx`.
+pub trait ExchangeAdapter: Send + 'static {
+    fn exchange(&self) -> Exchange;
+
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>>;
+}
+
+pub struct OkexAdapter {
+    pub channel: OkexBookChannel,
+    pub max_reconnect_attempts: u32,
+    #[cfg(feature = "auth")]
+    pub credentials: Option<Arc<OkexCredentials>>,
+}
+
+impl ExchangeAdapter for OkexAdapter {
+    fn exchange(&self) -> Exchange {
+        Exchange::Okex
+    }
+
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>> {
+        Box::pin(okex_websocket_task(
+            symbol,
+            self.channel,
+            self.max_reconnect_attempts,
+            #[cfg(feature = "auth")]
+            self.credentials.clone(),
+            tx,
+            metrics,
+        ))
+    }
+}
+
+pub struct DeribitAdapter {
+    pub depth: u32,
+    pub max_reconnect_attempts: u32,
+}
+
+impl ExchangeAdapter for DeribitAdapter {
+    fn exchange(&self) -> Exchange {
+        Exchange::Deribit
+    }
+
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>> {
+        Box::pin(deribit_websocket_task(
+            symbol,
+            self.depth,
+            self.max_reconnect_attempts,
+            tx,
+            metrics,
+        ))
+    }
+}
+
+pub struct BinanceAdapter {
+    pub max_reconnect_attempts: u32,
+}
+
+impl ExchangeAdapter for BinanceAdapter {
+    fn exchange(&self) -> Exchange {
+        Exchange::Binance
+    }
 
-fn parse_okex_levels(levels: Vec<Vec<String>>) -> Vec<OrderLevel> {
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>> {
+        Box::pin(binance_websocket_task(
+            symbol,
+            self.max_reconnect_attempts,
+            tx,
+            metrics,
+        ))
+    }
+}
+
+pub struct BybitAdapter {
+    pub max_reconnect_attempts: u32,
+}
+
+impl ExchangeAdapter for BybitAdapter {
+    fn exchange(&self) -> Exchange {
+        Exchange::Bybit
+    }
+
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>> {
+        Box::pin(bybit_websocket_task(
+            symbol,
+            self.max_reconnect_attempts,
+            tx,
+            metrics,
+        ))
+    }
+}
+
+/// The websocket feed for `exchange`, or `None` for `Exchange::Aggregated`, which has no feed.
+pub fn adapter_for(exchange: &Exchange, config: &FeedConfig) -> Option<Box<dyn ExchangeAdapter>> {
+    Some(match exchange {
+        Exchange::Okex => Box::new(OkexAdapter {
+            channel: config.okex_channel,
+            max_reconnect_attempts: config.max_reconnect_attempts,
+            #[cfg(feature = "auth")]
+            credentials: config.okex_credentials.clone(),
+        }),
+        Exchange::Deribit => Box::new(DeribitAdapter {
+            depth: config.deribit_depth,
+            max_reconnect_attempts: config.max_reconnect_attempts,
+        }),
+        Exchange::Binance => Box::new(BinanceAdapter {
+            max_reconnect_attempts: config.max_reconnect_attempts,
+        }),
+        Exchange::Bybit => Box::new(BybitAdapter {
+            max_reconnect_attempts: config.max_reconnect_attempts,
+        }),
+        Exchange::Aggregated => return None,
+    })
+}
+
+/// Counts failed connection attempts for one feed and paces the reconnects between them.
+struct Reconnect {
+    exchange: Exchange,
+    symbol: String,
+    attempt: u32,
+    /// 0 retries forever.
+    max_attempts: u32,
+}
+
+impl Reconnect {
+    fn new(exchange: Exchange, symbol: &str, max_attempts: u32) -> Self {
+        Self {
+            exchange,
+            symbol: symbol.to_string(),
+            attempt: 0,
+            max_attempts,
+        }
+    }
+
+    /// Called once a connection proves live: its subscription is confirmed or its first book
+    /// arrives. A connection that is accepted and then dropped still counts as a failure.
+    fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Exponential backoff, capped at 2^7 = 128 seconds plus jitter.
+    fn backoff_secs(&self) -> u64 {
+        let jitter: u64 = rand::random::<u64>() % 5;
+        2_u64.saturating_pow(self.attempt.min(7)) + jitter
+    }
+
+    /// Records a failed attempt and sleeps before the next one. Returns `false`, after sending a
+    /// final `ConnectionError`, once `max_attempts` attempts in a row have failed.
+    async fn backoff(&mut self, tx: &mpsc::Sender<OrderBookUpdate>, metrics: &Metrics) -> bool {
+        self.attempt += 1;
+        if self.max_attempts != 0 && self.attempt >= self.max_attempts {
+            warn!(exchange = %self.exchange, attempts = self.attempt, "giving up reconnecting");
+            send_update(
+                tx,
+                OrderBookUpdate::ConnectionError {
+                    exchange: self.exchange.clone(),
+                    error: format!(
+                        "Giving up after {} failed connection attempts",
+                        self.attempt
+                    ),
+                    symbol: self.symbol.clone(),
+                },
+                metrics,
+            )
+            .await;
+            return false;
+        }
+
+        metrics.record_reconnect(&self.exchange);
+        let backoff = self.backoff_secs();
+        warn!(exchange = %self.exchange, backoff_secs = backoff, "reconnecting");
+        sleep(Duration::from_secs(backoff)).await;
+        true
+    }
+}
+
+pub fn parse_okex_levels(levels: Vec<Vec<String>>) -> Vec<OrderLevel> {
     levels
         .into_iter()
         .filter_map(|l| {
             if l.len() >= 2 {
                 Some(OrderLevel {
-                    price: l[0].parse().ok()?,
-                    quantity: l[1].parse().ok()?,
+                    price: Decimal::from_str(&l[0]).ok()?,
+                    quantity: Decimal::from_str(&l[1]).ok()?,
                 })
             } else {
                 None
@@ -26,18 +350,40 @@ fn parse_okex_levels(levels: Vec<Vec<String>>) -> Vec<OrderLevel> {
         .collect()
 }
 
-fn parse_deribit_levels(levels: Vec<(f64, f64)>) -> Vec<OrderLevel> {
+/// Levels of a grouped Deribit book are plain `[price, amount]` pairs.
+pub(crate) fn parse_deribit_levels(levels: Vec<(Decimal, Decimal)>) -> Vec<OrderLevel> {
+    levels
+        .into_iter()
+        .map(|(price, quantity)| OrderLevel { price, quantity })
+        .collect()
+}
+
+fn parse_binance_levels(levels: Vec<(String, String)>) -> Vec<OrderLevel> {
     levels
         .into_iter()
-        .map(|(p, q)| OrderLevel {
-            price: p,
-            quantity: q,
+        .filter_map(|(p, q)| {
+            Some(OrderLevel {
+                price: Decimal::from_str(&p).ok()?,
+                quantity: Decimal::from_str(&q).ok()?,
+            })
         })
         .collect()
 }
 
+/// Whether a `books` push replaces the book or patches it.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum OkexAction {
+    /// The full book, sent on subscribe.
+    Snapshot,
+    /// Changed levels since the previous push.
+    Update,
+}
+
 #[derive(Deserialize, Debug)]
 struct OkexResponse {
+    /// Absent on channels that only push snapshots.
+    action: Option<OkexAction>,
     data: Vec<OkexOrderBookData>,
 }
 
@@ -45,6 +391,181 @@ struct OkexResponse {
 struct OkexOrderBookData {
     asks: Vec<Vec<String>>,
     bids: Vec<Vec<String>>,
+    #[serde(rename = "seqId")]
+    seq_id: u64,
+    /// Sequence id of the previous message, -1 on snapshots.
+    #[serde(rename = "prevSeqId")]
+    prev_seq_id: Option<i64>,
+    /// CRC32 of the top 25 levels after applying this message; see `OrderBook::compute_checksum`.
+    checksum: Option<i32>,
+}
+
+/// `{"event": "subscribe", "arg": {...}}`, sent once a subscription is live.
+#[derive(Deserialize, Debug)]
+struct OkexEvent {
+    event: String,
+    arg: OkexEventArg,
+}
+
+#[derive(Deserialize, Debug)]
+struct OkexEventArg {
+    channel: String,
+}
+
+/// The channel an Okex subscribe acknowledgement confirms, if `text` is one.
+fn okex_subscription_ack(text: &str) -> Option<String> {
+    let event: OkexEvent = serde_json::from_str(text).ok()?;
+    (event.event == "subscribe").then_some(event.arg.channel)
+}
+
+/// Okex does not guarantee consecutive `seqId`s, so prefer `prevSeqId` when present and only
+/// fall back to expecting `last + 1` when it is missing. In `strict` mode a missing `prevSeqId`
+/// counts as a gap, since continuity cannot be proven.
+fn okex_sequence_gap(last_seq_id: Option<u64>, data: &OkexOrderBookData, strict: bool) -> bool {
+    let Some(last) = last_seq_id else {
+        return false;
+    };
+    match data.prev_seq_id {
+        Some(prev) if prev < 0 => false,
+        Some(prev) => prev as u64 != last,
+        None => strict || data.seq_id != last + 1,
+    }
+}
+
+/// Checks an Okex connection's pushes against a local copy of the book before forwarding them.
+struct OkexFeed {
+    symbol: String,
+    channel: OkexBookChannel,
+    last_seq_id: Option<u64>,
+    /// Local copy of the book, only used to verify Okex's checksums.
+    mirror: OrderBook,
+}
+
+impl OkexFeed {
+    fn new(symbol: &str, channel: OkexBookChannel) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            channel,
+            last_seq_id: None,
+            mirror: OrderBook::new(symbol.to_string(), Exchange::Okex),
+        }
+    }
+
+    /// Forwards the book in `resp`. On a sequence gap, a checksum mismatch or a dropped update
+    /// the book is cleared instead, and `true` tells the caller to resubscribe for a fresh
+    /// snapshot.
+    async fn forward(
+        &mut self,
+        resp: &OkexResponse,
+        received_at: Instant,
+        tx: &mpsc::Sender<OrderBookUpdate>,
+        metrics: &Metrics,
+    ) -> bool {
+        let Some(data) = resp.data.first() else {
+            return false;
+        };
+        let bids = parse_okex_levels(data.bids.clone());
+        let asks = parse_okex_levels(data.asks.clone());
+        if self.channel.pushes_snapshots() {
+            // The next push replaces the book anyway, so a dropped one needs no resync.
+            send_update(tx, self.snapshot(bids, asks, received_at), metrics).await;
+            return false;
+        }
+        let is_snapshot = resp.action == Some(OkexAction::Snapshot);
+
+        let mut resync =
+            if okex_sequence_gap(self.last_seq_id, data, self.channel.strict_sequencing()) {
+                warn!(
+                    exchange = "Okex",
+                    last_seq_id = self.last_seq_id,
+                    seq_id = data.seq_id,
+                    "sequence gap detected, requesting fresh snapshot"
+                );
+                true
+            } else {
+                if is_snapshot {
+                    self.mirror.update_from_snapshot(bids.clone(), asks.clone());
+                } else {
+                    self.mirror.update_bids(bids.clone());
+                    self.mirror.update_asks(asks.clone());
+                }
+                self.mirror.sequence = Some(data.seq_id);
+                self.mirror.checksum = data.checksum;
+                let actual = self.mirror.compute_checksum();
+                match data.checksum {
+                    Some(expected) if expected != actual => {
+                        warn!(
+                            exchange = "Okex",
+                            seq_id = data.seq_id,
+                            expected,
+                            actual,
+                            "checksum mismatch, requesting fresh snapshot"
+                        );
+                        true
+                    }
+                    _ => false,
+                }
+            };
+        if !resync {
+            self.last_seq_id = Some(data.seq_id);
+            let sent = if is_snapshot {
+                vec![send_update(tx, self.snapshot(bids, asks, received_at), metrics).await]
+            } else if bids.is_empty() && asks.is_empty() {
+                vec![]
+            } else {
+                let bids = OrderBookUpdate::Bids {
+                    exchange: Exchange::Okex,
+                    symbol: self.symbol.clone(),
+                    levels: bids,
+                    received_at,
+                };
+                let asks = OrderBookUpdate::Asks {
+                    exchange: Exchange::Okex,
+                    symbol: self.symbol.clone(),
+                    levels: asks,
+                    received_at,
+                };
+                vec![
+                    send_update(tx, bids, metrics).await,
+                    send_update(tx, asks, metrics).await,
+                ]
+            };
+            // Later deltas cannot repair a book that missed one.
+            if sent.contains(&Sent::Dropped) {
+                warn!(
+                    exchange = "Okex",
+                    seq_id = data.seq_id,
+                    "update dropped, requesting fresh snapshot"
+                );
+                resync = true;
+            }
+        }
+        if resync {
+            let clear = OrderBookUpdate::Clear {
+                exchange: Exchange::Okex,
+                symbol: self.symbol.clone(),
+            };
+            send_update(tx, clear, metrics).await;
+            self.last_seq_id = None;
+            self.mirror.clear();
+        }
+        resync
+    }
+
+    fn snapshot(
+        &self,
+        bids: Vec<OrderLevel>,
+        asks: Vec<OrderLevel>,
+        received_at: Instant,
+    ) -> OrderBookUpdate {
+        OrderBookUpdate::Snapshot {
+            exchange: Exchange::Okex,
+            symbol: self.symbol.clone(),
+            bids,
+            asks,
+            received_at,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -57,36 +578,264 @@ struct DeribitParams {
     data: DeribitOrderBookData,
 }
 
+/// JSON-RPC id of the `public/subscribe` request.
+const DERIBIT_SUBSCRIBE_ID: u64 = 0;
+
+/// JSON-RPC reply to `public/subscribe`: the channels now subscribed.
+#[derive(Deserialize, Debug)]
+struct DeribitSubscribeResult {
+    id: u64,
+    result: Vec<String>,
+}
+
+/// JSON-RPC error reply. Deribit leaves out the id when it could not read the request's.
+#[derive(Deserialize, Debug)]
+struct DeribitErrorReply {
+    #[serde(default)]
+    id: u64,
+    error: DeribitError,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct DeribitError {
+    code: i64,
+    message: String,
+}
+
+/// The Deribit messages the book connection acts on.
+#[derive(Debug)]
+enum DeribitMessage {
+    BookUpdate(DeribitResponse),
+    SubscribeResult {
+        id: u64,
+        result: Vec<String>,
+    },
+    Error {
+        id: u64,
+        error: DeribitError,
+    },
+    /// `kind` is `heartbeat` or `test_request`.
+    Heartbeat {
+        method: String,
+        kind: String,
+    },
+}
+
+/// Sorts `text` by shape before deserializing it. Replies the feed ignores (to `public/test`
+/// and `public/set_heartbeat`) and anything unrecognised come back as `None`.
+fn parse_deribit_message(text: &str) -> Option<DeribitMessage> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let message = if value.get("error").is_some() {
+        let reply: DeribitErrorReply = serde_json::from_value(value).ok()?;
+        DeribitMessage::Error {
+            id: reply.id,
+            error: reply.error,
+        }
+    } else if value.get("result").is_some_and(serde_json::Value::is_array) {
+        let reply: DeribitSubscribeResult = serde_json::from_value(value).ok()?;
+        DeribitMessage::SubscribeResult {
+            id: reply.id,
+            result: reply.result,
+        }
+    } else {
+        match value.get("method")?.as_str()? {
+            "subscription" => DeribitMessage::BookUpdate(serde_json::from_value(value).ok()?),
+            "heartbeat" => {
+                let heartbeat: DeribitHeartbeat = serde_json::from_value(value).ok()?;
+                DeribitMessage::Heartbeat {
+                    method: heartbeat.method,
+                    kind: heartbeat.params.type_,
+                }
+            }
+            _ => return None,
+        }
+    };
+    Some(message)
+}
+
+/// Server heartbeat enabled by `public/set_heartbeat`. `test_request` heartbeats must be answered
+/// with `public/test` or Deribit closes the connection.
+#[derive(Deserialize, Debug)]
+struct DeribitHeartbeat {
+    method: String,
+    params: DeribitHeartbeatParams,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeribitHeartbeatParams {
+    /// `heartbeat` or `test_request`.
+    #[serde(rename = "type")]
+    type_: String,
+}
+
+/// Seconds between Deribit server heartbeats (the minimum it accepts is 10).
+const DERIBIT_HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+/// A reconnecting Deribit feed skips the REST snapshot if its last book is at most this old.
+const DERIBIT_BOOK_FRESH_SECS: u64 = 5;
+
+fn deribit_test_msg() -> serde_json::Value {
+    serde_json::json!({
+        "id": 42,
+        "method": "public/test",
+        "params": {},
+        "jsonrpc": "2.0"
+    })
+}
+
+/// Grouped book notification: always the full top `depth` levels of each side.
 #[derive(Deserialize, Debug)]
-struct DeribitOrderBookData {
-    asks: Vec<(f64, f64)>,
-    bids: Vec<(f64, f64)>,
+pub(crate) struct DeribitOrderBookData {
+    pub(crate) asks: Vec<(Decimal, Decimal)>,
+    pub(crate) bids: Vec<(Decimal, Decimal)>,
+}
+
+/// Bybit sends levels as `[price, size]` strings; a size of zero removes the level.
+fn parse_bybit_levels(levels: Vec<(String, String)>) -> Vec<OrderLevel> {
+    parse_binance_levels(levels)
+}
+
+#[derive(Deserialize, Debug)]
+struct BybitResponse {
+    topic: String,
+    /// `snapshot` on subscribe, `delta` afterwards.
+    #[serde(rename = "type")]
+    type_: String,
+    data: BybitData,
+}
+
+/// Reply to an `op` request such as `subscribe` or `ping`.
+#[derive(Deserialize, Debug)]
+struct BybitOpResponse {
+    success: bool,
+    op: String,
+}
+
+fn bybit_subscription_ack(text: &str) -> bool {
+    serde_json::from_str::<BybitOpResponse>(text)
+        .is_ok_and(|resp| resp.success && resp.op == "subscribe")
+}
+
+#[derive(Deserialize, Debug)]
+struct BybitData {
+    s: String,
+    b: Vec<(String, String)>,
+    a: Vec<(String, String)>,
+}
+
+/// Id of the `SUBSCRIBE` request.
+const BINANCE_SUBSCRIBE_ID: u64 = 1;
+
+/// `{"result": null, "id": 1}` on success; failures carry an `error` instead.
+#[derive(Deserialize, Debug)]
+struct BinanceRpcResponse {
+    id: u64,
+    result: Option<serde_json::Value>,
+    error: Option<serde_json::Value>,
+}
+
+fn binance_subscription_ack(text: &str) -> bool {
+    serde_json::from_str::<BinanceRpcResponse>(text).is_ok_and(|resp| {
+        resp.id == BINANCE_SUBSCRIBE_ID && resp.result.is_none() && resp.error.is_none()
+    })
+}
+
+/// `depth20` stream message: the full top 20 levels of each side, not a delta.
+#[derive(Deserialize, Debug)]
+struct BinanceResponse {
+    data: BinanceDepthData,
+}
+
+/// The book `text` carries as a snapshot, if it is a depth message. Levels that dropped out of
+/// the top 20 or were pulled are simply absent, so they must not survive as deltas would.
+fn binance_depth_snapshot(
+    symbol: &str,
+    text: &str,
+    received_at: Instant,
+) -> Option<OrderBookUpdate> {
+    let resp = serde_json::from_str::<BinanceResponse>(text).ok()?;
+    Some(OrderBookUpdate::Snapshot {
+        exchange: Exchange::Binance,
+        symbol: symbol.to_string(),
+        bids: parse_binance_levels(resp.data.bids),
+        asks: parse_binance_levels(resp.data.asks),
+        received_at,
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct BinanceDepthData {
+    #[serde(rename = "b")]
+    bids: Vec<(String, String)>,
+    #[serde(rename = "a")]
+    asks: Vec<(String, String)>,
 }
 
 pub async fn okex_websocket_task(
     symbol: String,
-    tx: mpsc::UnboundedSender<OrderBookUpdate>,
+    channel: OkexBookChannel,
+    max_reconnect_attempts: u32,
+    #[cfg(feature = "auth")] credentials: Option<Arc<OkexCredentials>>,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
 ) -> Result<()> {
     let url = "wss://ws.okx.com:8443/ws/v5/public";
-    let mut attempt: u32 = 0;
+    let mut reconnect = Reconnect::new(Exchange::Okex, &symbol, max_reconnect_attempts);
 
     loop {
         match connect_async(url).await {
             Ok((ws_stream, _)) => {
-                attempt = 0;
                 let (mut write, mut read) = ws_stream.split();
                 let subscribe_msg = serde_json::json!({
                     "op": "subscribe",
-                    "args": [{"channel": "books", "instId": &symbol}]
+                    "args": [{"channel": channel.name(), "instId": &symbol}]
                 });
+                let unsubscribe_msg = serde_json::json!({
+                    "op": "unsubscribe",
+                    "args": [{"channel": channel.name(), "instId": &symbol}]
+                });
+                let mut feed = OkexFeed::new(&symbol, channel);
+                #[cfg(feature = "auth")]
+                if let Some(credentials) = &credentials {
+                    if let Err(e) = auth::okex_login(&mut write, &mut read, credentials).await {
+                        send_update(
+                            &tx,
+                            OrderBookUpdate::ConnectionError {
+                                exchange: Exchange::Okex,
+                                error: format!("Login failed: {e}"),
+                                symbol: symbol.clone(),
+                            },
+                            &metrics,
+                        )
+                        .await;
+                        if !reconnect.backoff(&tx, &metrics).await {
+                            return Ok(());
+                        }
+                        continue;
+                    }
+                    info!(exchange = "Okex", "logged in");
+                }
                 if write
                     .send(Message::text(subscribe_msg.to_string()))
                     .await
                     .is_err()
                 {
+                    send_update(
+                        &tx,
+                        OrderBookUpdate::ConnectionError {
+                            exchange: Exchange::Okex,
+                            error: "Failed to subscribe".to_string(),
+                            symbol: symbol.clone(),
+                        },
+                        &metrics,
+                    )
+                    .await;
+                    if !reconnect.backoff(&tx, &metrics).await {
+                        return Ok(());
+                    }
                     continue;
                 }
-                println!("Okex connected");
+                info!(exchange = "Okex", "connected");
 
                 let mut ping_interval =
                     tokio::time::interval(Duration::from_secs(OKEX_PING_INTERVAL_SECS));
@@ -96,42 +845,44 @@ pub async fn okex_websocket_task(
                         msg = read.next() => {
                             match msg {
                                 Some(Ok(Message::Text(text))) => {
-                                    if let Ok(resp) = serde_json::from_str::<OkexResponse>(&text) {
-                                        if let Some(data) = resp.data.first() {
-                                            // skip empty updates
-                                            if data.bids.is_empty() && data.asks.is_empty() {
-                                                continue;
-                                            }
-                                            let bids = parse_okex_levels(data.bids.clone());
-                                            let asks = parse_okex_levels(data.asks.clone());
-                                            let _ = tx.send(OrderBookUpdate::Bids {
-                                                exchange: Exchange::Okex,
-                                                symbol: symbol.clone(),
-                                                levels: bids,
-                                            });
-                                            let _ = tx.send(OrderBookUpdate::Asks {
-                                                exchange: Exchange::Okex,
-                                                symbol: symbol.clone(),
-                                                levels: asks,
-                                            });
+                                    let received_at = Instant::now();
+                                    metrics.record_message(&Exchange::Okex, text.len());
+                                    if let Ok(resp) = serde_json::from_str::<OkexResponse>(&text)
+                                        && !resp.data.is_empty()
+                                    {
+                                        reconnect.reset();
+                                        if feed.forward(&resp, received_at, &tx, &metrics).await {
+                                            // Okex ignores a duplicate subscribe, so drop the
+                                            // subscription first to get a new snapshot.
+                                            let _ = write.send(Message::text(unsubscribe_msg.to_string())).await;
+                                            let _ = write.send(Message::text(subscribe_msg.to_string())).await;
                                         }
+                                    } else if let Some(channel) = okex_subscription_ack(&text) {
+                                        reconnect.reset();
+                                        send_update(&tx, OrderBookUpdate::SubscriptionConfirmed {
+                                            exchange: Exchange::Okex,
+                                            symbol: symbol.clone(),
+                                            channel,
+                                        }, &metrics).await;
                                     }
                                 }
                                 Some(Ok(Message::Close(frame))) => {
                                     let reason = frame
                                         .map(|f| f.reason.to_string())
                                         .unwrap_or_else(|| "Connection closed by server".to_string());
-                                    let _ = tx.send(OrderBookUpdate::ConnectionError {
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
                                         exchange: Exchange::Okex,
                                         error: reason,
-                                    });
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
                                     break;
                                 }
                                 Some(Err(e)) => {
-                                    let _ = tx.send(OrderBookUpdate::ConnectionError {
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
                                         exchange: Exchange::Okex,
                                         error: format!("Websocket error: {e}"),
-                                    });
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
                                     break;
                                 }
                                 None => break,
@@ -145,48 +896,89 @@ pub async fn okex_websocket_task(
                 }
             }
             Err(e) => {
-                let _ = tx.send(OrderBookUpdate::ConnectionError {
-                    exchange: Exchange::Okex,
-                    error: format!("Failed to connect: {e}"),
-                });
+                send_update(
+                    &tx,
+                    OrderBookUpdate::ConnectionError {
+                        exchange: Exchange::Okex,
+                        error: format!("Failed to connect: {e}"),
+                        symbol: symbol.clone(),
+                    },
+                    &metrics,
+                )
+                .await;
             }
         }
 
-        attempt += 1;
-        let base = ((attempt.min(5)) * 5) as u64;
-        let jitter: u64 = rand::random::<u64>() % 5;
-        let backoff = base + jitter;
-        println!("Okex reconnecting in {backoff}s...");
-        sleep(Duration::from_secs(backoff)).await;
+        if !reconnect.backoff(&tx, &metrics).await {
+            return Ok(());
+        }
     }
 }
 
 pub async fn deribit_websocket_task(
     symbol: String,
-    tx: mpsc::UnboundedSender<OrderBookUpdate>,
+    depth: u32,
+    max_reconnect_attempts: u32,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
 ) -> Result<()> {
     let url = "wss://www.deribit.com/ws/api/v2";
-    let mut attempt: u32 = 0;
+    let mut reconnect = Reconnect::new(Exchange::Deribit, &symbol, max_reconnect_attempts);
+
+    // When this feed last pushed a book, over REST or the websocket.
+    let mut last_book: Option<Instant> = None;
 
     loop {
         match connect_async(url).await {
             Ok((ws_stream, _)) => {
-                attempt = 0;
                 let (mut write, mut read) = ws_stream.split();
+                // Seed the book over REST so it holds real levels until the websocket's first
+                // snapshot, unless the previous connection delivered one moments ago.
+                if last_book
+                    .is_none_or(|at| at.elapsed() > Duration::from_secs(DERIBIT_BOOK_FRESH_SECS))
+                {
+                    match rest::deribit_rest_snapshot(&symbol, &tx, &metrics).await {
+                        Ok(()) => last_book = Some(Instant::now()),
+                        Err(e) => warn!(exchange = "Deribit", error = %e, "REST snapshot failed"),
+                    }
+                }
                 let subscribe_msg = serde_json::json!({
                     "method": "public/subscribe",
-                    "params": {"channels": [format!("book.{}.none.20.100ms", symbol)]},
+                    "params": {"channels": [format!("book.{symbol}.none.{depth}.100ms")]},
                     "jsonrpc": "2.0",
-                    "id": 0
+                    "id": DERIBIT_SUBSCRIBE_ID
+                });
+                let set_heartbeat_msg = serde_json::json!({
+                    "method": "public/set_heartbeat",
+                    "params": {"interval": DERIBIT_HEARTBEAT_INTERVAL_SECS},
+                    "jsonrpc": "2.0",
+                    "id": 1
                 });
                 if write
                     .send(Message::text(subscribe_msg.to_string()))
                     .await
                     .is_err()
+                    || write
+                        .send(Message::text(set_heartbeat_msg.to_string()))
+                        .await
+                        .is_err()
                 {
+                    send_update(
+                        &tx,
+                        OrderBookUpdate::ConnectionError {
+                            exchange: Exchange::Deribit,
+                            error: "Failed to subscribe".to_string(),
+                            symbol: symbol.clone(),
+                        },
+                        &metrics,
+                    )
+                    .await;
+                    if !reconnect.backoff(&tx, &metrics).await {
+                        return Ok(());
+                    }
                     continue;
                 }
-                println!("Deribit connected");
+                info!(exchange = "Deribit", "connected");
 
                 let mut ping_interval = tokio::time::interval(Duration::from_secs(15));
 
@@ -195,36 +987,64 @@ pub async fn deribit_websocket_task(
                         msg = read.next() => {
                             match msg {
                                 Some(Ok(Message::Text(text))) => {
-                                    if let Ok(resp) = serde_json::from_str::<DeribitResponse>(&text) {
-                                        let bids = parse_deribit_levels(resp.params.data.bids);
-                                        let asks = parse_deribit_levels(resp.params.data.asks);
-                                        let _ = tx.send(OrderBookUpdate::Bids {
-                                            exchange: Exchange::Deribit,
-                                            symbol: symbol.clone(),
-                                            levels: bids,
-                                        });
-                                        let _ = tx.send(OrderBookUpdate::Asks {
-                                            exchange: Exchange::Deribit,
-                                            symbol: symbol.clone(),
-                                            levels: asks,
-                                        });
+                                    let received_at = Instant::now();
+                                    metrics.record_message(&Exchange::Deribit, text.len());
+                                    match parse_deribit_message(&text) {
+                                        Some(DeribitMessage::BookUpdate(resp)) => {
+                                            reconnect.reset();
+                                            last_book = Some(received_at);
+                                            let data = resp.params.data;
+                                            send_update(&tx, OrderBookUpdate::Snapshot {
+                                                exchange: Exchange::Deribit,
+                                                symbol: symbol.clone(),
+                                                bids: parse_deribit_levels(data.bids),
+                                                asks: parse_deribit_levels(data.asks),
+                                                received_at,
+                                            }, &metrics).await;
+                                        }
+                                        Some(DeribitMessage::SubscribeResult { id: DERIBIT_SUBSCRIBE_ID, result }) => {
+                                            if let Some(channel) = result.into_iter().next() {
+                                                reconnect.reset();
+                                                send_update(&tx, OrderBookUpdate::SubscriptionConfirmed {
+                                                    exchange: Exchange::Deribit,
+                                                    symbol: symbol.clone(),
+                                                    channel,
+                                                }, &metrics).await;
+                                            }
+                                        }
+                                        Some(DeribitMessage::Error { id, error }) => {
+                                            warn!(exchange = "Deribit", id, code = error.code, message = %error.message, "error reply, reconnecting");
+                                            send_update(&tx, OrderBookUpdate::ConnectionError {
+                                                exchange: Exchange::Deribit,
+                                                error: format!("Error reply {}: {}", error.code, error.message),
+                                                symbol: symbol.clone(),
+                                            }, &metrics).await;
+                                            break;
+                                        }
+                                        Some(DeribitMessage::Heartbeat { method, kind }) => {
+                                            debug!(exchange = "Deribit", %method, %kind, "heartbeat");
+                                            let _ = write.send(Message::text(deribit_test_msg().to_string())).await;
+                                        }
+                                        Some(DeribitMessage::SubscribeResult { .. }) | None => {}
                                     }
                                 }
                                 Some(Ok(Message::Close(frame))) => {
                                     let reason = frame
                                         .map(|f| f.reason.to_string())
                                         .unwrap_or_else(|| "Connection closed by server".to_string());
-                                    let _ = tx.send(OrderBookUpdate::ConnectionError {
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
                                         exchange: Exchange::Deribit,
                                         error: reason,
-                                    });
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
                                     break;
                                 }
                                 Some(Err(e)) => {
-                                    let _ = tx.send(OrderBookUpdate::ConnectionError {
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
                                         exchange: Exchange::Deribit,
                                         error: format!("Websocket error: {e}"),
-                                    });
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
                                     break;
                                 }
                                 None => break,
@@ -232,30 +1052,742 @@ pub async fn deribit_websocket_task(
                             }
                         }
                         _ = ping_interval.tick() => {
-                            let heartbeat = serde_json::json!({
-                                "id": 42,
-                                "method": "public/test",
-                                "params": {},
-                                "jsonrpc": "2.0"
-                            });
-                            let _ = write.send(Message::text(heartbeat.to_string())).await;
+                            let _ = write.send(Message::text(deribit_test_msg().to_string())).await;
                         }
                     }
                 }
             }
             Err(e) => {
-                let _ = tx.send(OrderBookUpdate::ConnectionError {
-                    exchange: Exchange::Deribit,
-                    error: format!("Failed to connect: {e}"),
+                send_update(
+                    &tx,
+                    OrderBookUpdate::ConnectionError {
+                        exchange: Exchange::Deribit,
+                        error: format!("Failed to connect: {e}"),
+                        symbol: symbol.clone(),
+                    },
+                    &metrics,
+                )
+                .await;
+            }
+        }
+
+        if !reconnect.backoff(&tx, &metrics).await {
+            return Ok(());
+        }
+    }
+}
+
+pub async fn binance_websocket_task(
+    symbol: String,
+    max_reconnect_attempts: u32,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
+) -> Result<()> {
+    let url = "wss://nbstream.binance.com/eoptions/stream";
+    let mut reconnect = Reconnect::new(Exchange::Binance, &symbol, max_reconnect_attempts);
+
+    loop {
+        match connect_async(url).await {
+            Ok((ws_stream, _)) => {
+                let (mut write, mut read) = ws_stream.split();
+                let stream = format!("{symbol}@depth20@100ms");
+                let subscribe_msg = serde_json::json!({
+                    "method": "SUBSCRIBE",
+                    "params": [&stream],
+                    "id": BINANCE_SUBSCRIBE_ID
                 });
+                if write
+                    .send(Message::text(subscribe_msg.to_string()))
+                    .await
+                    .is_err()
+                {
+                    send_update(
+                        &tx,
+                        OrderBookUpdate::ConnectionError {
+                            exchange: Exchange::Binance,
+                            error: "Failed to subscribe".to_string(),
+                            symbol: symbol.clone(),
+                        },
+                        &metrics,
+                    )
+                    .await;
+                    if !reconnect.backoff(&tx, &metrics).await {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                info!(exchange = "Binance", "connected");
+
+                // Binance drives keepalive with server pings, which tungstenite answers for us.
+                while let Some(msg) = read.next().await {
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            let received_at = Instant::now();
+                            metrics.record_message(&Exchange::Binance, text.len());
+                            if let Some(snapshot) =
+                                binance_depth_snapshot(&symbol, &text, received_at)
+                            {
+                                reconnect.reset();
+                                send_update(&tx, snapshot, &metrics).await;
+                            } else if binance_subscription_ack(&text) {
+                                reconnect.reset();
+                                send_update(
+                                    &tx,
+                                    OrderBookUpdate::SubscriptionConfirmed {
+                                        exchange: Exchange::Binance,
+                                        symbol: symbol.clone(),
+                                        channel: stream.clone(),
+                                    },
+                                    &metrics,
+                                )
+                                .await;
+                            }
+                        }
+                        Ok(Message::Close(frame)) => {
+                            let reason = frame
+                                .map(|f| f.reason.to_string())
+                                .unwrap_or_else(|| "Connection closed by server".to_string());
+                            send_update(
+                                &tx,
+                                OrderBookUpdate::ConnectionError {
+                                    exchange: Exchange::Binance,
+                                    error: reason,
+                                    symbol: symbol.clone(),
+                                },
+                                &metrics,
+                            )
+                            .await;
+                            break;
+                        }
+                        Err(e) => {
+                            send_update(
+                                &tx,
+                                OrderBookUpdate::ConnectionError {
+                                    exchange: Exchange::Binance,
+                                    error: format!("Websocket error: {e}"),
+                                    symbol: symbol.clone(),
+                                },
+                                &metrics,
+                            )
+                            .await;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Err(e) => {
+                send_update(
+                    &tx,
+                    OrderBookUpdate::ConnectionError {
+                        exchange: Exchange::Binance,
+                        error: format!("Failed to connect: {e}"),
+                        symbol: symbol.clone(),
+                    },
+                    &metrics,
+                )
+                .await;
             }
         }
 
-        attempt += 1;
-        let base = ((attempt.min(5)) * 5) as u64;
-        let jitter: u64 = rand::random::<u64>() % 5;
-        let backoff = base + jitter;
-        println!("Deribit reconnecting in {backoff}s...");
-        sleep(Duration::from_secs(backoff)).await;
+        if !reconnect.backoff(&tx, &metrics).await {
+            return Ok(());
+        }
+    }
+}
+
+pub async fn bybit_websocket_task(
+    symbol: String,
+    max_reconnect_attempts: u32,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
+) -> Result<()> {
+    let url = "wss://stream.bybit.com/v5/public/option";
+    let mut reconnect = Reconnect::new(Exchange::Bybit, &symbol, max_reconnect_attempts);
+
+    loop {
+        match connect_async(url).await {
+            Ok((ws_stream, _)) => {
+                let (mut write, mut read) = ws_stream.split();
+                let topic = format!("orderbook.{BYBIT_BOOK_DEPTH}.{symbol}");
+                let subscribe_msg = serde_json::json!({
+                    "op": "subscribe",
+                    "args": [&topic]
+                });
+                let unsubscribe_msg = serde_json::json!({
+                    "op": "unsubscribe",
+                    "args": [&topic]
+                });
+                if write
+                    .send(Message::text(subscribe_msg.to_string()))
+                    .await
+                    .is_err()
+                {
+                    send_update(
+                        &tx,
+                        OrderBookUpdate::ConnectionError {
+                            exchange: Exchange::Bybit,
+                            error: "Failed to subscribe".to_string(),
+                            symbol: symbol.clone(),
+                        },
+                        &metrics,
+                    )
+                    .await;
+                    if !reconnect.backoff(&tx, &metrics).await {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                info!(exchange = "Bybit", "connected");
+
+                let mut ping_interval =
+                    tokio::time::interval(Duration::from_secs(BYBIT_PING_INTERVAL_SECS));
+
+                loop {
+                    tokio::select! {
+                        msg = read.next() => {
+                            match msg {
+                                Some(Ok(Message::Text(text))) => {
+                                    let received_at = Instant::now();
+                                    metrics.record_message(&Exchange::Bybit, text.len());
+                                    if let Ok(resp) = serde_json::from_str::<BybitResponse>(&text) {
+                                        reconnect.reset();
+                                        debug!(exchange = "Bybit", topic = %resp.topic, instrument = %resp.data.s, "book update");
+                                        let bids = parse_bybit_levels(resp.data.b);
+                                        let asks = parse_bybit_levels(resp.data.a);
+                                        let sent = if resp.type_ == "snapshot" {
+                                            vec![send_update(&tx, OrderBookUpdate::Snapshot {
+                                                exchange: Exchange::Bybit,
+                                                symbol: symbol.clone(),
+                                                bids,
+                                                asks,
+                                                received_at,
+                                            }, &metrics).await]
+                                        } else {
+                                            vec![
+                                                send_update(&tx, OrderBookUpdate::Bids {
+                                                    exchange: Exchange::Bybit,
+                                                    symbol: symbol.clone(),
+                                                    levels: bids,
+                                                    received_at,
+                                                }, &metrics).await,
+                                                send_update(&tx, OrderBookUpdate::Asks {
+                                                    exchange: Exchange::Bybit,
+                                                    symbol: symbol.clone(),
+                                                    levels: asks,
+                                                    received_at,
+                                                }, &metrics).await,
+                                            ]
+                                        };
+                                        // Bybit only sends a snapshot on subscribe, so a book
+                                        // that missed an update is rebuilt from a new one.
+                                        if sent.contains(&Sent::Dropped) {
+                                            warn!(exchange = "Bybit", "update dropped, requesting fresh snapshot");
+                                            send_update(&tx, OrderBookUpdate::Clear {
+                                                exchange: Exchange::Bybit,
+                                                symbol: symbol.clone(),
+                                            }, &metrics).await;
+                                            let _ = write.send(Message::text(unsubscribe_msg.to_string())).await;
+                                            let _ = write.send(Message::text(subscribe_msg.to_string())).await;
+                                        }
+                                    } else if bybit_subscription_ack(&text) {
+                                        reconnect.reset();
+                                        send_update(&tx, OrderBookUpdate::SubscriptionConfirmed {
+                                            exchange: Exchange::Bybit,
+                                            symbol: symbol.clone(),
+                                            channel: topic.clone(),
+                                        }, &metrics).await;
+                                    }
+                                }
+                                Some(Ok(Message::Close(frame))) => {
+                                    let reason = frame
+                                        .map(|f| f.reason.to_string())
+                                        .unwrap_or_else(|| "Connection closed by server".to_string());
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
+                                        exchange: Exchange::Bybit,
+                                        error: reason,
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
+                                    break;
+                                }
+                                Some(Err(e)) => {
+                                    send_update(&tx, OrderBookUpdate::ConnectionError {
+                                        exchange: Exchange::Bybit,
+                                        error: format!("Websocket error: {e}"),
+                                        symbol: symbol.clone(),
+                                    }, &metrics).await;
+                                    break;
+                                }
+                                None => break,
+                                _ => {}
+                            }
+                        }
+                        _ = ping_interval.tick() => {
+                            let ping = serde_json::json!({"op": "ping"});
+                            let _ = write.send(Message::text(ping.to_string())).await;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                send_update(
+                    &tx,
+                    OrderBookUpdate::ConnectionError {
+                        exchange: Exchange::Bybit,
+                        error: format!("Failed to connect: {e}"),
+                        symbol: symbol.clone(),
+                    },
+                    &metrics,
+                )
+                .await;
+            }
+        }
+
+        if !reconnect.backoff(&tx, &metrics).await {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    /// Replays a fixed sequence of updates instead of talking to a real venue.
+    pub struct MockAdapter {
+        exchange: Exchange,
+        updates: Vec<OrderBookUpdate>,
+    }
+
+    impl ExchangeAdapter for MockAdapter {
+        fn exchange(&self) -> Exchange {
+            self.exchange.clone()
+        }
+
+        fn connect(
+            &self,
+            _symbol: String,
+            tx: mpsc::Sender<OrderBookUpdate>,
+            _metrics: Arc<Metrics>,
+        ) -> BoxFuture<'static, Result<()>> {
+            let updates = self.updates.clone();
+            Box::pin(async move {
+                for update in updates {
+                    tx.send(update).await?;
+                }
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_adapter_replays_updates() {
+        let adapter: Box<dyn ExchangeAdapter> = Box::new(MockAdapter {
+            exchange: Exchange::Deribit,
+            updates: vec![
+                OrderBookUpdate::Bids {
+                    exchange: Exchange::Deribit,
+                    symbol: "MOCK".to_string(),
+                    levels: vec![OrderLevel {
+                        price: dec!(0.15),
+                        quantity: dec!(10),
+                    }],
+                    received_at: Instant::now(),
+                },
+                OrderBookUpdate::Clear {
+                    exchange: Exchange::Deribit,
+                    symbol: "MOCK".to_string(),
+                },
+            ],
+        });
+        assert_eq!(adapter.exchange(), Exchange::Deribit);
+
+        let (tx, mut rx) = mpsc::channel(16);
+        tokio::spawn(adapter.connect("MOCK".to_string(), tx, Arc::new(Metrics::new())))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Bids { levels, .. }) if levels[0].price == dec!(0.15)
+        ));
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Clear { .. })
+        ));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_gives_up_after_max_attempts() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = mpsc::channel(16);
+        let mut reconnect = Reconnect::new(Exchange::Okex, "MOCK", 3);
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        assert!(!reconnect.backoff(&tx, &metrics).await);
+        drop(tx);
+
+        let mut errors = Vec::new();
+        while let Some(update) = rx.recv().await {
+            match update {
+                OrderBookUpdate::ConnectionError { error, symbol, .. } => {
+                    assert_eq!(symbol, "MOCK");
+                    errors.push(error);
+                }
+                other => panic!("unexpected update {other:?}"),
+            }
+        }
+        assert_eq!(errors, ["Giving up after 3 failed connection attempts"]);
+        let reconnects = metrics
+            .websocket_reconnects_total
+            .with_label_values(&[Exchange::Okex.to_string().as_str()]);
+        assert_eq!(reconnects.get(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_only_resets_once_live() {
+        let metrics = Metrics::new();
+        let (tx, _rx) = mpsc::channel(16);
+        let mut reconnect = Reconnect::new(Exchange::Okex, "MOCK", 3);
+        // Connections that never confirm keep counting towards the limit.
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        reconnect.reset();
+        assert_eq!(reconnect.attempt, 0);
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        assert!(reconnect.backoff(&tx, &metrics).await);
+        assert!(!reconnect.backoff(&tx, &metrics).await);
+    }
+
+    #[tokio::test]
+    async fn test_send_update_drops_and_counts_when_channel_full() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = mpsc::channel(1);
+        for i in 0..5 {
+            let update = OrderBookUpdate::Bids {
+                exchange: Exchange::Okex,
+                symbol: "MOCK".to_string(),
+                levels: vec![],
+                received_at: Instant::now(),
+            };
+            let expected = if i == 0 { Sent::Queued } else { Sent::Dropped };
+            assert_eq!(send_update(&tx, update, &metrics).await, expected);
+        }
+
+        let okex = metrics.throughput.get(&Exchange::Okex).unwrap();
+        assert_eq!(
+            okex.dropped_updates
+                .load(std::sync::atomic::Ordering::Relaxed),
+            4
+        );
+        assert!(rx.recv().await.is_some());
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        let update = OrderBookUpdate::Clear {
+            exchange: Exchange::Okex,
+            symbol: "MOCK".to_string(),
+        };
+        assert_eq!(send_update(&tx, update, &metrics).await, Sent::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_send_update_waits_for_room_for_control_messages() {
+        let metrics = Arc::new(Metrics::new());
+        let (tx, mut rx) = mpsc::channel(1);
+        let clear = || OrderBookUpdate::Clear {
+            exchange: Exchange::Okex,
+            symbol: "MOCK".to_string(),
+        };
+        assert_eq!(send_update(&tx, clear(), &metrics).await, Sent::Queued);
+        let sender = {
+            let metrics = metrics.clone();
+            tokio::spawn(async move { send_update(&tx, clear(), &metrics).await })
+        };
+
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Clear { .. })
+        ));
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Clear { .. })
+        ));
+        assert_eq!(sender.await.unwrap(), Sent::Queued);
+        let okex = metrics.throughput.get(&Exchange::Okex).unwrap();
+        assert_eq!(
+            okex.dropped_updates
+                .load(std::sync::atomic::Ordering::Relaxed),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_okex_feed_resyncs_after_dropping_an_update() {
+        let metrics = Metrics::new();
+        let (tx, mut rx) = mpsc::channel(2);
+        let mut feed = OkexFeed::new("BTC-USD-250328-100000-C", OkexBookChannel::Books);
+        let mut book = OrderBook::new(feed.symbol.clone(), Exchange::Okex);
+        let push = |text: &str| serde_json::from_str::<OkexResponse>(text).unwrap();
+
+        let snapshot = push(
+            r#"{"action":"snapshot","data":[{"bids":[["0.05","10"]],"asks":[["0.06","10"]],"seqId":1,"prevSeqId":-1}]}"#,
+        );
+        assert!(!feed.forward(&snapshot, Instant::now(), &tx, &metrics).await);
+        // The main loop has fallen behind: the delta's bids take the last slot and its asks
+        // find the channel full.
+        let delta = push(
+            r#"{"action":"update","data":[{"bids":[["0.055","5"]],"asks":[],"seqId":2,"prevSeqId":1}]}"#,
+        );
+        let (resubscribe, ()) =
+            tokio::join!(feed.forward(&delta, Instant::now(), &tx, &metrics), async {
+                for _ in 0..3 {
+                    book.apply_update(&rx.recv().await.unwrap()).unwrap();
+                }
+            });
+        assert!(resubscribe);
+        assert_eq!(book.best_bid(), None);
+        assert_eq!(feed.last_seq_id, None);
+
+        // Resubscribing brings a snapshot that includes the dropped delta.
+        let fresh = push(
+            r#"{"action":"snapshot","data":[{"bids":[["0.055","5"],["0.05","10"]],"asks":[["0.06","10"]],"seqId":5,"prevSeqId":-1}]}"#,
+        );
+        assert!(!feed.forward(&fresh, Instant::now(), &tx, &metrics).await);
+        book.apply_update(&rx.recv().await.unwrap()).unwrap();
+        let next = push(
+            r#"{"action":"update","data":[{"bids":[],"asks":[["0.058","2"]],"seqId":6,"prevSeqId":5}]}"#,
+        );
+        assert!(!feed.forward(&next, Instant::now(), &tx, &metrics).await);
+        for _ in 0..2 {
+            book.apply_update(&rx.recv().await.unwrap()).unwrap();
+        }
+        assert_eq!(book.best_bid().unwrap().price, dec!(0.055));
+        assert_eq!(book.best_ask().unwrap().price, dec!(0.058));
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_exponential_and_capped() {
+        let mut reconnect = Reconnect::new(Exchange::Deribit, "MOCK", 0);
+        for (attempt, base) in [(1, 2), (3, 8), (7, 128), (20, 128)] {
+            reconnect.attempt = attempt;
+            let backoff = reconnect.backoff_secs();
+            assert!((base..base + 5).contains(&backoff), "{attempt}: {backoff}");
+        }
+    }
+
+    #[test]
+    fn test_adapter_for_matches_exchange() {
+        for exchange in [
+            Exchange::Okex,
+            Exchange::Deribit,
+            Exchange::Binance,
+            Exchange::Bybit,
+        ] {
+            assert_eq!(
+                adapter_for(&exchange, &FeedConfig::default())
+                    .unwrap()
+                    .exchange(),
+                exchange
+            );
+        }
+        assert!(adapter_for(&Exchange::Aggregated, &FeedConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_okex_sequence_gap() {
+        let data = |seq_id, prev_seq_id| OkexOrderBookData {
+            asks: Vec::new(),
+            bids: Vec::new(),
+            seq_id,
+            prev_seq_id,
+            checksum: None,
+        };
+
+        // Nothing to compare against before the first message, and snapshots reset the chain
+        assert!(!okex_sequence_gap(None, &data(10, Some(3)), true));
+        assert!(!okex_sequence_gap(Some(5), &data(10, Some(-1)), true));
+
+        assert!(!okex_sequence_gap(Some(5), &data(10, Some(5)), false));
+        assert!(okex_sequence_gap(Some(5), &data(10, Some(7)), false));
+
+        // Without prevSeqId only strict mode refuses to trust a consecutive seqId
+        assert!(!okex_sequence_gap(Some(5), &data(6, None), false));
+        assert!(okex_sequence_gap(Some(5), &data(6, None), true));
+        assert!(okex_sequence_gap(Some(5), &data(8, None), false));
+    }
+
+    #[test]
+    fn test_okex_channel_names() {
+        use clap::ValueEnum;
+        for channel in OkexBookChannel::value_variants() {
+            let parsed = OkexBookChannel::from_str(channel.name(), false).unwrap();
+            assert_eq!(parsed, *channel);
+        }
+        assert!(OkexBookChannel::from_str("books50", false).is_err());
+    }
+
+    #[test]
+    fn test_parse_deribit_heartbeat() {
+        let text = r#"{"jsonrpc":"2.0","method":"heartbeat","params":{"type":"test_request"}}"#;
+        assert!(serde_json::from_str::<DeribitResponse>(text).is_err());
+
+        match parse_deribit_message(text) {
+            Some(DeribitMessage::Heartbeat { method, kind }) => {
+                assert_eq!(method, "heartbeat");
+                assert_eq!(kind, "test_request");
+            }
+            other => panic!("expected a heartbeat, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_deribit_message_shapes() {
+        let book = r#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-31OCT25-140000-P.none.5.100ms","data":{"bids":[[0.1405,12.0]],"asks":[]}}}"#;
+        assert!(matches!(
+            parse_deribit_message(book),
+            Some(DeribitMessage::BookUpdate(_))
+        ));
+
+        let error =
+            r#"{"jsonrpc":"2.0","id":0,"error":{"message":"Invalid params","code":-32602}}"#;
+        match parse_deribit_message(error) {
+            Some(DeribitMessage::Error { id, error }) => {
+                assert_eq!(id, 0);
+                assert_eq!(
+                    error,
+                    DeribitError {
+                        code: -32602,
+                        message: "Invalid params".to_string()
+                    }
+                );
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+
+        // Replies to public/test and public/set_heartbeat carry nothing the feed needs.
+        let test_reply = r#"{"jsonrpc":"2.0","id":42,"result":{"version":"1.2.26"}}"#;
+        assert!(parse_deribit_message(test_reply).is_none());
+        assert!(parse_deribit_message(r#"{"jsonrpc":"2.0","id":1,"result":"ok"}"#).is_none());
+        assert!(parse_deribit_message("not json").is_none());
+    }
+
+    #[test]
+    fn test_parse_deribit_depth() {
+        for depth in DERIBIT_DEPTHS {
+            assert_eq!(parse_deribit_depth(&depth.to_string()), Ok(depth));
+        }
+        assert!(parse_deribit_depth("25").is_err());
+        assert!(parse_deribit_depth("0").is_err());
+        assert!(parse_deribit_depth("deep").is_err());
+    }
+
+    #[test]
+    fn test_parse_grouped_deribit_book() {
+        let text = r#"{"jsonrpc":"2.0","method":"subscription","params":{"channel":"book.BTC-31OCT25-140000-P.none.5.100ms","data":{"timestamp":1761000000000,"instrument_name":"BTC-31OCT25-140000-P","change_id":42,"bids":[[0.1405,12.0],[0.14,3.5]],"asks":[[0.142,8.0]]}}}"#;
+        let data = serde_json::from_str::<DeribitResponse>(text)
+            .unwrap()
+            .params
+            .data;
+
+        let bids = parse_deribit_levels(data.bids);
+        assert_eq!(bids.len(), 2);
+        assert_eq!(bids[0].price, dec!(0.1405));
+        assert_eq!(bids[1].quantity, dec!(3.5));
+        assert_eq!(parse_deribit_levels(data.asks)[0].price, dec!(0.142));
+    }
+
+    #[test]
+    fn test_parse_bybit_book() {
+        let text = r#"{"topic":"orderbook.25.BTC-31OCT25-140000-P","type":"delta","ts":1761000000000,"data":{"s":"BTC-31OCT25-140000-P","b":[["0.1405","12"],["0.14","0"]],"a":[["0.142","8.5"]],"u":42,"seq":1001}}"#;
+        let resp = serde_json::from_str::<BybitResponse>(text).unwrap();
+        assert_eq!(resp.topic, "orderbook.25.BTC-31OCT25-140000-P");
+        assert_eq!(resp.type_, "delta");
+        assert_eq!(resp.data.s, "BTC-31OCT25-140000-P");
+
+        let bids = parse_bybit_levels(resp.data.b);
+        assert_eq!(bids.len(), 2);
+        assert_eq!(bids[0].price, dec!(0.1405));
+        assert_eq!(bids[1].quantity, dec!(0));
+        assert_eq!(parse_bybit_levels(resp.data.a)[0].quantity, dec!(8.5));
+    }
+
+    #[test]
+    fn test_binance_depth_replaces_the_book() {
+        let symbol = "BTC-251031-140000-P";
+        let first = r#"{"stream":"BTC-251031-140000-P@depth20@100ms","data":{"e":"depth","E":1761000000000,"T":1761000000000,"s":"BTC-251031-140000-P","u":100,"pu":99,"b":[["1405","2"],["1400","3"]],"a":[["1420","1"],["1425","4"]]}}"#;
+        // 1400 was pulled and 1425 dropped out of the top levels.
+        let second = r#"{"stream":"BTC-251031-140000-P@depth20@100ms","data":{"e":"depth","E":1761000000100,"T":1761000000100,"s":"BTC-251031-140000-P","u":101,"pu":100,"b":[["1405","2"]],"a":[["1420","1"]]}}"#;
+
+        let mut book = OrderBook::new(symbol.to_string(), Exchange::Binance);
+        for text in [first, second] {
+            let update = binance_depth_snapshot(symbol, text, Instant::now()).unwrap();
+            assert!(matches!(update, OrderBookUpdate::Snapshot { .. }));
+            book.apply_update(&update).unwrap();
+        }
+        assert_eq!(book.bids.keys().collect::<Vec<_>>(), [&dec!(1405)]);
+        assert_eq!(book.asks.keys().collect::<Vec<_>>(), [&dec!(1420)]);
+
+        assert!(
+            binance_depth_snapshot(symbol, r#"{"result":null,"id":1}"#, Instant::now()).is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_okex_snapshot_action() {
+        let text = r#"{"arg":{"channel":"books","instId":"BTC-USD-251031-140000-P"},"action":"snapshot","data":[{"asks":[["0.142","8","0","1"]],"bids":[["0.1405","12","0","2"]],"ts":"1761000000000","checksum":0,"seqId":100,"prevSeqId":-1}]}"#;
+        let resp = serde_json::from_str::<OkexResponse>(text).unwrap();
+        assert_eq!(resp.action, Some(OkexAction::Snapshot));
+        assert_eq!(
+            parse_okex_levels(resp.data[0].bids.clone())[0].price,
+            dec!(0.1405)
+        );
+
+        let text = r#"{"arg":{"channel":"books","instId":"BTC-USD-251031-140000-P"},"action":"update","data":[{"asks":[],"bids":[["0.1405","0","0","0"]],"ts":"1761000000100","checksum":0,"seqId":101,"prevSeqId":100}]}"#;
+        assert_eq!(
+            serde_json::from_str::<OkexResponse>(text).unwrap().action,
+            Some(OkexAction::Update)
+        );
+
+        let text = r#"{"arg":{"channel":"books5","instId":"BTC-USD-251031-140000-P"},"data":[{"asks":[],"bids":[],"ts":"1761000000000","seqId":101}]}"#;
+        assert!(
+            serde_json::from_str::<OkexResponse>(text)
+                .unwrap()
+                .action
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_subscription_acks() {
+        let okex = r#"{"event":"subscribe","arg":{"channel":"books","instId":"BTC-USD-251031-140000-P"},"connId":"a4d3ae55"}"#;
+        assert_eq!(okex_subscription_ack(okex).as_deref(), Some("books"));
+        let okex_error =
+            r#"{"event":"error","code":"60012","msg":"Invalid request","connId":"a4d3ae55"}"#;
+        assert_eq!(okex_subscription_ack(okex_error), None);
+
+        let deribit = r#"{"jsonrpc":"2.0","id":0,"result":["book.BTC-31OCT25-140000-P.none.20.100ms"],"usIn":1,"usOut":2,"usDiff":1,"testnet":false}"#;
+        match parse_deribit_message(deribit) {
+            Some(DeribitMessage::SubscribeResult { id, result }) => {
+                assert_eq!(id, DERIBIT_SUBSCRIBE_ID);
+                assert_eq!(result, ["book.BTC-31OCT25-140000-P.none.20.100ms"]);
+            }
+            other => panic!("expected a subscribe result, got {other:?}"),
+        }
+
+        assert!(binance_subscription_ack(r#"{"result":null,"id":1}"#));
+        assert!(!binance_subscription_ack(
+            r#"{"error":{"code":2,"msg":"Invalid request"},"id":1}"#
+        ));
+
+        assert!(bybit_subscription_ack(
+            r#"{"success":true,"ret_msg":"","conn_id":"2324d924","req_id":"","op":"subscribe"}"#
+        ));
+        assert!(!bybit_subscription_ack(
+            r#"{"success":true,"ret_msg":"pong","conn_id":"2324d924","op":"pong"}"#
+        ));
     }
 }
@@ -0,0 +1,271 @@
+use crate::{
+    orderbook::{ArbitrageOpportunity, Exchange, SpotPrices},
+    parsing_utils::{InstrumentValidator, OptionType},
+};
+use chrono::{DateTime, NaiveTime, Utc};
+use rust_decimal::Decimal;
+use statrs::distribution::{Continuous, ContinuousCDF, Normal};
+
+/// Newton-Raphson gives up after this many steps.
+const MAX_ITERATIONS: usize = 100;
+/// Stop once the model price is within this of the market price.
+const PRICE_TOLERANCE: f64 = 1e-8;
+/// Starting guess: 50% is near the middle of crypto option volatilities.
+const INITIAL_VOLATILITY: f64 = 0.5;
+/// Options on every supported venue expire at 08:00 UTC.
+const EXPIRY_TIME: NaiveTime = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 60.0 * 60.0;
+
+/// Black-Scholes price and vega of a European option.
+fn price_and_vega(
+    spot: f64,
+    strike: f64,
+    t: f64,
+    rate: f64,
+    vol: f64,
+    is_call: bool,
+) -> (f64, f64) {
+    let n = Normal::standard();
+    let sqrt_t = t.sqrt();
+    let d1 = ((spot / strike).ln() + (rate + vol * vol / 2.0) * t) / (vol * sqrt_t);
+    let d2 = d1 - vol * sqrt_t;
+    let discounted_strike = strike * (-rate * t).exp();
+    let price = if is_call {
+        spot * n.cdf(d1) - discounted_strike * n.cdf(d2)
+    } else {
+        discounted_strike * n.cdf(-d2) - spot * n.cdf(-d1)
+    };
+    (price, spot * n.pdf(d1) * sqrt_t)
+}
+
+/// Black-Scholes implied volatility (0.55 = 55%) of a European option, with `option_price` in
+/// the same currency as `spot` and `strike`. `None` when an input is out of range, the price
+/// is outside the no-arbitrage bounds, or Newton-Raphson does not converge.
+pub fn implied_volatility(
+    option_price: Decimal,
+    spot: Decimal,
+    strike: Decimal,
+    time_to_expiry_years: Decimal,
+    risk_free_rate: Decimal,
+    is_call: bool,
+) -> Option<Decimal> {
+    let [price, spot, strike, t, rate] = [
+        option_price,
+        spot,
+        strike,
+        time_to_expiry_years,
+        risk_free_rate,
+    ]
+    .map(|d| f64::try_from(d).ok());
+    let (price, spot, strike, t, rate) = (price?, spot?, strike?, t?, rate?);
+    if price <= 0.0 || spot <= 0.0 || strike <= 0.0 || t <= 0.0 {
+        return None;
+    }
+
+    let discounted_strike = strike * (-rate * t).exp();
+    let (lower, upper) = if is_call {
+        ((spot - discounted_strike).max(0.0), spot)
+    } else {
+        ((discounted_strike - spot).max(0.0), discounted_strike)
+    };
+    if price <= lower || price >= upper {
+        return None;
+    }
+
+    let mut vol = INITIAL_VOLATILITY;
+    for _ in 0..MAX_ITERATIONS {
+        let (model, vega) = price_and_vega(spot, strike, t, rate, vol, is_call);
+        let diff = model - price;
+        if diff.abs() < PRICE_TOLERANCE {
+            return Decimal::try_from(vol).ok().map(|v| v.round_dp(6));
+        }
+        if vega < f64::EPSILON {
+            return None;
+        }
+        // Overshooting below zero is common for deep out-of-the-money options.
+        vol = (vol - diff / vega).max(vol / 2.0);
+    }
+    None
+}
+
+/// Implied volatilities of the best level of each leg of an opportunity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IvSpread {
+    /// Of the bid hit on the sell venue.
+    pub bid_iv: Decimal,
+    /// Of the ask lifted on the buy venue.
+    pub ask_iv: Decimal,
+}
+
+impl IvSpread {
+    /// Volatility points (percent) the bid sits above the ask.
+    pub fn spread_points(&self) -> Decimal {
+        (self.bid_iv - self.ask_iv) * Decimal::ONE_HUNDRED
+    }
+}
+
+impl std::fmt::Display for IvSpread {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "IV bid {}% vs ask {}% ({} vol pts)",
+            (self.bid_iv * Decimal::ONE_HUNDRED).round_dp(2),
+            (self.ask_iv * Decimal::ONE_HUNDRED).round_dp(2),
+            self.spread_points().round_dp(2)
+        )
+    }
+}
+
+/// Okex and Deribit quote options in the underlying coin, Binance and Bybit in dollars. An
+/// aggregated book's prices are taken as they are.
+fn usd_price(exchange: &Exchange, price: Decimal, spot: Decimal) -> Decimal {
+    match exchange {
+        Exchange::Okex | Exchange::Deribit => price * spot,
+        Exchange::Binance | Exchange::Bybit | Exchange::Aggregated => price,
+    }
+}
+
+/// IVs of the opportunity's first (best) trade level as of `now`. `None` when the symbol is not
+/// a recognised option, there is no spot price for its underlying, or either IV cannot be
+/// solved for.
+pub fn opportunity_iv_spread(
+    opportunity: &ArbitrageOpportunity,
+    spot_prices: &SpotPrices,
+    risk_free_rate: Decimal,
+    now: DateTime<Utc>,
+) -> Option<IvSpread> {
+    let instrument =
+        InstrumentValidator::parse_symbol(Some(&opportunity.sell_exchange), &opportunity.symbol)
+            .ok()?;
+    let spot = spot_prices.for_underlying(&instrument.underlying)?;
+    let trade = opportunity.trades.first()?;
+    let expiry = instrument.expiry_date.and_time(EXPIRY_TIME).and_utc();
+    let years = (expiry - now).num_seconds() as f64 / SECONDS_PER_YEAR;
+    let years = Decimal::try_from(years).ok()?;
+    let is_call = instrument.option_type == OptionType::Call;
+    let iv = |exchange, price| {
+        implied_volatility(
+            usd_price(exchange, price, spot),
+            spot,
+            instrument.strike,
+            years,
+            risk_free_rate,
+            is_call,
+        )
+    };
+    Some(IvSpread {
+        bid_iv: iv(&opportunity.sell_exchange, trade.sell_price)?,
+        ask_iv: iv(&opportunity.buy_exchange, trade.buy_price)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::TradeLevel;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_implied_volatility_recovers_textbook_prices() {
+        // S = K = 100, one year, 5% rate, 20% volatility
+        let call = implied_volatility(
+            dec!(10.4506),
+            dec!(100),
+            dec!(100),
+            dec!(1),
+            dec!(0.05),
+            true,
+        )
+        .unwrap();
+        assert!((call - dec!(0.2)).abs() < dec!(0.0001), "{call}");
+        let put = implied_volatility(
+            dec!(5.5735),
+            dec!(100),
+            dec!(100),
+            dec!(1),
+            dec!(0.05),
+            false,
+        )
+        .unwrap();
+        assert!((put - dec!(0.2)).abs() < dec!(0.0001), "{put}");
+    }
+
+    #[test]
+    fn test_implied_volatility_round_trips() {
+        for (vol, strike, is_call) in [
+            (0.35, 120.0, true),
+            (0.8, 60.0, false),
+            (1.5, 150.0, true),
+            (0.6, 100.0, false),
+        ] {
+            let (price, _) = price_and_vega(100.0, strike, 0.25, 0.03, vol, is_call);
+            let iv = implied_volatility(
+                Decimal::try_from(price).unwrap(),
+                dec!(100),
+                Decimal::try_from(strike).unwrap(),
+                dec!(0.25),
+                dec!(0.03),
+                is_call,
+            )
+            .unwrap();
+            let iv = f64::try_from(iv).unwrap();
+            assert!((iv - vol).abs() < 1e-4, "{vol} -> {iv}");
+        }
+    }
+
+    #[test]
+    fn test_implied_volatility_rejects_arbitrage_prices() {
+        // Below intrinsic value
+        assert_eq!(
+            implied_volatility(dec!(1), dec!(120), dec!(100), dec!(1), dec!(0), true),
+            None
+        );
+        // A call worth more than the underlying
+        assert_eq!(
+            implied_volatility(dec!(101), dec!(100), dec!(100), dec!(1), dec!(0), true),
+            None
+        );
+        // Expired
+        assert_eq!(
+            implied_volatility(dec!(5), dec!(100), dec!(100), dec!(0), dec!(0), true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_opportunity_iv_spread() {
+        let trade = TradeLevel {
+            buy_price: dec!(0.0400),
+            sell_price: dec!(0.0450),
+            quantity: dec!(10),
+            normalized_quantity: dec!(1),
+            gross_profit: dec!(0.005),
+            fee_cost: Decimal::ZERO,
+            profit: dec!(0.005),
+        };
+        let opportunity = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "BTC-31OCT25-100000-C".to_string(),
+            total_profit: trade.profit,
+            total_volume: trade.quantity,
+            trades: vec![trade],
+        };
+        let now = Utc.with_ymd_and_hms(2025, 10, 1, 8, 0, 0).unwrap();
+        let spot_prices = SpotPrices {
+            btc: Some(dec!(100000)),
+            eth: None,
+        };
+
+        let spread = opportunity_iv_spread(&opportunity, &spot_prices, dec!(0.05), now).unwrap();
+        assert!(spread.bid_iv > spread.ask_iv);
+        assert!(spread.spread_points() > Decimal::ZERO);
+        assert!(spread.to_string().starts_with("IV bid "));
+
+        assert_eq!(
+            opportunity_iv_spread(&opportunity, &SpotPrices::default(), dec!(0.05), now),
+            None
+        );
+    }
+}
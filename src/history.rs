@@ -0,0 +1,157 @@
+use crate::orderbook::ArbitrageOpportunity;
+use rust_decimal::Decimal;
+use std::{
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+/// The most recent reported opportunities, oldest first, capped at `capacity` entries.
+#[derive(Debug, Clone)]
+pub struct ArbitrageHistory {
+    capacity: usize,
+    entries: VecDeque<(SystemTime, ArbitrageOpportunity)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryStats {
+    pub count: usize,
+    pub total_profit: Decimal,
+    /// Zero when the history is empty.
+    pub avg_profit: Decimal,
+    /// Zero when the history is empty.
+    pub max_profit: Decimal,
+}
+
+impl ArbitrageHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn push(&mut self, opp: ArbitrageOpportunity) {
+        self.push_at(SystemTime::now(), opp);
+    }
+
+    fn push_at(&mut self, at: SystemTime, opp: ArbitrageOpportunity) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((at, opp));
+    }
+
+    /// Entries recorded within `dur` of now, oldest first.
+    pub fn opportunities_in_last(
+        &self,
+        dur: Duration,
+    ) -> impl Iterator<Item = &(SystemTime, ArbitrageOpportunity)> {
+        let cutoff = SystemTime::now()
+            .checked_sub(dur)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        // Entries are pushed in time order, so everything after the first recent one is recent.
+        let start = self.entries.partition_point(|(at, _)| *at < cutoff);
+        self.entries.range(start..)
+    }
+
+    pub fn stats(&self) -> HistoryStats {
+        let count = self.entries.len();
+        let total_profit: Decimal = self.entries.iter().map(|(_, opp)| opp.total_profit).sum();
+        let max_profit = self
+            .entries
+            .iter()
+            .map(|(_, opp)| opp.total_profit)
+            .max()
+            .unwrap_or_default();
+        let avg_profit = if count == 0 {
+            Decimal::ZERO
+        } else {
+            total_profit / Decimal::from(count)
+        };
+
+        HistoryStats {
+            count,
+            total_profit,
+            avg_profit,
+            max_profit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::opportunity;
+    use rust_decimal_macros::dec;
+
+    const SYMBOL: &str = "BTC-USD-251031-140000-P";
+
+    #[test]
+    fn test_evicts_oldest_at_capacity() {
+        let mut history = ArbitrageHistory::new(2);
+        history.push(opportunity(SYMBOL, dec!(0.1)));
+        history.push(opportunity(SYMBOL, dec!(0.2)));
+        history.push(opportunity(SYMBOL, dec!(0.3)));
+
+        assert_eq!(history.len(), 2);
+        let stats = history.stats();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.total_profit, dec!(0.5));
+        assert_eq!(stats.avg_profit, dec!(0.25));
+        assert_eq!(stats.max_profit, dec!(0.3));
+    }
+
+    #[test]
+    fn test_opportunities_in_last_window() {
+        let mut history = ArbitrageHistory::new(10);
+        let now = SystemTime::now();
+        history.push_at(
+            now - Duration::from_secs(600),
+            opportunity(SYMBOL, dec!(0.1)),
+        );
+        history.push_at(
+            now - Duration::from_secs(30),
+            opportunity(SYMBOL, dec!(0.2)),
+        );
+        history.push(opportunity(SYMBOL, dec!(0.3)));
+
+        let recent: Vec<Decimal> = history
+            .opportunities_in_last(Duration::from_secs(60))
+            .map(|(_, opp)| opp.total_profit)
+            .collect();
+        assert_eq!(recent, [dec!(0.2), dec!(0.3)]);
+        assert_eq!(
+            history
+                .opportunities_in_last(Duration::from_secs(3600))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_empty_stats() {
+        let mut history = ArbitrageHistory::new(0);
+        history.push(opportunity(SYMBOL, dec!(0.1)));
+        assert!(history.is_empty());
+        assert_eq!(
+            history.stats(),
+            HistoryStats {
+                count: 0,
+                total_profit: Decimal::ZERO,
+                avg_profit: Decimal::ZERO,
+                max_profit: Decimal::ZERO,
+            }
+        );
+    }
+}
@@ -0,0 +1,23 @@
+pub mod alert;
+#[cfg(feature = "auth")]
+pub mod auth;
+pub mod calendar;
+pub mod config;
+#[cfg(feature = "sqlite")]
+pub mod db;
+pub mod exchanges;
+pub mod greeks;
+pub mod history;
+pub mod metrics;
+pub mod orderbook;
+pub mod output;
+pub mod parsing_utils;
+pub mod recorder;
+pub mod registry;
+pub mod rest;
+pub mod scanner;
+#[cfg(any(test, feature = "simulation"))]
+pub mod simulation;
+pub mod store;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
@@ -1,108 +1,1281 @@
-mod exchanges;
-mod orderbook;
-mod parsing_utils;
-
-use crate::{
-    exchanges::{deribit_websocket_task, okex_websocket_task},
-    orderbook::{ArbitrageDetector, Exchange, OrderBook, OrderBookUpdate},
-};
 use anyhow::Result;
-use clap::Parser;
-use parsing_utils::InstrumentValidator;
-use std::collections::HashMap;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum, parser::ValueSource};
+#[cfg(feature = "sqlite")]
+use options_arbitrage::db;
+use options_arbitrage::{
+    alert::WebhookNotifier,
+    config::{AppConfig, EXAMPLE_CONFIG},
+    exchanges::{ExchangeAdapter, FeedConfig, OkexBookChannel, adapter_for, parse_deribit_depth},
+    history::ArbitrageHistory,
+    metrics::{self, Metrics},
+    orderbook::{
+        ArbitrageDetector, ArbitrageOpportunity, Exchange, FeeConfig, OrderBook, OrderBookUpdate,
+        OutputFormat, RenderOptions, SpotPrices,
+    },
+    output::{Output, write_opportunity},
+    parsing_utils::{ExpiryClass, InstrumentValidator, SymbolPair, parse_symbols_file},
+    recorder::{self, RecordingReader, RecordingWriter},
+    registry::{BookKey, BookRegistry},
+    rest,
+    scanner::ScannerReport,
+    store::OpportunityStore,
+};
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{Arc, atomic::Ordering},
+    time::{Duration, Instant},
+};
 use tokio::sync::mpsc;
+use tracing::{debug, debug_span, error, info, warn};
+use tracing_subscriber::{
+    EnvFilter, fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
 struct Args {
-    #[arg(long, required = true)]
-    okex_symbol: String,
-    #[arg(long, required = true)]
-    deribit_symbol: String,
+    /// Venue of the first leg: okex, deribit, binance or bybit
+    #[arg(long, default_value = "okex")]
+    exchange_a: Exchange,
+    #[arg(long, required_unless_present_any = ["symbols_file", "config", "generate_config"])]
+    symbol_a: Option<String>,
+    /// Venue of the second leg: okex, deribit, binance or bybit
+    #[arg(long, default_value = "deribit")]
+    exchange_b: Exchange,
+    #[arg(long, required_unless_present_any = ["symbols_file", "config", "generate_config"])]
+    symbol_b: Option<String>,
+    /// Optional Binance symbol (e.g. BTC-251031-140000-P) to add a third venue
+    #[arg(long)]
+    binance_symbol: Option<String>,
+    /// Optional Bybit symbol (e.g. BTC-31OCT25-140000-P) to add another venue
+    #[arg(long)]
+    bybit_symbol: Option<String>,
+    /// File with one `okex_symbol,deribit_symbol[,binance_symbol[,bybit_symbol]]` entry per
+    /// line to monitor concurrently instead of the single pair given on the command line
+    #[arg(
+        long,
+        conflicts_with_all = ["symbol_a", "symbol_b", "binance_symbol", "bybit_symbol"]
+    )]
+    symbols_file: Option<PathBuf>,
+    /// Minimum total profit required to report an opportunity, in units of the underlying
+    #[arg(long, default_value_t = 0.0)]
+    min_profit: f64,
+    /// Ignore price differences narrower than this many basis points of the buy price
+    #[arg(long, default_value_t = 0.0)]
+    min_spread_bps: f64,
+    /// Skip detection while either book holds less than this total volume on either side
+    #[arg(long, default_value_t = 0.0)]
+    min_book_depth: f64,
+    /// Order book levels scanned per side when looking for arbitrage (matches the default Deribit depth)
+    #[arg(long, default_value_t = 20)]
+    max_depth: usize,
+    /// Order book levels kept per side; worse levels are dropped to bound memory
+    #[arg(long, default_value_t = 400)]
+    max_book_depth: usize,
+    /// Book updates queued for detection; feeds drop updates once this many are waiting
+    #[arg(long, default_value_t = 1000)]
+    channel_capacity: usize,
+    /// Cap on contracts traded per opportunity (0 = unlimited)
+    #[arg(long, default_value_t = 0.0)]
+    max_position_size: f64,
+    /// Skip instruments with fewer than this many days to expiry (e.g. to avoid pin risk)
+    #[arg(long)]
+    min_dte: Option<i64>,
+    /// Only monitor instruments of this expiry cycle
+    #[arg(long, value_enum)]
+    expiry_class: Option<ExpiryClass>,
+    /// Log level filter used when RUST_LOG is not set (e.g. info, debug, warn)
+    #[arg(long, default_value = "info")]
+    log_level: String,
+    /// Full tracing filter used instead of --log-level when RUST_LOG is not set
+    /// (e.g. options_arbitrage=debug,tokio_tungstenite=warn)
+    #[arg(long, conflicts_with = "log_level", value_parser = parse_log_filter)]
+    log_filter: Option<String>,
+    /// Okex taker fee rate as a fraction of price (e.g. 0.0003 for 0.03%)
+    #[arg(long, default_value_t = 0.0003)]
+    fee_rate_okex: f64,
+    /// Deribit taker fee rate as a fraction of price (e.g. 0.0003 for 0.03%)
+    #[arg(long, default_value_t = 0.0003)]
+    fee_rate_deribit: f64,
+    /// Binance taker fee rate as a fraction of price (e.g. 0.0003 for 0.03%)
+    #[arg(long, default_value_t = 0.0003)]
+    fee_rate_binance: f64,
+    /// Bybit taker fee rate as a fraction of price (e.g. 0.0003 for 0.03%)
+    #[arg(long, default_value_t = 0.0003)]
+    fee_rate_bybit: f64,
+    /// How reported opportunities are written: text logs, NDJSON or CSV on stdout
+    #[arg(long, value_enum, default_value = "text")]
+    output_format: OutputFormat,
+    /// Append reported opportunities to this file instead of stdout; logs then go to stderr
+    #[arg(long)]
+    output_file: Option<PathBuf>,
+    /// Disable colored output (also disabled when NO_COLOR is set)
+    #[arg(long)]
+    no_color: bool,
+    /// Print one line per opportunity instead of every execution step (text output only)
+    #[arg(long)]
+    compact: bool,
+    /// BTC spot price in USD, to also show BTC option profits in USD
+    #[arg(long)]
+    btc_spot_price: Option<f64>,
+    /// ETH spot price in USD, to also show ETH option profits in USD
+    #[arg(long)]
+    eth_spot_price: Option<f64>,
+    /// Also show the implied volatility of each leg's best price (text output only). Needs the
+    /// underlying's spot price and --risk-free-rate
+    #[arg(long, requires = "risk_free_rate")]
+    iv_mode: bool,
+    /// Annual risk-free rate used by --iv-mode, as a fraction (e.g. 0.05)
+    #[arg(long)]
+    risk_free_rate: Option<f64>,
+    /// Fetch spot prices automatically (not implemented yet)
+    #[arg(long, conflicts_with_all = ["btc_spot_price", "eth_spot_price"])]
+    fetch_spot: bool,
+    /// SQLite database to persist detected opportunities into (created if absent)
+    #[cfg(feature = "sqlite")]
+    #[arg(long)]
+    db_path: Option<PathBuf>,
+    /// Write spans to this file in Chrome's trace format (open it in chrome://tracing or Perfetto)
+    #[cfg(feature = "chrome")]
+    #[arg(long)]
+    chrome_trace: Option<PathBuf>,
+    /// Deribit order book depth to subscribe to (1, 5, 10, 20, 50 or 100)
+    #[arg(long, default_value_t = 20, value_parser = parse_deribit_depth)]
+    deribit_depth: u32,
+    /// Okex order book channel: full book (batched or tick-by-tick), top 5 levels or best
+    /// bid/offer. `books-l2-tbt` resubscribes on any break in the prevSeqId chain
+    #[arg(long, alias = "okex-depth", value_enum, default_value = "books")]
+    okex_channel: OkexBookChannel,
+    /// Okex API key, to log in before subscribing (needs --okex-api-secret and --okex-passphrase)
+    #[cfg(feature = "auth")]
+    #[arg(long, requires_all = ["okex_api_secret", "okex_passphrase"])]
+    okex_api_key: Option<String>,
+    /// Okex API secret key
+    #[cfg(feature = "auth")]
+    #[arg(long, requires = "okex_api_key")]
+    okex_api_secret: Option<String>,
+    /// Okex API passphrase
+    #[cfg(feature = "auth")]
+    #[arg(long, requires = "okex_api_key")]
+    okex_passphrase: Option<String>,
+    /// Poll the Okex and Deribit REST order book endpoints instead of streaming them over
+    /// websockets (other venues keep their websocket feed)
+    #[arg(long)]
+    rest_mode: bool,
+    /// Milliseconds between REST polls in `--rest-mode`
+    #[arg(long, default_value_t = 1000, requires = "rest_mode")]
+    watch_interval: u64,
+    /// Stop reconnecting to an exchange after this many failed attempts in a row (0 = retry
+    /// forever)
+    #[arg(long, default_value_t = 0)]
+    max_reconnect_attempts: u32,
+    /// Warn, and stop detecting on a book, once it has gone this many seconds without an update
+    #[arg(long, default_value_t = 30)]
+    stale_threshold_secs: u64,
+    /// Seconds between per-exchange message throughput reports
+    #[arg(long, default_value_t = 60)]
+    stats_interval: u64,
+    /// Seconds between cross-symbol scanner reports when monitoring several pairs
+    #[arg(long, default_value_t = 10)]
+    report_interval: u64,
+    /// Number of recent opportunities kept for the shutdown summary
+    #[arg(long, default_value_t = 1000)]
+    history_size: usize,
+    /// Seconds reported opportunities stay in the in-memory store the scanner report counts
+    /// recent opportunities from
+    #[arg(long, default_value_t = 3600)]
+    store_max_age_secs: u64,
+    /// POST each newly reported opportunity as JSON to this URL (at most once a second per symbol)
+    #[arg(long)]
+    alert_webhook: Option<String>,
+    /// Serve Prometheus metrics on 0.0.0.0:<PORT>/metrics
+    #[arg(long)]
+    metrics_port: Option<u16>,
+    /// TOML config file; flags given on the command line take precedence over its values
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Print a documented example config file to stdout and exit
+    #[arg(long)]
+    generate_config: bool,
+    /// Write every order book update to this file for later `--replay`
+    #[arg(long)]
+    record: Option<PathBuf>,
+    /// Feed updates from a `--record` file instead of connecting to the exchanges
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// Replay pacing multiplier (e.g. 10.0 replays ten times faster than recorded)
+    #[arg(long, default_value_t = 1.0, requires = "replay")]
+    replay_speed: f64,
+    /// Symbol pairs taken from the config file
+    #[arg(skip)]
+    config_symbols: Vec<SymbolPair>,
+}
+
+impl Args {
+    /// Takes every setting from `config` unless the matching flag was given on the command line.
+    fn apply_config(&mut self, config: &AppConfig, matches: &ArgMatches) -> Result<(), String> {
+        let from_config = |id: &str| matches.value_source(id) != Some(ValueSource::CommandLine);
+
+        if from_config("fee_rate_okex") {
+            self.fee_rate_okex = config.okex_fee_bps / 10_000.0;
+        }
+        if from_config("fee_rate_deribit") {
+            self.fee_rate_deribit = config.deribit_fee_bps / 10_000.0;
+        }
+        if from_config("fee_rate_binance") {
+            self.fee_rate_binance = config.binance_fee_bps / 10_000.0;
+        }
+        if from_config("fee_rate_bybit") {
+            self.fee_rate_bybit = config.bybit_fee_bps / 10_000.0;
+        }
+        if from_config("min_profit") {
+            self.min_profit = config.min_profit;
+        }
+        if from_config("max_position_size") {
+            self.max_position_size = config.max_position_size;
+        }
+        if from_config("min_dte") {
+            self.min_dte = Some(config.min_dte.into());
+        }
+        if from_config("max_reconnect_attempts") {
+            self.max_reconnect_attempts = config.reconnect_max_attempts;
+        }
+        if from_config("output_format") {
+            self.output_format = OutputFormat::from_str(&config.output_format, true)
+                .map_err(|e| format!("invalid output_format in config: {e}"))?;
+        }
+        self.config_symbols = config.symbols.clone();
+        Ok(())
+    }
+}
+
+/// Running totals for the current session, summarized on shutdown.
+struct SessionStats {
+    started_at: Instant,
+    opportunities: u64,
+    cumulative_profit: Decimal,
+    best: Option<ArbitrageOpportunity>,
+    history: ArbitrageHistory,
+}
+
+impl SessionStats {
+    fn new(history_size: usize) -> Self {
+        Self {
+            started_at: Instant::now(),
+            opportunities: 0,
+            cumulative_profit: Decimal::ZERO,
+            best: None,
+            history: ArbitrageHistory::new(history_size),
+        }
+    }
+
+    fn record(&mut self, opp: &ArbitrageOpportunity) {
+        self.history.push(opp.clone());
+        self.opportunities += 1;
+        self.cumulative_profit += opp.total_profit;
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| opp.total_profit > best.total_profit)
+        {
+            self.best = Some(opp.clone());
+        }
+    }
+
+    fn print_summary(&self) {
+        let runtime = self.started_at.elapsed();
+        let minutes = runtime.as_secs_f64() / 60.0;
+        let per_minute = if minutes > 0.0 {
+            self.opportunities as f64 / minutes
+        } else {
+            0.0
+        };
+
+        info!(
+            runtime_secs = runtime.as_secs(),
+            opportunities = self.opportunities,
+            cumulative_profit = %self.cumulative_profit,
+            opportunities_per_minute = format!("{per_minute:.2}"),
+            "Session summary"
+        );
+        if let Some(best) = &self.best {
+            info!(
+                symbol = %best.symbol,
+                buy_exchange = %best.buy_exchange,
+                sell_exchange = %best.sell_exchange,
+                total_profit = %best.total_profit,
+                total_volume = %best.total_volume,
+                "Most profitable opportunity"
+            );
+        }
+
+        let recent = self.history.stats();
+        info!(
+            count = recent.count,
+            total_profit = %recent.total_profit,
+            avg_profit = %recent.avg_profit.round_dp(8),
+            max_profit = %recent.max_profit,
+            "Recent opportunities"
+        );
+    }
+}
+
+fn parse_log_filter(s: &str) -> Result<String, String> {
+    EnvFilter::try_new(s)
+        .map(|_| s.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Anything above this is almost certainly a percentage passed where a fraction was expected.
+const MAX_FEE_RATE: f64 = 0.05;
+
+fn parse_fee_rate(flag: &str, rate: f64) -> Result<Decimal, String> {
+    if !(0.0..=MAX_FEE_RATE).contains(&rate) {
+        return Err(format!(
+            "--{flag} must be a fraction between 0 and {MAX_FEE_RATE} (e.g. 0.0003 for 0.03%), \
+             got {rate}"
+        ));
+    }
+    Decimal::try_from(rate).map_err(|e| format!("--{flag} is not a valid number: {e}"))
+}
+
+fn fee_config_from_args(args: &Args) -> Result<FeeConfig, String> {
+    let bps_scale = Decimal::from(10_000);
+    Ok(FeeConfig {
+        okex_taker_bps: parse_fee_rate("fee-rate-okex", args.fee_rate_okex)? * bps_scale,
+        deribit_taker_bps: parse_fee_rate("fee-rate-deribit", args.fee_rate_deribit)? * bps_scale,
+        binance_taker_bps: parse_fee_rate("fee-rate-binance", args.fee_rate_binance)? * bps_scale,
+        bybit_taker_bps: parse_fee_rate("fee-rate-bybit", args.fee_rate_bybit)? * bps_scale,
+    })
+}
+
+fn parse_spot_price(flag: &str, price: Option<f64>) -> Result<Option<Decimal>, String> {
+    let Some(price) = price else {
+        return Ok(None);
+    };
+    if !(price.is_finite() && price > 0.0) {
+        return Err(format!(
+            "--{flag} must be a positive USD price, got {price}"
+        ));
+    }
+    Decimal::try_from(price)
+        .map(Some)
+        .map_err(|e| format!("--{flag} is not a valid number: {e}"))
+}
+
+fn spot_prices_from_args(args: &Args) -> Result<SpotPrices, String> {
+    Ok(SpotPrices {
+        btc: parse_spot_price("btc-spot-price", args.btc_spot_price)?,
+        eth: parse_spot_price("eth-spot-price", args.eth_spot_price)?,
+    })
+}
+
+/// The rate to solve implied volatilities at, or `None` without `--iv-mode`.
+fn iv_rate_from_args(args: &Args, spot_prices: &SpotPrices) -> Result<Option<Decimal>, String> {
+    if !args.iv_mode {
+        return Ok(None);
+    }
+    if spot_prices.btc.is_none() && spot_prices.eth.is_none() {
+        return Err("--iv-mode needs --btc-spot-price or --eth-spot-price".to_string());
+    }
+    let rate = args.risk_free_rate.unwrap_or_default();
+    Decimal::try_from(rate)
+        .map(Some)
+        .map_err(|e| format!("--risk-free-rate is not a valid number: {e}"))
+}
+
+/// The pair given by `--exchange-a`/`--symbol-a` and `--exchange-b`/`--symbol-b`, plus any
+/// `--binance-symbol`/`--bybit-symbol`, or `None` when the symbols come from a file.
+fn pair_from_args(args: &Args) -> Result<Option<SymbolPair>, String> {
+    let (Some(symbol_a), Some(symbol_b)) = (&args.symbol_a, &args.symbol_b) else {
+        return Ok(None);
+    };
+    let mut pair = SymbolPair::default();
+    let venues = [
+        (args.exchange_a.clone(), Some(symbol_a)),
+        (args.exchange_b.clone(), Some(symbol_b)),
+        (Exchange::Binance, args.binance_symbol.as_ref()),
+        (Exchange::Bybit, args.bybit_symbol.as_ref()),
+    ];
+    for (exchange, symbol) in venues {
+        let Some(symbol) = symbol else { continue };
+        let Some(slot) = pair.symbol_mut(&exchange) else {
+            return Err(format!("{exchange} has no feed to monitor"));
+        };
+        if slot.is_some() {
+            return Err(format!("{exchange} is given more than one symbol"));
+        }
+        *slot = Some(symbol.clone());
+    }
+    Ok(Some(pair))
+}
+
+/// Thin books are usually a partial snapshot early in the session rather than real liquidity.
+fn has_min_depth(book: &OrderBook, min_volume: Decimal) -> bool {
+    book.total_bid_volume() >= min_volume && book.total_ask_volume() >= min_volume
+}
+
+/// Maps each book to the indices of the pairs it belongs to, so only those pairs are re-checked
+/// when it updates.
+fn pairs_by_book(pairs: &[SymbolPair]) -> HashMap<BookKey, Vec<usize>> {
+    let mut pair_by_book: HashMap<BookKey, Vec<usize>> = HashMap::new();
+    for (index, pair) in pairs.iter().enumerate() {
+        for key in pair.venues() {
+            pair_by_book.entry(key).or_default().push(index);
+        }
+    }
+    pair_by_book
+}
+
+/// Drops the opportunities of pairs with a book detection is suspended on: stale, or waiting for
+/// its subscription to be confirmed again. A pair is only re-checked when one of its books
+/// updates, so its last opportunities would otherwise stay in the scanner report.
+fn clear_suspended_pairs(
+    pairs: &[SymbolPair],
+    registry: &BookRegistry,
+    stale: &HashSet<BookKey>,
+    active_opportunities: &mut [Vec<ArbitrageOpportunity>],
+) {
+    for (pair, opportunities) in pairs.iter().zip(active_opportunities) {
+        if pair
+            .venues()
+            .iter()
+            .any(|venue| stale.contains(venue) || !registry.is_subscribed(venue))
+        {
+            opportunities.clear();
+        }
+    }
+}
+
+/// Warns once when a book goes stale and logs once when it recovers. `stale` holds the books
+/// currently reported as stale.
+fn report_stale_books<'a>(
+    books: impl IntoIterator<Item = (&'a BookKey, &'a OrderBook)>,
+    stale: &mut HashSet<BookKey>,
+    threshold: Duration,
+) {
+    for (key, book) in books {
+        if book.is_stale(threshold) {
+            if stale.insert(key.clone()) {
+                warn!(
+                    exchange = %book.exchange,
+                    symbol = %book.symbol,
+                    threshold_secs = threshold.as_secs(),
+                    "Order book is stale, detection suspended"
+                );
+            }
+        } else if stale.remove(key) {
+            info!(
+                exchange = %book.exchange,
+                symbol = %book.symbol,
+                "Order book updating again"
+            );
+        }
+    }
+}
+
+fn log_throughput(metrics: &Metrics, exchanges: &[Exchange]) {
+    for exchange in exchanges {
+        let Some(stats) = metrics.throughput.get(exchange) else {
+            continue;
+        };
+        let throughput = stats.take_throughput();
+        info!(
+            %exchange,
+            messages_per_sec = format!("{:.1}", throughput.messages_per_sec),
+            kb_per_sec = format!("{:.1}", throughput.kb_per_sec),
+            dropped_updates = stats.dropped_updates.load(Ordering::Relaxed),
+            "websocket throughput"
+        );
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let args = Args::parse();
-    let okex_symbol = args.okex_symbol;
-    let deribit_symbol = args.deribit_symbol;
+    // Listen before any startup work, so a signal sent meanwhile still shuts down cleanly.
+    let shutdown = shutdown_signal();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches)?;
 
-    println!(
-        "LET'S GOOO: Trying to find arbitrage between {okex_symbol} (Okex) and {deribit_symbol} \
-         (Deribit)"
-    );
+    if args.generate_config {
+        print!("{EXAMPLE_CONFIG}");
+        return Ok(());
+    }
+
+    if let Some(path) = &args.config {
+        let config = AppConfig::load(path)?;
+        if let Err(e) = args.apply_config(&config, &matches) {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    }
 
-    match InstrumentValidator::are_same_instrument(&okex_symbol, &deribit_symbol) {
-        Ok(true) => {}
-        Ok(false) => {
-            eprintln!("Error: Instruments do not match!");
-            eprintln!("Okex: {okex_symbol}");
-            eprintln!("Deribit: {deribit_symbol}");
-            return Ok(());
+    let fees = match fee_config_from_args(&args) {
+        Ok(fees) => fees,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
         }
+    };
+    let cli_pair = match pair_from_args(&args) {
+        Ok(pair) => pair,
         Err(e) => {
-            eprintln!("Failed to parse instruments: {e}");
-            return Ok(());
+            eprintln!("Error: {e}");
+            std::process::exit(2);
         }
+    };
+
+    if !(args.replay_speed.is_finite() && args.replay_speed > 0.0) {
+        eprintln!(
+            "Error: --replay-speed must be a positive number, got {}",
+            args.replay_speed
+        );
+        std::process::exit(2);
     }
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<OrderBookUpdate>();
+    // RUST_LOG wins over both flags.
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(args.log_filter.as_deref().unwrap_or(&args.log_level)));
+    let filter_directives = filter.to_string();
+    // https://no-color.org: any non-empty NO_COLOR disables color.
+    let color = !args.no_color && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty());
+    if args.fetch_spot {
+        eprintln!(
+            "Error: --fetch-spot is not implemented yet; pass --btc-spot-price/--eth-spot-price"
+        );
+        std::process::exit(2);
+    }
+    let spot_prices = match spot_prices_from_args(&args) {
+        Ok(spot_prices) => spot_prices,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+    let iv_risk_free_rate = match iv_rate_from_args(&args, &spot_prices) {
+        Ok(rate) => rate,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(2);
+        }
+    };
+    let render_options = RenderOptions {
+        color,
+        compact: args.compact,
+        spot_prices,
+        iv_risk_free_rate,
+    };
+    let mut output = match &args.output_file {
+        Some(path) => match Output::append_to(path) {
+            Ok(output) => output,
+            Err(e) => {
+                eprintln!("Error: cannot open output file {}: {e}", path.display());
+                std::process::exit(2);
+            }
+        },
+        None => Output::stdout(),
+    };
+    // With opportunities going to a file, keep stdout free and log to stderr.
+    let log_writer = match output {
+        Output::Stdout { .. } => BoxMakeWriter::new(std::io::stdout),
+        Output::File { .. } => BoxMakeWriter::new(std::io::stderr),
+    };
+    let subscriber = tracing_subscriber::registry().with(filter).with(
+        tracing_subscriber::fmt::layer()
+            .with_ansi(color)
+            .with_writer(log_writer),
+    );
+    // Flushes the trace file when dropped at the end of `main`.
+    #[cfg(feature = "chrome")]
+    let _chrome_guard = match &args.chrome_trace {
+        Some(path) => {
+            let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new().file(path).build();
+            subscriber.with(layer).init();
+            Some(guard)
+        }
+        None => {
+            subscriber.init();
+            None
+        }
+    };
+    #[cfg(not(feature = "chrome"))]
+    subscriber.init();
+    info!(
+        version = env!("CARGO_PKG_VERSION"),
+        build_timestamp = env!("BUILD_TIMESTAMP"),
+        filter = %filter_directives,
+        "Starting options-arbitrage"
+    );
 
-    tokio::spawn({
-        let symbol = okex_symbol.clone();
-        let tx = tx.clone();
-        async move { okex_websocket_task(symbol, tx).await }
-    });
+    let min_profit_threshold = Decimal::try_from(args.min_profit)?;
+    let min_book_depth = Decimal::try_from(args.min_book_depth)?;
+    let max_position = match Decimal::try_from(args.max_position_size)? {
+        size if size > Decimal::ZERO => size,
+        _ => Decimal::MAX,
+    };
 
-    tokio::spawn({
-        let symbol = deribit_symbol.clone();
-        let tx = tx.clone();
-        async move { deribit_websocket_task(symbol, tx).await }
-    });
+    let requested_pairs = match &args.symbols_file {
+        Some(path) => parse_symbols_file(&std::fs::read_to_string(path)?)?,
+        None => match cli_pair {
+            Some(pair) => vec![pair],
+            None => args.config_symbols.clone(),
+        },
+    };
 
-    let mut books = HashMap::new();
-    let mut last_fingerprint = None;
+    let today = chrono::Utc::now().date_naive();
+    let mut pairs = Vec::new();
+    for pair in requested_pairs {
+        match pair.mismatch() {
+            Ok(None) => {
+                // All venues describe the same instrument, so one expiry check covers them.
+                let (exchange, symbol) = &pair.venues()[0];
+                match InstrumentValidator::parse_symbol(Some(exchange), symbol) {
+                    Ok(parsed) => {
+                        if let Some(min_dte) = args.min_dte
+                            && parsed.days_to_expiry(today) < min_dte
+                        {
+                            anyhow::bail!(
+                                "{pair} expires in {} days, less than --min-dte={min_dte}",
+                                parsed.days_to_expiry(today)
+                            );
+                        }
+                        if let Some(expiry_class) = args.expiry_class
+                            && parsed.classify_expiry_at(today) != expiry_class
+                        {
+                            info!(
+                                symbols = %pair,
+                                expiry_class = ?parsed.classify_expiry_at(today),
+                                wanted = ?expiry_class,
+                                "Instrument is outside the requested expiry class, skipping"
+                            );
+                            continue;
+                        }
+                    }
+                    Err(e) => warn!(
+                        symbols = %pair,
+                        error = %e,
+                        "Failed to parse expiry, expiry filters not applied"
+                    ),
+                }
+                info!(
+                    symbols = %pair,
+                    "LET'S GOOO: Trying to find arbitrage"
+                );
+                pairs.push(pair);
+            }
+            Ok(Some(mismatch)) => error!(
+                symbols = %pair,
+                reason = %mismatch,
+                "Instruments do not match, skipping"
+            ),
+            Err(e) => error!(
+                symbols = %pair,
+                error = %e,
+                "Failed to parse instruments, skipping"
+            ),
+        }
+    }
 
-    while let Some(update) = rx.recv().await {
-        match update {
-            OrderBookUpdate::Bids {
+    if pairs.is_empty() {
+        anyhow::bail!("No valid symbol pairs to monitor");
+    }
+
+    #[cfg(feature = "sqlite")]
+    let mut db_conn = match &args.db_path {
+        Some(path) => Some(db::open(path)?),
+        None => None,
+    };
+
+    // tokio panics on a zero-capacity channel.
+    let (tx, mut rx) = mpsc::channel::<OrderBookUpdate>(args.channel_capacity.max(1));
+
+    let metrics = Arc::new(Metrics::new());
+    let mut webhook = args
+        .alert_webhook
+        .clone()
+        .map(WebhookNotifier::new)
+        .transpose()?;
+
+    if let Some(port) = args.metrics_port {
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = metrics::serve(metrics, port).await {
+                error!(error = %e, port, "Metrics server stopped");
+            }
+        });
+    }
+
+    let mut recorder = match &args.record {
+        Some(path) => Some(RecordingWriter::create(path)?),
+        None => None,
+    };
+
+    let replaying = match &args.replay {
+        Some(path) => {
+            let reader = RecordingReader::open(path)?;
+            info!(path = %path.display(), speed = args.replay_speed, "Replaying recording");
+            let tx = tx.clone();
+            let speed = args.replay_speed;
+            tokio::spawn(async move {
+                if let Err(e) = recorder::replay(reader, tx, speed).await {
+                    error!(error = %e, "Replay stopped");
+                }
+            });
+            true
+        }
+        None => false,
+    };
+
+    let feed_config = FeedConfig {
+        okex_channel: args.okex_channel,
+        deribit_depth: args.deribit_depth,
+        max_reconnect_attempts: args.max_reconnect_attempts,
+        // Taken out of `args` so the credentials only live in the zeroized wrapper.
+        #[cfg(feature = "auth")]
+        okex_credentials: match (
+            args.okex_api_key.take(),
+            args.okex_api_secret.take(),
+            args.okex_passphrase.take(),
+        ) {
+            (Some(api_key), Some(secret_key), Some(passphrase)) => {
+                Some(Arc::new(options_arbitrage::auth::OkexCredentials {
+                    api_key,
+                    secret_key,
+                    passphrase,
+                }))
+            }
+            _ => None,
+        },
+    };
+    let watch_interval = Duration::from_millis(args.watch_interval.max(1));
+    // Replays never connect, so they get no adapters and the loop below only indexes the books.
+    let mut adapters: Vec<Box<dyn ExchangeAdapter>> = Vec::new();
+    for (exchange, _) in pairs.iter().flat_map(SymbolPair::venues) {
+        if replaying {
+            break;
+        }
+        if !adapters
+            .iter()
+            .any(|adapter| adapter.exchange() == exchange)
+        {
+            let rest_adapter = args
+                .rest_mode
+                .then(|| rest::rest_adapter_for(&exchange, watch_interval))
+                .flatten();
+            adapters.extend(rest_adapter.or_else(|| adapter_for(&exchange, &feed_config)));
+        }
+    }
+
+    let pair_by_book = pairs_by_book(&pairs);
+    // A book shared by several pairs is still streamed once.
+    for (exchange, symbol) in pair_by_book.keys() {
+        if let Some(adapter) = adapters
+            .iter()
+            .find(|adapter| adapter.exchange() == *exchange)
+        {
+            tokio::spawn(adapter.connect(symbol.clone(), tx.clone(), metrics.clone()));
+        }
+    }
+    let feed_exchanges: Vec<Exchange> = adapters.iter().map(|adapter| adapter.exchange()).collect();
+    // Only the feeds hold senders now, so a finished replay closes the channel and ends the run.
+    drop(tx);
+
+    let detector = ArbitrageDetector::builder()
+        .with_max_depth(args.max_depth)
+        .with_min_spread_bps(Decimal::try_from(args.min_spread_bps)?)
+        .with_fees(fees)
+        .build();
+    let mut registry = BookRegistry::new().with_max_depth(args.max_book_depth);
+    // Recordings may predate subscription confirmations, so replayed books count as subscribed
+    // from the start.
+    if replaying {
+        registry = registry.assume_subscribed(pair_by_book.keys().cloned());
+    }
+    let mut last_fingerprints = HashMap::new();
+    let stale_threshold = Duration::from_secs(args.stale_threshold_secs);
+    let mut stale_books = HashSet::new();
+    // A stalled feed sends nothing, so staleness is also checked on a timer.
+    let mut stale_check = tokio::time::interval(Duration::from_secs(1));
+    let stats_period = Duration::from_secs(args.stats_interval.max(1));
+    // Current above-threshold opportunities of each pair, for the scanner report.
+    let mut active_opportunities: Vec<Vec<ArbitrageOpportunity>> = vec![Vec::new(); pairs.len()];
+    let report_period = Duration::from_secs(args.report_interval.max(1));
+    let mut scanner_report =
+        tokio::time::interval_at(tokio::time::Instant::now() + report_period, report_period);
+    let mut stats_report =
+        tokio::time::interval_at(tokio::time::Instant::now() + stats_period, stats_period);
+
+    let mut stats = SessionStats::new(args.history_size);
+    let mut store = OpportunityStore::new(Duration::from_secs(args.store_max_age_secs));
+    tokio::pin!(shutdown);
+
+    loop {
+        let update = tokio::select! {
+            update = rx.recv() => match update {
+                Some(update) => update,
+                None => break,
+            },
+            signal = &mut shutdown => {
+                info!(signal, "Shutting down");
+                log_throughput(&metrics, &feed_exchanges);
+                break;
+            }
+            _ = stats_report.tick() => {
+                log_throughput(&metrics, &feed_exchanges);
+                continue;
+            }
+            _ = scanner_report.tick(), if pairs.len() > 1 => {
+                clear_suspended_pairs(&pairs, &registry, &stale_books, &mut active_opportunities);
+                let active_symbols = pairs
+                    .iter()
+                    .filter(|pair| pair.venues().iter().filter(|venue| registry.contains(venue)).count() >= 2)
+                    .count();
+                let mut report = ScannerReport::compute(active_symbols, active_opportunities.iter().map(Vec::as_slice));
+                report.recent_opportunities = store.count_last(report_period);
+                report.log();
+                continue;
+            }
+            _ = stale_check.tick() => {
+                report_stale_books(registry.iter(), &mut stale_books, stale_threshold);
+                clear_suspended_pairs(&pairs, &registry, &stale_books, &mut active_opportunities);
+                continue;
+            }
+        };
+
+        store.prune();
+
+        if let Some(writer) = recorder.as_mut()
+            && let Err(e) = writer.write(&update)
+        {
+            error!(error = %e, "Failed to record update, recording stopped");
+            recorder = None;
+        }
+
+        let updated_book = registry.apply_update(&update)?;
+        match &update {
+            OrderBookUpdate::Bids { received_at, .. }
+            | OrderBookUpdate::Asks { received_at, .. }
+            | OrderBookUpdate::Snapshot { received_at, .. } => {
+                let event = match &update {
+                    OrderBookUpdate::Bids { .. } => "bids updated",
+                    OrderBookUpdate::Asks { .. } => "asks updated",
+                    _ => "snapshot applied",
+                };
+                if let Some(book) = updated_book.as_ref().and_then(|key| registry.get(key)) {
+                    metrics.record_orderbook_update(&book.exchange, received_at.elapsed());
+                    debug!(
+                        exchange = %book.exchange,
+                        symbol = %book.symbol,
+                        mid_price = ?book.mid_price(),
+                        spread = ?book.spread(),
+                        spread_bps = ?book.spread_bps(),
+                        latency_us = received_at.elapsed().as_micros() as u64,
+                        "{event}"
+                    );
+                }
+            }
+            OrderBookUpdate::Clear { .. } => {}
+            OrderBookUpdate::SubscriptionConfirmed {
                 exchange,
                 symbol,
-                levels,
-            } => {
-                let book = books
-                    .entry(exchange.clone())
-                    .or_insert_with(|| OrderBook::new(symbol, exchange));
-                book.update_bids(levels);
-            }
-            OrderBookUpdate::Asks {
+                channel,
+            } => info!(%exchange, %symbol, %channel, "Subscription confirmed"),
+            OrderBookUpdate::ConnectionError {
                 exchange,
+                error,
                 symbol,
-                levels,
             } => {
-                let book = books
-                    .entry(exchange.clone())
-                    .or_insert_with(|| OrderBook::new(symbol, exchange));
-                book.update_asks(levels);
-            }
-            OrderBookUpdate::ConnectionError { exchange, error } => {
-                eprintln!("Connection error from {exchange}: {error}");
+                warn!(%exchange, %symbol, %error, "Connection error");
             }
         }
 
-        if let (Some(okex), Some(deribit)) =
-            (books.get(&Exchange::Okex), books.get(&Exchange::Deribit))
+        if let Some(book) = updated_book.as_ref().and_then(|key| registry.get(key))
+            && book.is_crossed()
         {
-            if let Some(opp) = ArbitrageDetector::detect_arbitrage(okex, deribit) {
-                // Only print arbitrage opportunities when new opportunity is spotted.
-                let fp = (opp.symbol.clone(), opp.total_profit);
-                if Some(fp.clone()) != last_fingerprint {
-                    opp.show_arb_stats();
-                    last_fingerprint = Some(fp);
+            warn!(
+                exchange = %book.exchange,
+                symbol = %book.symbol,
+                best_bid = ?book.best_bid().map(|bid| bid.price),
+                best_ask = ?book.best_ask().map(|ask| ask.price),
+                "Order book is crossed"
+            );
+        }
+
+        let Some(pair_indices) = updated_book.and_then(|key| pair_by_book.get(&key)) else {
+            continue;
+        };
+
+        for &pair_index in pair_indices {
+            let mut current_opportunities = Vec::new();
+            let venues = pairs[pair_index].venues();
+            for (i, venue_a) in venues.iter().enumerate() {
+                for venue_b in &venues[i + 1..] {
+                    // A crossed book would show the stale side as free money, and a stale book may
+                    // no longer be quoting those prices at all.
+                    if let Some((book_a, book_b)) = registry.ready_pair(venue_a, venue_b)
+                        && !book_a.is_crossed()
+                        && !book_b.is_crossed()
+                        && !book_a.is_stale(stale_threshold)
+                        && !book_b.is_stale(stale_threshold)
+                        && has_min_depth(book_a, min_book_depth)
+                        && has_min_depth(book_b, min_book_depth)
+                    {
+                        let opportunities =
+                            debug_span!("detect_arbitrage", symbol = %book_a.symbol).in_scope(
+                                || detector.detect_with_max_position(book_a, book_b, max_position),
+                            );
+                        for opp in opportunities {
+                            // Only print arbitrage opportunities when new opportunity is spotted.
+                            // Below-threshold opportunities still update the fingerprint so
+                            // crossing back above re-prints.
+                            let fp = opp.fingerprint();
+                            let fp_key =
+                                (venue_a.clone(), venue_b.clone(), opp.buy_exchange.clone());
+                            if opp.total_profit >= min_profit_threshold {
+                                current_opportunities.push(opp.clone());
+                            }
+                            if last_fingerprints.get(&fp_key) != Some(&fp) {
+                                if opp.total_profit >= min_profit_threshold {
+                                    if let Err(e) = write_opportunity(
+                                        &mut output,
+                                        &opp,
+                                        args.output_format,
+                                        &render_options,
+                                    ) {
+                                        error!(error = %e, "Failed to write opportunity");
+                                    }
+                                    stats.record(&opp);
+                                    store.insert(opp.clone());
+                                    if let Some(webhook) = webhook.as_mut() {
+                                        webhook.notify(&opp);
+                                    }
+                                    metrics.arbitrage_opportunities_total.inc();
+                                    metrics
+                                        .arbitrage_profit_total
+                                        .inc_by(opp.total_profit.to_f64().unwrap_or_default());
+
+                                    #[cfg(feature = "sqlite")]
+                                    if let Some(conn) = db_conn.as_mut()
+                                        && let Err(e) =
+                                            db::insert_opportunity(conn, &opp, chrono::Utc::now())
+                                    {
+                                        error!(error = %e, "Failed to persist opportunity");
+                                    }
+                                }
+                                last_fingerprints.insert(fp_key, fp);
+                            }
+                        }
+                    }
                 }
             }
+            active_opportunities[pair_index] = current_opportunities;
         }
+        report_stale_books(registry.iter(), &mut stale_books, stale_threshold);
+        clear_suspended_pairs(&pairs, &registry, &stale_books, &mut active_opportunities);
     }
 
+    finish_session(recorder.as_mut(), &mut output, &stats);
     Ok(())
 }
+
+/// Starts listening for the signals that ask the process to stop: CTRL-C anywhere, plus
+/// SIGTERM on Unix and CTRL-BREAK on Windows. Listening starts before this returns, so a signal
+/// sent during startup is held for the returned future, which resolves with the signal's name.
+/// Failing to listen is logged once the future is polled.
+fn shutdown_signal() -> impl Future<Output = &'static str> {
+    #[cfg(unix)]
+    let (ctrl_c, platform) = {
+        use tokio::signal::unix::{SignalKind, signal};
+        (
+            signal_received(
+                signal(SignalKind::interrupt()),
+                "CTRL-C",
+                |mut s| async move {
+                    s.recv().await;
+                },
+            ),
+            signal_received(
+                signal(SignalKind::terminate()),
+                "SIGTERM",
+                |mut s| async move {
+                    s.recv().await;
+                },
+            ),
+        )
+    };
+    #[cfg(windows)]
+    let (ctrl_c, platform) = {
+        use tokio::signal::windows::{ctrl_break, ctrl_c};
+        (
+            signal_received(ctrl_c(), "CTRL-C", |mut s| async move {
+                s.recv().await;
+            }),
+            signal_received(ctrl_break(), "CTRL-BREAK", |mut s| async move {
+                s.recv().await;
+            }),
+        )
+    };
+    #[cfg(not(any(unix, windows)))]
+    let (ctrl_c, platform) = (
+        std::future::pending::<&'static str>(),
+        std::future::pending::<&'static str>(),
+    );
+
+    async move {
+        tokio::select! {
+            signal = ctrl_c => signal,
+            signal = platform => signal,
+        }
+    }
+}
+
+/// Resolves with `name` once `recv` sees the signal `listener` was registered for, or never if
+/// registering failed.
+#[cfg(any(unix, windows))]
+async fn signal_received<L, F>(
+    listener: std::io::Result<L>,
+    name: &'static str,
+    recv: impl FnOnce(L) -> F,
+) -> &'static str
+where
+    F: Future<Output = ()>,
+{
+    match listener {
+        Ok(listener) => {
+            recv(listener).await;
+            name
+        }
+        Err(e) => {
+            warn!(error = %e, signal = name, "Cannot listen for signal");
+            std::future::pending().await
+        }
+    }
+}
+
+/// Flushes everything written during the session and logs its summary.
+fn finish_session(
+    recorder: Option<&mut RecordingWriter>,
+    output: &mut Output,
+    stats: &SessionStats,
+) {
+    if let Some(writer) = recorder
+        && let Err(e) = writer.flush()
+    {
+        error!(error = %e, "Failed to flush recording");
+    }
+    if let Err(e) = output.flush() {
+        error!(error = %e, "Failed to flush output");
+    }
+    stats.print_summary();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(argv: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command().try_get_matches_from(argv).unwrap();
+        (Args::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    #[test]
+    fn test_cli_flags_override_config() {
+        let config = AppConfig::parse(
+            r#"
+            okex_fee_bps = 5.0
+            min_profit = 0.5
+            output_format = "json"
+            "#,
+        )
+        .unwrap();
+        let (mut args, matches) = parse_args(&[
+            "options-arbitrage",
+            "--config",
+            "arb.toml",
+            "--min-profit",
+            "0.2",
+        ]);
+
+        args.apply_config(&config, &matches).unwrap();
+
+        // Explicit flag wins over the file
+        assert_eq!(args.min_profit, 0.2);
+        // Unset flags take the file value, even where the file just repeats the default
+        assert_eq!(args.fee_rate_okex, 0.0005);
+        assert_eq!(args.fee_rate_deribit, 0.0003);
+        assert_eq!(args.output_format, OutputFormat::Json);
+        assert_eq!(args.min_dte, Some(0));
+    }
+
+    #[test]
+    fn test_spot_prices_from_args() {
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--config",
+            "arb.toml",
+            "--btc-spot-price",
+            "65000.5",
+        ]);
+        let spot_prices = spot_prices_from_args(&args).unwrap();
+        assert_eq!(spot_prices.btc, Some(Decimal::new(650005, 1)));
+        assert_eq!(spot_prices.eth, None);
+
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--config",
+            "arb.toml",
+            "--eth-spot-price=-1",
+        ]);
+        assert!(spot_prices_from_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_iv_mode_needs_rate_and_spot() {
+        assert!(
+            Args::try_parse_from(["options-arbitrage", "--iv-mode", "--btc-spot-price=65000"])
+                .is_err()
+        );
+
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--config",
+            "arb.toml",
+            "--iv-mode",
+            "--risk-free-rate=0.05",
+        ]);
+        assert!(iv_rate_from_args(&args, &SpotPrices::default()).is_err());
+        let spot_prices = SpotPrices {
+            btc: Some(Decimal::from(65000)),
+            eth: None,
+        };
+        assert_eq!(
+            iv_rate_from_args(&args, &spot_prices),
+            Ok(Some(Decimal::new(5, 2)))
+        );
+    }
+
+    #[test]
+    fn test_pair_from_exchange_args() {
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--exchange-a=bybit",
+            "--symbol-a=BTC-31OCT25-140000-P",
+            "--exchange-b=OKX",
+            "--symbol-b=BTC-USD-251031-140000-P",
+        ]);
+        let pair = pair_from_args(&args).unwrap().unwrap();
+        assert_eq!(
+            pair.venues(),
+            vec![
+                (Exchange::Okex, "BTC-USD-251031-140000-P".to_string()),
+                (Exchange::Bybit, "BTC-31OCT25-140000-P".to_string()),
+            ]
+        );
+        assert!(pair.is_same_instrument().unwrap());
+
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--symbol-a=BTC-USD-251031-140000-P",
+            "--exchange-b=okex",
+            "--symbol-b=BTC-USD-251031-140000-P",
+        ]);
+        assert!(pair_from_args(&args).is_err());
+
+        assert!(Args::try_parse_from(["options-arbitrage", "--exchange-a=kraken"]).is_err());
+    }
+
+    #[test]
+    fn test_log_filter_flag() {
+        let (args, _) = parse_args(&[
+            "options-arbitrage",
+            "--config",
+            "arb.toml",
+            "--log-filter=options_arbitrage=debug,tokio_tungstenite=warn",
+        ]);
+        assert_eq!(
+            args.log_filter.as_deref(),
+            Some("options_arbitrage=debug,tokio_tungstenite=warn")
+        );
+
+        for argv in [
+            ["--log-filter=debug", "--log-level=warn"],
+            ["--log-filter=options_arbitrage=loud", "--config=arb.toml"],
+        ] {
+            assert!(Args::try_parse_from(["options-arbitrage"].into_iter().chain(argv)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_invalid_config_output_format() {
+        let config = AppConfig::parse(r#"output_format = "xml""#).unwrap();
+        let (mut args, matches) = parse_args(&["options-arbitrage", "--config", "arb.toml"]);
+        assert!(args.apply_config(&config, &matches).is_err());
+    }
+
+    #[test]
+    fn test_report_stale_books_tracks_transitions() {
+        let key = (Exchange::Okex, "BTC-USD-251031-140000-P".to_string());
+        let mut book = OrderBook::new(key.1.clone(), key.0.clone());
+        book.last_updated = Some(Instant::now() - Duration::from_secs(60));
+        let mut books = HashMap::from([(key.clone(), book)]);
+        let mut stale = HashSet::new();
+        let threshold = Duration::from_secs(30);
+
+        report_stale_books(&books, &mut stale, threshold);
+        assert!(stale.contains(&key));
+
+        books.get_mut(&key).unwrap().last_updated = Some(Instant::now());
+        report_stale_books(&books, &mut stale, threshold);
+        assert!(stale.is_empty());
+    }
+
+    #[test]
+    fn test_pairs_by_book_keeps_every_pair_sharing_a_book() {
+        let deribit = "BTC-31OCT25-140000-P".to_string();
+        let pairs = [
+            SymbolPair {
+                okex: Some("BTC-USD-251031-140000-P".to_string()),
+                deribit: Some(deribit.clone()),
+                ..SymbolPair::default()
+            },
+            SymbolPair {
+                deribit: Some(deribit.clone()),
+                bybit: Some("BTC-31OCT25-140000-P-USDT".to_string()),
+                ..SymbolPair::default()
+            },
+        ];
+
+        let pair_by_book = pairs_by_book(&pairs);
+        assert_eq!(pair_by_book.len(), 3);
+        assert_eq!(pair_by_book[&(Exchange::Deribit, deribit)], [0, 1]);
+        assert_eq!(
+            pair_by_book[&(Exchange::Okex, "BTC-USD-251031-140000-P".to_string())],
+            [0]
+        );
+    }
+
+    #[test]
+    fn test_clear_suspended_pairs_drops_stale_and_unsubscribed_pairs() {
+        let pair = |okex: &str, deribit: &str| SymbolPair {
+            okex: Some(okex.to_string()),
+            deribit: Some(deribit.to_string()),
+            ..SymbolPair::default()
+        };
+        let pairs = [
+            pair("BTC-USD-251031-140000-P", "BTC-31OCT25-140000-P"),
+            pair("BTC-USD-251031-150000-P", "BTC-31OCT25-150000-P"),
+            pair("BTC-USD-251031-160000-P", "BTC-31OCT25-160000-P"),
+        ];
+        let registry =
+            BookRegistry::new().assume_subscribed(pairs[..2].iter().flat_map(SymbolPair::venues));
+        let stale = HashSet::from([(Exchange::Deribit, "BTC-31OCT25-140000-P".to_string())]);
+        let mut active = vec![vec![ArbitrageOpportunity::default()]; pairs.len()];
+
+        clear_suspended_pairs(&pairs, &registry, &stale, &mut active);
+        assert!(active[0].is_empty());
+        assert_eq!(active[1].len(), 1);
+        assert!(active[2].is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::orderbook::Exchange;
+use anyhow::Result;
+use axum::{Router, routing::get};
+use prometheus::{
+    Counter, Encoder, GaugeVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::net::TcpListener;
+use tracing::info;
+
+/// Process-wide counters and gauges, exposed in Prometheus text format by `serve`.
+pub struct Metrics {
+    registry: Registry,
+    pub arbitrage_opportunities_total: IntCounter,
+    pub arbitrage_profit_total: Counter,
+    pub orderbook_updates_total: IntCounterVec,
+    pub websocket_reconnects_total: IntCounterVec,
+    pub last_orderbook_latency_seconds: GaugeVec,
+    /// Raw websocket traffic per exchange, reset each time it is reported.
+    pub throughput: StatsRegistry,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let arbitrage_opportunities_total = IntCounter::new(
+            "arbitrage_opportunities_total",
+            "Arbitrage opportunities reported",
+        )
+        .unwrap();
+        let arbitrage_profit_total = Counter::new(
+            "arbitrage_profit_total",
+            "Cumulative profit of reported opportunities, in units of the underlying",
+        )
+        .unwrap();
+        let orderbook_updates_total = IntCounterVec::new(
+            Opts::new("orderbook_updates_total", "Order book updates applied"),
+            &["exchange"],
+        )
+        .unwrap();
+        let websocket_reconnects_total = IntCounterVec::new(
+            Opts::new("websocket_reconnects_total", "Websocket reconnect attempts"),
+            &["exchange"],
+        )
+        .unwrap();
+        let last_orderbook_latency_seconds = GaugeVec::new(
+            Opts::new(
+                "last_orderbook_latency_seconds",
+                "Time from websocket read to book update for the latest message",
+            ),
+            &["exchange"],
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(arbitrage_opportunities_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(arbitrage_profit_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(orderbook_updates_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(websocket_reconnects_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(last_orderbook_latency_seconds.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            arbitrage_opportunities_total,
+            arbitrage_profit_total,
+            orderbook_updates_total,
+            websocket_reconnects_total,
+            last_orderbook_latency_seconds,
+            throughput: StatsRegistry::new(),
+        }
+    }
+
+    pub fn record_orderbook_update(&self, exchange: &Exchange, latency: Duration) {
+        let exchange = exchange.to_string();
+        self.orderbook_updates_total
+            .with_label_values(&[exchange.as_str()])
+            .inc();
+        self.last_orderbook_latency_seconds
+            .with_label_values(&[exchange.as_str()])
+            .set(latency.as_secs_f64());
+    }
+
+    pub fn record_reconnect(&self, exchange: &Exchange) {
+        self.websocket_reconnects_total
+            .with_label_values(&[exchange.to_string().as_str()])
+            .inc();
+    }
+
+    /// Counts one websocket text message of `bytes` bytes.
+    pub fn record_message(&self, exchange: &Exchange, bytes: usize) {
+        if let Some(stats) = self.throughput.get(exchange) {
+            stats.record(bytes);
+        }
+    }
+
+    /// Counts one update from `exchange` dropped on a full channel, returning its session total,
+    /// or 0 for a venue without stats.
+    pub fn record_dropped_update(&self, exchange: &Exchange) -> u64 {
+        self.throughput
+            .get(exchange)
+            .map_or(0, ExchangeStats::record_dropped)
+    }
+
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into a Vec only fails on malformed metric families, which `new` rules out.
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .unwrap();
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Message and byte counts for one exchange's websocket since `last_reset`.
+pub struct ExchangeStats {
+    pub exchange: Exchange,
+    pub messages_received: AtomicU64,
+    pub bytes_received: AtomicU64,
+    pub last_reset: Mutex<Instant>,
+    /// Updates dropped because the main loop's channel was full, over the whole session.
+    pub dropped_updates: AtomicU64,
+}
+
+/// Average rates over one reporting period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    pub messages_per_sec: f64,
+    pub kb_per_sec: f64,
+}
+
+impl ExchangeStats {
+    fn new(exchange: Exchange) -> Self {
+        Self {
+            exchange,
+            messages_received: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            last_reset: Mutex::new(Instant::now()),
+            dropped_updates: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, bytes: usize) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Counts one dropped update, returning the session total.
+    pub fn record_dropped(&self) -> u64 {
+        self.dropped_updates.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Rates since the previous call (or creation), then starts a new period.
+    pub fn take_throughput(&self) -> Throughput {
+        let mut last_reset = self.last_reset.lock().unwrap();
+        let elapsed = last_reset.elapsed().as_secs_f64().max(f64::EPSILON);
+        *last_reset = Instant::now();
+        let messages = self.messages_received.swap(0, Ordering::Relaxed);
+        let bytes = self.bytes_received.swap(0, Ordering::Relaxed);
+        Throughput {
+            messages_per_sec: messages as f64 / elapsed,
+            kb_per_sec: bytes as f64 / 1024.0 / elapsed,
+        }
+    }
+}
+
+/// One `ExchangeStats` per exchange.
+pub struct StatsRegistry {
+    stats: Vec<ExchangeStats>,
+}
+
+impl StatsRegistry {
+    pub fn new() -> Self {
+        Self {
+            stats: Exchange::all()
+                .iter()
+                .cloned()
+                .map(ExchangeStats::new)
+                .collect(),
+        }
+    }
+
+    /// `None` for `Exchange::Aggregated`, which has no feed to measure.
+    pub fn get(&self, exchange: &Exchange) -> Option<&ExchangeStats> {
+        self.stats.iter().find(|stats| stats.exchange == *exchange)
+    }
+}
+
+impl Default for StatsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serves `GET /metrics` on `0.0.0.0:<port>` until the process exits.
+pub async fn serve(metrics: Arc<Metrics>, port: u16) -> Result<()> {
+    let app = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { metrics.render() }
+        }),
+    );
+
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    info!(port, "serving metrics");
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_labelled_metrics() {
+        let metrics = Metrics::new();
+        metrics.arbitrage_opportunities_total.inc();
+        metrics.arbitrage_profit_total.inc_by(0.25);
+        metrics.record_orderbook_update(&Exchange::Okex, Duration::from_millis(3));
+        metrics.record_reconnect(&Exchange::Deribit);
+
+        let output = metrics.render();
+        assert!(output.contains("arbitrage_opportunities_total 1"));
+        assert!(output.contains("arbitrage_profit_total 0.25"));
+        assert!(output.contains("orderbook_updates_total{exchange=\"Okex\"} 1"));
+        assert!(output.contains("websocket_reconnects_total{exchange=\"Deribit\"} 1"));
+        assert!(output.contains("last_orderbook_latency_seconds{exchange=\"Okex\"} 0.003"));
+    }
+
+    #[test]
+    fn test_throughput_resets_each_period() {
+        let metrics = Metrics::new();
+        metrics.record_message(&Exchange::Okex, 2048);
+        metrics.record_message(&Exchange::Okex, 1024);
+
+        let okex = metrics.throughput.get(&Exchange::Okex).unwrap();
+        *okex.last_reset.lock().unwrap() = Instant::now() - Duration::from_secs(2);
+        let throughput = okex.take_throughput();
+        assert!((throughput.messages_per_sec - 1.0).abs() < 0.01);
+        assert!((throughput.kb_per_sec - 1.5).abs() < 0.01);
+
+        assert_eq!(okex.messages_received.load(Ordering::Relaxed), 0);
+        assert_eq!(
+            metrics
+                .throughput
+                .get(&Exchange::Deribit)
+                .unwrap()
+                .take_throughput()
+                .messages_per_sec,
+            0.0
+        );
+
+        // Aggregated books have no feed, so there is nothing to count.
+        metrics.record_message(&Exchange::Aggregated, 512);
+        assert!(metrics.throughput.get(&Exchange::Aggregated).is_none());
+        assert_eq!(metrics.record_dropped_update(&Exchange::Aggregated), 0);
+    }
+}
@@ -0,0 +1,1183 @@
+use super::{Exchange, OrderBookUpdate, OrderLevel, Side};
+use crate::parsing_utils::{InstrumentValidator, ParsedInstrument};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// Result of sweeping one side of a book for a given quantity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceImpactResult {
+    /// Volume-weighted price across all filled levels.
+    pub avg_fill_price: Decimal,
+    /// Price of the deepest level touched.
+    pub worst_fill_price: Decimal,
+    pub total_fillable_qty: Decimal,
+    /// Quantity left over once the book is depleted.
+    pub unfilled_qty: Decimal,
+}
+
+/// Why `OrderBook::apply_update` refused an update.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BookUpdateError {
+    #[error("update for {update} applied to a {book} book")]
+    ExchangeMismatch { book: Exchange, update: Exchange },
+    #[error("update for {update} applied to a {book} book")]
+    SymbolMismatch { book: String, update: String },
+    /// Subscription and connection events carry no book data.
+    #[error("update carries no book data")]
+    UnexpectedVariant,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderBook {
+    pub bids: BTreeMap<Decimal, Decimal>,
+    pub asks: BTreeMap<Decimal, Decimal>,
+    pub symbol: String,
+    pub exchange: Exchange,
+    pub last_updated: Option<Instant>,
+    /// Checksum the venue reported for the latest update, where it sends one (Okex).
+    pub checksum: Option<i32>,
+    /// Venue sequence number of the latest update, where it sends one (Okex).
+    pub sequence: Option<u64>,
+    /// `symbol` parsed for the venue, or `None` when it is not a recognised option symbol.
+    pub instrument: Option<ParsedInstrument>,
+    /// Levels kept per side; the worst levels beyond it are dropped on every update.
+    pub max_depth: Option<usize>,
+}
+
+/// Snapshot levels as a book side; zero quantities mean "no level" and are skipped.
+fn levels_to_map(levels: Vec<OrderLevel>) -> BTreeMap<Decimal, Decimal> {
+    levels
+        .into_iter()
+        .filter(|level| !level.quantity.is_zero())
+        .map(|level| (level.price, level.quantity))
+        .collect()
+}
+
+/// Placeholder: an empty Okex book with no symbol. Set `symbol` and `exchange` before use.
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new(String::new(), Exchange::default())
+    }
+}
+
+impl OrderBook {
+    pub fn new(symbol: String, exchange: Exchange) -> Self {
+        let instrument = InstrumentValidator::parse_symbol(Some(&exchange), &symbol).ok();
+        Self {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            symbol,
+            exchange,
+            last_updated: None,
+            checksum: None,
+            sequence: None,
+            instrument,
+            max_depth: None,
+        }
+    }
+
+    /// Keep at most `depth` levels per side from now on.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self.trim_to_max_depth();
+        self
+    }
+
+    /// Drops the lowest bids and highest asks beyond `max_depth`.
+    fn trim_to_max_depth(&mut self) {
+        let Some(depth) = self.max_depth else {
+            return;
+        };
+        while self.bids.len() > depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
+    }
+
+    /// A book holding `bids` and `asks`, applied as updates (zero quantities are skipped).
+    pub fn from_levels(
+        symbol: String,
+        exchange: Exchange,
+        bids: Vec<OrderLevel>,
+        asks: Vec<OrderLevel>,
+    ) -> Self {
+        let mut book = Self::new(symbol, exchange);
+        book.update_bids(bids);
+        book.update_asks(asks);
+        book
+    }
+
+    /// Like [`OrderBook::from_levels`], from `(price, quantity)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// If a price or quantity is NaN or infinite.
+    pub fn from_raw(
+        symbol: String,
+        exchange: Exchange,
+        bids: Vec<(f64, f64)>,
+        asks: Vec<(f64, f64)>,
+    ) -> Self {
+        let to_levels = |levels: Vec<(f64, f64)>| {
+            levels
+                .into_iter()
+                .map(|(price, quantity)| OrderLevel {
+                    price: Decimal::try_from(price).expect("finite price"),
+                    quantity: Decimal::try_from(quantity).expect("finite quantity"),
+                })
+                .collect()
+        };
+        Self::from_levels(symbol, exchange, to_levels(bids), to_levels(asks))
+    }
+
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(exchange = %self.exchange, symbol = %self.symbol, levels = levels.len())
+    )]
+    pub fn update_bids(&mut self, levels: Vec<OrderLevel>) {
+        self.last_updated = Some(Instant::now());
+        for level in levels {
+            if level.quantity.is_zero() {
+                self.bids.remove(&level.price);
+            } else {
+                self.bids.insert(level.price, level.quantity);
+            }
+        }
+        self.trim_to_max_depth();
+    }
+
+    #[tracing::instrument(
+        level = "trace",
+        skip_all,
+        fields(exchange = %self.exchange, symbol = %self.symbol, levels = levels.len())
+    )]
+    pub fn update_asks(&mut self, levels: Vec<OrderLevel>) {
+        self.last_updated = Some(Instant::now());
+        for level in levels {
+            if level.quantity.is_zero() {
+                self.asks.remove(&level.price);
+            } else {
+                self.asks.insert(level.price, level.quantity);
+            }
+        }
+        self.trim_to_max_depth();
+    }
+
+    /// Applies a book-changing update (bids, asks, a snapshot or a clear) from this book's
+    /// exchange and symbol.
+    pub fn apply_update(&mut self, update: &OrderBookUpdate) -> Result<(), BookUpdateError> {
+        let (exchange, symbol) = match update {
+            OrderBookUpdate::Bids {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Asks {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Snapshot {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Clear { exchange, symbol } => (exchange, symbol),
+            OrderBookUpdate::SubscriptionConfirmed { .. }
+            | OrderBookUpdate::ConnectionError { .. } => {
+                return Err(BookUpdateError::UnexpectedVariant);
+            }
+        };
+        if *exchange != self.exchange {
+            return Err(BookUpdateError::ExchangeMismatch {
+                book: self.exchange.clone(),
+                update: exchange.clone(),
+            });
+        }
+        if *symbol != self.symbol {
+            return Err(BookUpdateError::SymbolMismatch {
+                book: self.symbol.clone(),
+                update: symbol.clone(),
+            });
+        }
+
+        match update {
+            OrderBookUpdate::Bids { levels, .. } => self.update_bids(levels.clone()),
+            OrderBookUpdate::Asks { levels, .. } => self.update_asks(levels.clone()),
+            OrderBookUpdate::Snapshot { bids, asks, .. } => {
+                self.update_from_snapshot(bids.clone(), asks.clone())
+            }
+            OrderBookUpdate::Clear { .. } => self.clear(),
+            OrderBookUpdate::SubscriptionConfirmed { .. }
+            | OrderBookUpdate::ConnectionError { .. } => unreachable!("rejected above"),
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    /// Drops every bid priced strictly below `price_threshold`.
+    pub fn remove_bids_below(&mut self, price_threshold: Decimal) {
+        self.bids = self.bids.split_off(&price_threshold);
+    }
+
+    /// Drops every ask priced strictly above `price_threshold`.
+    pub fn remove_asks_above(&mut self, price_threshold: Decimal) {
+        let mut removed = self.asks.split_off(&price_threshold);
+        if let Some((price, quantity)) = removed.remove_entry(&price_threshold) {
+            self.asks.insert(price, quantity);
+        }
+    }
+
+    /// Replaces the whole book with a full snapshot, dropping every level not in it. Both
+    /// sides are built before either is swapped in, so the book is never seen half-updated.
+    pub fn update_from_snapshot(&mut self, bids: Vec<OrderLevel>, asks: Vec<OrderLevel>) {
+        self.last_updated = Some(Instant::now());
+        self.bids = levels_to_map(bids);
+        self.asks = levels_to_map(asks);
+        self.trim_to_max_depth();
+    }
+
+    /// Same as `update_from_snapshot`.
+    pub fn replace_all(&mut self, bids: Vec<OrderLevel>, asks: Vec<OrderLevel>) {
+        self.update_from_snapshot(bids, asks);
+    }
+
+    /// Replaces every bid, leaving the asks untouched.
+    pub fn replace_bids(&mut self, levels: Vec<OrderLevel>) {
+        self.last_updated = Some(Instant::now());
+        self.bids = levels_to_map(levels);
+        self.trim_to_max_depth();
+    }
+
+    /// Replaces every ask, leaving the bids untouched.
+    pub fn replace_asks(&mut self, levels: Vec<OrderLevel>) {
+        self.last_updated = Some(Instant::now());
+        self.asks = levels_to_map(levels);
+        self.trim_to_max_depth();
+    }
+
+    /// Adds every level of `other` to this book, summing the quantities at prices both quote,
+    /// where `update_bids`/`update_asks` would replace them. Quantities are summed as quoted, so
+    /// only merge books whose contracts are the same size.
+    pub fn merge_into(&mut self, other: &OrderBook) {
+        for (&price, &quantity) in &other.bids {
+            *self.bids.entry(price).or_default() += quantity;
+        }
+        for (&price, &quantity) in &other.asks {
+            *self.asks.entry(price).or_default() += quantity;
+        }
+        self.last_updated = self.last_updated.max(other.last_updated);
+        self.trim_to_max_depth();
+    }
+
+    /// A synthetic `Exchange::Aggregated` book holding the levels of all `books` summed.
+    pub fn merged(books: &[&OrderBook], symbol: String) -> OrderBook {
+        let mut merged = OrderBook::new(symbol, Exchange::Aggregated);
+        for book in books {
+            merged.merge_into(book);
+        }
+        merged
+    }
+
+    /// Copy of the book holding only the best `n` levels per side, for detector hot paths
+    /// where the deep levels are never reached.
+    pub fn clone_top_n(&self, n: usize) -> OrderBook {
+        OrderBook {
+            bids: self
+                .bids
+                .iter()
+                .rev()
+                .take(n)
+                .map(|(&p, &q)| (p, q))
+                .collect(),
+            asks: self.asks.iter().take(n).map(|(&p, &q)| (p, q)).collect(),
+            symbol: self.symbol.clone(),
+            exchange: self.exchange.clone(),
+            last_updated: self.last_updated,
+            checksum: self.checksum,
+            sequence: self.sequence,
+            instrument: self.instrument.clone(),
+            max_depth: self.max_depth,
+        }
+    }
+
+    /// Underlying units per contract of this book, one when the symbol was not recognised.
+    pub fn contract_multiplier(&self) -> Decimal {
+        self.instrument.as_ref().map_or(Decimal::ONE, |instrument| {
+            instrument.contract_multiplier(&self.exchange)
+        })
+    }
+
+    /// Expiry of the book's instrument, `None` when the symbol was not recognised.
+    pub fn expiry(&self) -> Option<NaiveDate> {
+        self.instrument
+            .as_ref()
+            .map(|instrument| instrument.expiry_date)
+    }
+
+    /// Strike of the book's instrument, `None` when the symbol was not recognised.
+    pub fn strike(&self) -> Option<Decimal> {
+        self.instrument.as_ref().map(|instrument| instrument.strike)
+    }
+
+    pub fn best_bid(&self) -> Option<OrderLevel> {
+        self.bids.iter().next_back().map(|(&p, &q)| OrderLevel {
+            price: p,
+            quantity: q,
+        })
+    }
+
+    pub fn best_ask(&self) -> Option<OrderLevel> {
+        self.asks.iter().next().map(|(&p, &q)| OrderLevel {
+            price: p,
+            quantity: q,
+        })
+    }
+
+    /// Okex's book checksum: CRC32 of `bid1_px:bid1_sz:ask1_px:ask1_sz:...` over the best 25
+    /// levels per side, read as a signed 32-bit integer. Once one side runs out only the other
+    /// side's levels are appended. Prices and sizes are written as received, so this relies on
+    /// `Decimal` keeping the scale of the original strings.
+    pub fn compute_checksum(&self) -> i32 {
+        const CHECKSUM_DEPTH: usize = 25;
+        let mut bids = self.bids.iter().rev().take(CHECKSUM_DEPTH);
+        let mut asks = self.asks.iter().take(CHECKSUM_DEPTH);
+        let mut fields = Vec::with_capacity(CHECKSUM_DEPTH * 4);
+        loop {
+            let (bid, ask) = (bids.next(), asks.next());
+            if bid.is_none() && ask.is_none() {
+                break;
+            }
+            for (price, quantity) in bid.into_iter().chain(ask) {
+                fields.push(price.to_string());
+                fields.push(quantity.to_string());
+            }
+        }
+        crc32fast::hash(fields.join(":").as_bytes()) as i32
+    }
+
+    /// Up to `n` bids, highest price first.
+    pub fn n_best_bids(&self, n: usize) -> Vec<OrderLevel> {
+        self.bids
+            .iter()
+            .rev()
+            .take(n)
+            .map(|(&price, &quantity)| OrderLevel { price, quantity })
+            .collect()
+    }
+
+    /// Up to `n` asks, lowest price first.
+    pub fn n_best_asks(&self, n: usize) -> Vec<OrderLevel> {
+        self.asks
+            .iter()
+            .take(n)
+            .map(|(&price, &quantity)| OrderLevel { price, quantity })
+            .collect()
+    }
+
+    #[inline]
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some((bid.price + ask.price) / Decimal::TWO)
+    }
+
+    /// Quantity-weighted average price of the best `depth` bids.
+    pub fn weighted_average_bid(&self, depth: usize) -> Option<Decimal> {
+        Self::weighted_average(self.bids.iter().rev().take(depth))
+    }
+
+    /// Quantity-weighted average price of the best `depth` asks.
+    pub fn weighted_average_ask(&self, depth: usize) -> Option<Decimal> {
+        Self::weighted_average(self.asks.iter().take(depth))
+    }
+
+    /// Volume-weighted average price of the best `depth` levels a `side` order would trade
+    /// against: asks for a buy, bids for a sell. `None` when that side is empty.
+    pub fn vwap(&self, depth: usize, side: Side) -> Option<Decimal> {
+        match side {
+            Side::Buy => self.weighted_average_ask(depth),
+            Side::Sell => self.weighted_average_bid(depth),
+        }
+    }
+
+    /// Quantity a `side` order could trade at `up_to_price` or better: asks priced at or
+    /// below it for a buy, bids priced at or above it for a sell.
+    pub fn cumulative_volume(&self, up_to_price: Decimal, side: Side) -> Decimal {
+        match side {
+            Side::Buy => self.asks.range(..=up_to_price).map(|(_, qty)| qty).sum(),
+            Side::Sell => self.bids.range(up_to_price..).map(|(_, qty)| qty).sum(),
+        }
+    }
+
+    fn weighted_average<'a>(
+        levels: impl Iterator<Item = (&'a Decimal, &'a Decimal)>,
+    ) -> Option<Decimal> {
+        let (notional, quantity) = levels.fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(notional, quantity), (price, qty)| (notional + price * qty, quantity + qty),
+        );
+        if quantity.is_zero() {
+            return None;
+        }
+        Some(notional / quantity)
+    }
+
+    pub fn total_bid_volume(&self) -> Decimal {
+        self.bids.values().sum()
+    }
+
+    pub fn total_ask_volume(&self) -> Decimal {
+        self.asks.values().sum()
+    }
+
+    pub fn bid_levels_count(&self) -> usize {
+        self.bids.len()
+    }
+
+    pub fn ask_levels_count(&self) -> usize {
+        self.asks.len()
+    }
+
+    /// Best bid at or above best ask, usually a transient artefact of incremental updates
+    /// arriving out of step. An empty side is never crossed.
+    pub fn is_crossed(&self) -> bool {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => bid.price >= ask.price,
+            _ => false,
+        }
+    }
+
+    /// No update for longer than `threshold`. A book that has never been updated is not stale.
+    pub fn is_stale(&self, threshold: Duration) -> bool {
+        self.last_updated
+            .is_some_and(|updated| updated.elapsed() > threshold)
+    }
+
+    /// Negative when the book is crossed.
+    #[inline]
+    pub fn spread(&self) -> Option<Decimal> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        Some(ask.price - bid.price)
+    }
+
+    #[inline]
+    pub fn spread_bps(&self) -> Option<Decimal> {
+        let mid_price = self.mid_price()?;
+        if mid_price.is_zero() {
+            return None;
+        }
+        Some(self.spread()? / mid_price * Decimal::from(10_000))
+    }
+
+    /// Estimates the fill for a market order of `qty`: buys sweep the asks upward, sells sweep
+    /// the bids downward. Returns `None` for a non-positive `qty` or an empty side.
+    pub fn price_impact(&self, qty: Decimal, side: Side) -> Option<PriceImpactResult> {
+        if qty <= Decimal::ZERO {
+            return None;
+        }
+
+        let levels: Box<dyn Iterator<Item = (&Decimal, &Decimal)>> = match side {
+            Side::Buy => Box::new(self.asks.iter()),
+            Side::Sell => Box::new(self.bids.iter().rev()),
+        };
+
+        let mut remaining = qty;
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        let mut worst_fill_price = None;
+
+        for (&price, &level_qty) in levels {
+            if remaining.is_zero() {
+                break;
+            }
+            let fill = remaining.min(level_qty);
+            filled += fill;
+            notional += fill * price;
+            remaining -= fill;
+            worst_fill_price = Some(price);
+        }
+
+        let worst_fill_price = worst_fill_price?;
+        Some(PriceImpactResult {
+            avg_fill_price: notional / filled,
+            worst_fill_price,
+            total_fillable_qty: filled,
+            unfilled_qty: remaining,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+    use std::str::FromStr;
+
+    fn level(price: Decimal, quantity: Decimal) -> OrderLevel {
+        OrderLevel { price, quantity }
+    }
+
+    #[test]
+    fn test_from_raw_matches_from_levels() {
+        let raw = OrderBook::from_raw(
+            "BTC-USD-251031-140000-P".to_string(),
+            Exchange::Okex,
+            vec![(0.15, 10.0), (0.14, 0.0)],
+            vec![(0.16, 2.5)],
+        );
+        let levels = OrderBook::from_levels(
+            "BTC-USD-251031-140000-P".to_string(),
+            Exchange::Okex,
+            vec![level(dec!(0.15), dec!(10)), level(dec!(0.14), dec!(0))],
+            vec![level(dec!(0.16), dec!(2.5))],
+        );
+        assert_eq!(raw.bids, levels.bids);
+        assert_eq!(raw.asks, levels.asks);
+        assert_eq!(raw.bids.len(), 1);
+        assert!(raw.last_updated.is_some());
+    }
+
+    #[test]
+    fn test_max_depth_drops_worst_levels() {
+        let mut book = OrderBook::from_raw(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            vec![(0.10, 1.0), (0.11, 1.0), (0.12, 1.0)],
+            vec![(0.13, 1.0), (0.14, 1.0), (0.15, 1.0)],
+        )
+        .with_max_depth(2);
+        assert_eq!(
+            book.bids.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.11), dec!(0.12)]
+        );
+        assert_eq!(
+            book.asks.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.13), dec!(0.14)]
+        );
+
+        // A better level pushes out the worst one; a worse one is dropped straight away
+        book.update_bids(vec![
+            level(dec!(0.125), dec!(1)),
+            level(dec!(0.05), dec!(1)),
+        ]);
+        book.update_asks(vec![level(dec!(0.129), dec!(1))]);
+        assert_eq!(
+            book.bids.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.12), dec!(0.125)]
+        );
+        assert_eq!(
+            book.asks.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.129), dec!(0.13)]
+        );
+
+        book.update_from_snapshot(
+            vec![
+                level(dec!(0.1), dec!(1)),
+                level(dec!(0.2), dec!(1)),
+                level(dec!(0.3), dec!(1)),
+            ],
+            Vec::new(),
+        );
+        assert_eq!(
+            book.bids.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.2), dec!(0.3)]
+        );
+    }
+
+    #[test]
+    fn test_decimal_price_round_trips_unchanged() {
+        let mut book = OrderBook::new("PRECISION".to_string(), Exchange::Okex);
+        let price = Decimal::from_str("0.123456789").unwrap();
+        let quantity = Decimal::from_str("1.000000001").unwrap();
+        book.update_bids(vec![OrderLevel { price, quantity }]);
+
+        let best = book.best_bid().unwrap();
+        assert_eq!(best.price.to_string(), "0.123456789");
+        assert_eq!(best.quantity.to_string(), "1.000000001");
+    }
+
+    #[test]
+    fn test_merged_sums_overlapping_levels() {
+        let deribit = OrderBook::from_levels(
+            "BTC-31OCT25-140000-P".to_string(),
+            Exchange::Deribit,
+            vec![level(dec!(0.10), dec!(2)), level(dec!(0.09), dec!(1))],
+            vec![level(dec!(0.12), dec!(4))],
+        );
+        let bybit = OrderBook::from_levels(
+            "BTC-31OCT25-140000-P".to_string(),
+            Exchange::Bybit,
+            vec![level(dec!(0.10), dec!(3))],
+            vec![level(dec!(0.12), dec!(1.5)), level(dec!(0.13), dec!(2))],
+        );
+
+        let merged = OrderBook::merged(&[&deribit, &bybit], "BTC-31OCT25-140000-P".to_string());
+        assert_eq!(merged.exchange, Exchange::Aggregated);
+        assert!(merged.instrument.is_some());
+        assert_eq!(merged.bids[&dec!(0.10)], dec!(5));
+        assert_eq!(merged.bids[&dec!(0.09)], dec!(1));
+        assert_eq!(merged.asks[&dec!(0.12)], dec!(5.5));
+        assert_eq!(merged.asks[&dec!(0.13)], dec!(2));
+        assert_eq!(
+            merged.last_updated,
+            deribit.last_updated.max(bybit.last_updated)
+        );
+
+        // The source books are left as they were.
+        assert_eq!(deribit.bids[&dec!(0.10)], dec!(2));
+    }
+
+    #[test]
+    fn test_instrument_survives_updates() {
+        let mut book = OrderBook::new("BTC-31OCT25-140000-P".to_string(), Exchange::Deribit);
+        assert_eq!(book.expiry(), NaiveDate::from_ymd_opt(2025, 10, 31));
+        assert_eq!(book.strike(), Some(dec!(140000)));
+
+        book.update_from_snapshot(vec![level(dec!(0.1), dec!(1))], Vec::new());
+        book.update_asks(vec![level(dec!(0.2), dec!(1))]);
+        book.clear();
+        assert_eq!(book.strike(), Some(dec!(140000)));
+        assert_eq!(book.clone_top_n(1).expiry(), book.expiry());
+
+        let unrecognised = OrderBook::new("BTC-PERPETUAL".to_string(), Exchange::Deribit);
+        assert_eq!((unrecognised.expiry(), unrecognised.strike()), (None, None));
+    }
+
+    #[test]
+    fn test_clear_empties_both_sides() {
+        let mut book = OrderBook::from_levels(
+            "CLEAR".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(10.0),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.160),
+                quantity: dec!(10.0),
+            }],
+        );
+
+        book.clear();
+
+        assert!(book.best_bid().is_none());
+        assert!(book.best_ask().is_none());
+        assert_eq!(book.symbol, "CLEAR");
+    }
+
+    #[test]
+    fn test_apply_update() {
+        let mut book = OrderBook::new("APPLY".to_string(), Exchange::Deribit);
+        let bids = |exchange: Exchange, symbol: &str| OrderBookUpdate::Bids {
+            exchange,
+            symbol: symbol.to_string(),
+            levels: vec![level(dec!(0.1), dec!(2))],
+            received_at: Instant::now(),
+        };
+
+        book.apply_update(&bids(Exchange::Deribit, "APPLY"))
+            .unwrap();
+        book.apply_update(&OrderBookUpdate::Asks {
+            exchange: Exchange::Deribit,
+            symbol: "APPLY".to_string(),
+            levels: vec![level(dec!(0.2), dec!(3))],
+            received_at: Instant::now(),
+        })
+        .unwrap();
+        assert_eq!(book.best_bid(), Some(level(dec!(0.1), dec!(2))));
+        assert_eq!(book.best_ask(), Some(level(dec!(0.2), dec!(3))));
+
+        book.apply_update(&OrderBookUpdate::Snapshot {
+            exchange: Exchange::Deribit,
+            symbol: "APPLY".to_string(),
+            bids: vec![level(dec!(0.12), dec!(1))],
+            asks: vec![],
+            received_at: Instant::now(),
+        })
+        .unwrap();
+        assert_eq!(book.best_bid(), Some(level(dec!(0.12), dec!(1))));
+        assert!(book.best_ask().is_none());
+
+        assert_eq!(
+            book.apply_update(&bids(Exchange::Okex, "APPLY")),
+            Err(BookUpdateError::ExchangeMismatch {
+                book: Exchange::Deribit,
+                update: Exchange::Okex,
+            })
+        );
+        assert_eq!(
+            book.apply_update(&bids(Exchange::Deribit, "OTHER")),
+            Err(BookUpdateError::SymbolMismatch {
+                book: "APPLY".to_string(),
+                update: "OTHER".to_string(),
+            })
+        );
+        assert_eq!(
+            book.apply_update(&OrderBookUpdate::ConnectionError {
+                exchange: Exchange::Deribit,
+                error: "closed".to_string(),
+                symbol: "APPLY".to_string(),
+            }),
+            Err(BookUpdateError::UnexpectedVariant)
+        );
+        assert_eq!(book.best_bid(), Some(level(dec!(0.12), dec!(1))));
+
+        book.apply_update(&OrderBookUpdate::Clear {
+            exchange: Exchange::Deribit,
+            symbol: "APPLY".to_string(),
+        })
+        .unwrap();
+        assert!(book.best_bid().is_none());
+    }
+
+    #[test]
+    fn test_updates_refresh_last_updated() {
+        let mut book = OrderBook::new("FRESH".to_string(), Exchange::Okex);
+        assert!(book.last_updated.is_none());
+
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.1),
+            quantity: dec!(1.0),
+        }]);
+        let after_bids = book.last_updated.unwrap();
+
+        book.update_asks(vec![OrderLevel {
+            price: dec!(0.2),
+            quantity: dec!(1.0),
+        }]);
+        assert!(book.last_updated.unwrap() >= after_bids);
+    }
+
+    #[test]
+    fn test_mid_price_and_spread() {
+        let mut book = OrderBook::new("SPREAD".to_string(), Exchange::Deribit);
+        assert!(book.mid_price().is_none());
+        assert!(book.spread().is_none());
+        assert!(book.spread_bps().is_none());
+
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.095),
+            quantity: dec!(10.0),
+        }]);
+        // One-sided book still has no mid or spread
+        assert!(book.mid_price().is_none());
+        assert!(book.spread_bps().is_none());
+
+        book.update_asks(vec![OrderLevel {
+            price: dec!(0.105),
+            quantity: dec!(10.0),
+        }]);
+        assert_eq!(book.mid_price(), Some(dec!(0.1)));
+        assert_eq!(book.spread(), Some(dec!(0.01)));
+        assert_eq!(book.spread_bps(), Some(dec!(1000)));
+
+        // Crossed book: bid above ask gives a negative spread
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.110),
+            quantity: dec!(5.0),
+        }]);
+        assert_eq!(book.mid_price(), Some(dec!(0.1075)));
+        assert_eq!(book.spread(), Some(dec!(-0.005)));
+        assert!(book.spread_bps().unwrap() < Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_price_impact_buy_walks_asks() {
+        let book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(1.0),
+                    quantity: dec!(50),
+                },
+                OrderLevel {
+                    price: dec!(1.05),
+                    quantity: dec!(50),
+                },
+            ],
+        );
+
+        let impact = book.price_impact(dec!(100), Side::Buy).unwrap();
+        assert_eq!(impact.avg_fill_price, dec!(1.025));
+        assert_eq!(impact.worst_fill_price, dec!(1.05));
+        assert_eq!(impact.total_fillable_qty, dec!(100));
+        assert_eq!(impact.unfilled_qty, Decimal::ZERO);
+
+        // Only the best level is touched for a small order
+        let impact = book.price_impact(dec!(20), Side::Buy).unwrap();
+        assert_eq!(impact.avg_fill_price, dec!(1.0));
+        assert_eq!(impact.worst_fill_price, dec!(1.0));
+    }
+
+    #[test]
+    fn test_price_impact_sell_walks_bids_and_reports_unfilled() {
+        let book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.9),
+                    quantity: dec!(30),
+                },
+                OrderLevel {
+                    price: dec!(0.8),
+                    quantity: dec!(10),
+                },
+            ],
+            Vec::new(),
+        );
+
+        let impact = book.price_impact(dec!(50), Side::Sell).unwrap();
+        assert_eq!(impact.avg_fill_price, dec!(0.875));
+        assert_eq!(impact.worst_fill_price, dec!(0.8));
+        assert_eq!(impact.total_fillable_qty, dec!(40));
+        assert_eq!(impact.unfilled_qty, dec!(10));
+
+        assert_eq!(book.price_impact(dec!(10), Side::Buy), None);
+        assert_eq!(book.price_impact(Decimal::ZERO, Side::Sell), None);
+    }
+
+    #[test]
+    fn test_is_crossed_transitions() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        assert!(!book.is_crossed());
+
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.140),
+            quantity: dec!(10),
+        }]);
+        book.update_asks(vec![OrderLevel {
+            price: dec!(0.145),
+            quantity: dec!(10),
+        }]);
+        assert!(!book.is_crossed());
+
+        // A bid arriving at the ask price crosses the book before the ask is removed
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.145),
+            quantity: dec!(5),
+        }]);
+        assert!(book.is_crossed());
+
+        // The ask being lifted uncrosses it again
+        book.update_asks(vec![
+            OrderLevel {
+                price: dec!(0.145),
+                quantity: dec!(0),
+            },
+            OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(10),
+            },
+        ]);
+        assert!(!book.is_crossed());
+    }
+
+    #[test]
+    fn test_prune_deep_levels_at_boundary() {
+        let levels = |prices: &[Decimal]| {
+            prices
+                .iter()
+                .map(|&price| OrderLevel {
+                    price,
+                    quantity: dec!(1),
+                })
+                .collect()
+        };
+        let mut book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            levels(&[dec!(0.100), dec!(0.120), dec!(0.130)]),
+            levels(&[dec!(0.140), dec!(0.150), dec!(0.170)]),
+        );
+
+        // Levels exactly at the threshold are kept
+        book.remove_bids_below(dec!(0.120));
+        book.remove_asks_above(dec!(0.150));
+        assert_eq!(
+            book.bids.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.120), dec!(0.130)]
+        );
+        assert_eq!(
+            book.asks.keys().copied().collect::<Vec<_>>(),
+            [dec!(0.140), dec!(0.150)]
+        );
+
+        book.remove_bids_below(dec!(1));
+        book.remove_asks_above(dec!(0));
+        assert_eq!(book.bid_levels_count(), 0);
+        assert_eq!(book.ask_levels_count(), 0);
+    }
+
+    #[test]
+    fn test_update_from_snapshot_drops_old_levels() {
+        let mut book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(10),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(10),
+            }],
+        );
+
+        book.update_from_snapshot(
+            vec![OrderLevel {
+                price: dec!(0.135),
+                quantity: dec!(5),
+            }],
+            Vec::new(),
+        );
+        assert_eq!(book.bid_levels_count(), 1);
+        assert_eq!(book.best_bid().unwrap().price, dec!(0.135));
+        assert!(book.best_ask().is_none());
+
+        book.replace_all(
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.145),
+                quantity: dec!(2),
+            }],
+        );
+        assert!(book.best_bid().is_none());
+        assert_eq!(book.ask_levels_count(), 1);
+        assert_eq!(book.best_ask().unwrap().price, dec!(0.145));
+    }
+
+    #[test]
+    fn test_replace_one_side() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        book.update_from_snapshot(
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(10),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(10),
+            }],
+        );
+
+        book.replace_bids(vec![
+            OrderLevel {
+                price: dec!(0.130),
+                quantity: dec!(3),
+            },
+            OrderLevel {
+                price: dec!(0.125),
+                quantity: dec!(0),
+            },
+        ]);
+        assert_eq!(book.bid_levels_count(), 1);
+        assert_eq!(book.best_bid().unwrap().price, dec!(0.130));
+        assert_eq!(book.best_ask().unwrap().price, dec!(0.150));
+
+        book.replace_asks(Vec::new());
+        assert!(book.best_ask().is_none());
+        assert_eq!(book.bid_levels_count(), 1);
+    }
+
+    #[test]
+    fn test_compute_checksum() {
+        let level = |price: &str, quantity: &str| OrderLevel {
+            price: Decimal::from_str(price).unwrap(),
+            quantity: Decimal::from_str(quantity).unwrap(),
+        };
+        // Example book from the Okex API docs, whose checksum string is
+        // "3366.1:7:3366.8:9:3366:6:3368:8". Expected values computed with zlib.crc32.
+        let mut book = OrderBook::new("BTC-USD-251031-140000-P".to_string(), Exchange::Okex);
+        book.update_from_snapshot(
+            vec![level("3366.1", "7"), level("3366", "6")],
+            vec![level("3366.8", "9"), level("3368", "8")],
+        );
+        assert_eq!(book.compute_checksum(), -1881014294);
+
+        // Delta: the best ask is lifted and a third bid appears, leaving uneven sides
+        // ("3366.1:7:3368:8:3366:6:3365.5:2").
+        book.update_asks(vec![level("3366.8", "0")]);
+        book.update_bids(vec![level("3365.5", "2")]);
+        assert_eq!(book.compute_checksum(), 118332521);
+
+        assert_eq!(
+            OrderBook::new("EMPTY".to_string(), Exchange::Okex).compute_checksum(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        assert!(!book.is_stale(Duration::ZERO));
+
+        book.update_bids(vec![OrderLevel {
+            price: dec!(0.140),
+            quantity: dec!(10),
+        }]);
+        assert!(!book.is_stale(Duration::from_secs(30)));
+
+        book.last_updated = Some(Instant::now() - Duration::from_secs(31));
+        assert!(book.is_stale(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_n_best_levels() {
+        let book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            (1..=5)
+                .map(|i| OrderLevel {
+                    price: Decimal::from(i),
+                    quantity: dec!(1),
+                })
+                .collect(),
+            (6..=10)
+                .map(|i| OrderLevel {
+                    price: Decimal::from(i),
+                    quantity: dec!(1),
+                })
+                .collect(),
+        );
+
+        let prices = |levels: Vec<OrderLevel>| levels.iter().map(|l| l.price).collect::<Vec<_>>();
+        assert_eq!(prices(book.n_best_bids(2)), [dec!(5), dec!(4)]);
+        assert_eq!(prices(book.n_best_asks(2)), [dec!(6), dec!(7)]);
+        assert_eq!(book.n_best_bids(10).len(), 5);
+        assert!(book.n_best_asks(0).is_empty());
+    }
+
+    #[test]
+    fn test_clone_top_n_keeps_best_levels() {
+        let book = OrderBook::from_levels(
+            "BTC-USD-240427-56000-C".to_string(),
+            Exchange::Okex,
+            (1..=5)
+                .map(|i| OrderLevel {
+                    price: Decimal::from(i),
+                    quantity: dec!(1),
+                })
+                .collect(),
+            (6..=10)
+                .map(|i| OrderLevel {
+                    price: Decimal::from(i),
+                    quantity: dec!(1),
+                })
+                .collect(),
+        );
+
+        let top = book.clone_top_n(2);
+        assert_eq!(
+            top.bids.keys().copied().collect::<Vec<_>>(),
+            [dec!(4), dec!(5)]
+        );
+        assert_eq!(
+            top.asks.keys().copied().collect::<Vec<_>>(),
+            [dec!(6), dec!(7)]
+        );
+        assert_eq!(top.symbol, book.symbol);
+        assert_eq!(top.exchange, book.exchange);
+        assert_eq!(top.best_bid(), book.best_bid());
+        assert_eq!(top.best_ask(), book.best_ask());
+
+        assert_eq!(book.clone_top_n(100).bids, book.bids);
+        assert!(book.clone_top_n(0).asks.is_empty());
+    }
+
+    #[test]
+    fn test_volume_and_level_counts() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        assert_eq!(book.total_bid_volume(), Decimal::ZERO);
+        assert_eq!(book.ask_levels_count(), 0);
+
+        book.update_bids(vec![
+            OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(10),
+            },
+            OrderLevel {
+                price: dec!(0.139),
+                quantity: dec!(2.5),
+            },
+        ]);
+        book.update_asks(vec![OrderLevel {
+            price: dec!(0.145),
+            quantity: dec!(7),
+        }]);
+
+        assert_eq!(book.total_bid_volume(), dec!(12.5));
+        assert_eq!(book.total_ask_volume(), dec!(7));
+        assert_eq!(book.bid_levels_count(), 2);
+        assert_eq!(book.ask_levels_count(), 1);
+    }
+
+    #[test]
+    fn test_weighted_average_prices() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        assert_eq!(book.weighted_average_bid(2), None);
+
+        book.update_bids(vec![
+            OrderLevel {
+                price: dec!(1.0),
+                quantity: dec!(100),
+            },
+            OrderLevel {
+                price: dec!(0.9),
+                quantity: dec!(200),
+            },
+        ]);
+        book.update_asks(vec![
+            OrderLevel {
+                price: dec!(1.1),
+                quantity: dec!(50),
+            },
+            OrderLevel {
+                price: dec!(1.2),
+                quantity: dec!(150),
+            },
+        ]);
+
+        assert_eq!(
+            book.weighted_average_bid(2).unwrap().round_dp(4),
+            dec!(0.9333)
+        );
+        assert_eq!(book.weighted_average_bid(1), Some(dec!(1.0)));
+        // Asking for more levels than exist averages what is there
+        assert_eq!(book.weighted_average_ask(10), Some(dec!(1.175)));
+        assert_eq!(book.weighted_average_ask(0), None);
+    }
+
+    #[test]
+    fn test_vwap_and_cumulative_volume() {
+        let mut book = OrderBook::new("BTC-USD-240427-56000-C".to_string(), Exchange::Okex);
+        assert_eq!(book.vwap(5, Side::Buy), None);
+        assert_eq!(book.cumulative_volume(dec!(1), Side::Buy), Decimal::ZERO);
+
+        book.update_asks(vec![level(dec!(1.1), dec!(10)), level(dec!(1.2), dec!(10))]);
+        book.update_bids(vec![level(dec!(1.0), dec!(5)), level(dec!(0.9), dec!(20))]);
+
+        assert_eq!(book.vwap(1, Side::Buy), Some(dec!(1.1)));
+        assert_eq!(book.vwap(2, Side::Buy), Some(dec!(1.15)));
+        assert_eq!(book.vwap(1, Side::Sell), Some(dec!(1.0)));
+        assert_eq!(book.vwap(5, Side::Sell), Some(dec!(0.92)));
+
+        assert_eq!(book.cumulative_volume(dec!(1.1), Side::Buy), dec!(10));
+        assert_eq!(book.cumulative_volume(dec!(1.15), Side::Buy), dec!(10));
+        assert_eq!(book.cumulative_volume(dec!(2), Side::Buy), dec!(20));
+        assert_eq!(book.cumulative_volume(dec!(1.0), Side::Sell), dec!(5));
+        assert_eq!(book.cumulative_volume(dec!(0.9), Side::Sell), dec!(25));
+        assert_eq!(
+            book.cumulative_volume(dec!(1.05), Side::Sell),
+            Decimal::ZERO
+        );
+    }
+}
@@ -0,0 +1,1340 @@
+use super::{ArbitrageOpportunity, Exchange, FeeConfig, OrderBook, TradeLevel};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tracing::trace_span;
+
+/// Matches one book's bids against another's asks, looking at most `max_depth` levels deep on
+/// each side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrageDetector {
+    max_depth: usize,
+    /// Level pairs whose spread is narrower than this, in basis points of the buy price, are
+    /// not traded.
+    min_spread_bps: Decimal,
+    /// Charged by the detection methods that do not take their own fees.
+    fee_config: FeeConfig,
+}
+
+impl Default for ArbitrageDetector {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Configures an `ArbitrageDetector`, starting from `ArbitrageDetector::default()`.
+#[derive(Debug, Clone, Default)]
+pub struct ArbitrageDetectorBuilder {
+    detector: ArbitrageDetector,
+}
+
+impl ArbitrageDetectorBuilder {
+    /// Levels beyond `max_depth` are never traded, even if their prices still cross.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.detector.max_depth = max_depth;
+        self
+    }
+
+    pub fn with_min_spread_bps(mut self, min_spread_bps: Decimal) -> Self {
+        self.detector.min_spread_bps = min_spread_bps;
+        self
+    }
+
+    pub fn with_fees(mut self, fee_config: FeeConfig) -> Self {
+        self.detector.fee_config = fee_config;
+        self
+    }
+
+    pub fn build(self) -> ArbitrageDetector {
+        self.detector
+    }
+}
+
+/// A book level while `check_direction` consumes it, with `qty` what is still unfilled in units
+/// of the underlying.
+#[derive(Debug, Clone, Copy)]
+struct PartialFilledLevel {
+    price: Decimal,
+    qty: Decimal,
+}
+
+impl PartialFilledLevel {
+    /// Start filling the level at `price`, quoted in contracts of `multiplier` underlying units
+    /// each; levels with nothing to fill are skipped.
+    fn fresh(price: Decimal, quantity: Decimal, multiplier: Decimal) -> Option<Self> {
+        (quantity > Decimal::ZERO).then_some(Self {
+            price,
+            qty: quantity * multiplier,
+        })
+    }
+}
+
+impl ArbitrageDetector {
+    /// Levels beyond `max_depth` are never traded, even if their prices still cross.
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            ..Self::unlimited()
+        }
+    }
+
+    /// Walks the whole book, without fees.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_depth: usize::MAX,
+            min_spread_bps: Decimal::ZERO,
+            fee_config: FeeConfig {
+                okex_taker_bps: Decimal::ZERO,
+                deribit_taker_bps: Decimal::ZERO,
+                binance_taker_bps: Decimal::ZERO,
+                bybit_taker_bps: Decimal::ZERO,
+            },
+        }
+    }
+
+    pub fn builder() -> ArbitrageDetectorBuilder {
+        ArbitrageDetectorBuilder::default()
+    }
+
+    /// Ignores spreads narrower than `min_spread_bps` basis points, which are more likely
+    /// rounding between venues' tick sizes than a real edge.
+    pub fn with_min_spread_bps(mut self, min_spread_bps: Decimal) -> Self {
+        self.min_spread_bps = min_spread_bps;
+        self
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn min_spread_bps(&self) -> Decimal {
+        self.min_spread_bps
+    }
+
+    pub fn fee_config(&self) -> &FeeConfig {
+        &self.fee_config
+    }
+
+    /// Detection with the configured fees, which are zero unless set through the builder.
+    pub fn detect_arbitrage(
+        &self,
+        book_a: &OrderBook,
+        book_b: &OrderBook,
+    ) -> Vec<ArbitrageOpportunity> {
+        self.detect_with_max_position(book_a, book_b, Decimal::MAX)
+    }
+
+    pub fn detect_arbitrage_with_fees(
+        &self,
+        book_a: &OrderBook,
+        book_b: &OrderBook,
+        fees: &FeeConfig,
+    ) -> Vec<ArbitrageOpportunity> {
+        self.detect_with_max_position_and_fees(book_a, book_b, fees, Decimal::MAX)
+    }
+
+    /// Detection with the configured fees that stops once `max_qty` contracts have been traded
+    /// in total.
+    pub fn detect_with_max_position(
+        &self,
+        book_a: &OrderBook,
+        book_b: &OrderBook,
+        max_qty: Decimal,
+    ) -> Vec<ArbitrageOpportunity> {
+        self.detect_with_max_position_and_fees(book_a, book_b, &self.fee_config, max_qty)
+    }
+
+    pub fn detect_with_max_position_and_fees(
+        &self,
+        book_a: &OrderBook,
+        book_b: &OrderBook,
+        fees: &FeeConfig,
+        max_qty: Decimal,
+    ) -> Vec<ArbitrageOpportunity> {
+        // Each direction is an alternative trade, so each gets the full budget. Both can only
+        // be profitable at once when a book is crossed.
+        let mut opportunities = Vec::new();
+
+        // Try buy on B, sell on A
+        opportunities.extend(self.check_direction(
+            book_a,
+            book_b,
+            &book_b.exchange,
+            &book_a.exchange,
+            fees,
+            &mut max_qty.to_owned(),
+        ));
+
+        // Try buy on A, sell on B
+        opportunities.extend(self.check_direction(
+            book_b,
+            book_a,
+            &book_a.exchange,
+            &book_b.exchange,
+            fees,
+            &mut max_qty.to_owned(),
+        ));
+
+        opportunities
+    }
+
+    /// Walks at most `max_depth` levels of each book from the top. Levels are matched in units of
+    /// the underlying, since venues size contracts differently; `remaining_budget` counts
+    /// contracts bought.
+    fn check_direction(
+        &self,
+        sell_book: &OrderBook,
+        buy_book: &OrderBook,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        fees: &FeeConfig,
+        remaining_budget: &mut Decimal,
+    ) -> Option<ArbitrageOpportunity> {
+        let _span = trace_span!("check_direction", %buy_exchange, %sell_exchange).entered();
+        let best_bid = sell_book.best_bid()?;
+        let best_ask = buy_book.best_ask()?;
+
+        if best_bid.price <= best_ask.price {
+            return None;
+        }
+
+        let mut trades = Vec::new();
+        let mut total_profit = Decimal::ZERO;
+        let mut total_volume = Decimal::ZERO;
+
+        // Levels are walked lazily, best first, so detection only touches the levels it trades.
+        // They are consumed in place: each side holds the level being filled and what is left
+        // of it, moving to the next level only once nothing remains.
+        let sell_multiplier = sell_book.contract_multiplier();
+        let buy_multiplier = buy_book.contract_multiplier();
+        let mut sell_iter =
+            sell_book
+                .bids
+                .iter()
+                .rev()
+                .take(self.max_depth)
+                .filter_map(|(&price, &quantity)| {
+                    PartialFilledLevel::fresh(price, quantity, sell_multiplier)
+                });
+        let mut buy_iter =
+            buy_book
+                .asks
+                .iter()
+                .take(self.max_depth)
+                .filter_map(|(&price, &quantity)| {
+                    PartialFilledLevel::fresh(price, quantity, buy_multiplier)
+                });
+        let mut current_sell = sell_iter.next();
+        let mut current_buy = buy_iter.next();
+
+        while let (Some(mut sell), Some(mut buy)) = (current_sell, current_buy) {
+            if sell.price <= buy.price || *remaining_budget <= Decimal::ZERO {
+                break;
+            }
+            // Like fees below, deeper levels only narrow the spread further.
+            if buy.price > Decimal::ZERO
+                && (sell.price - buy.price) / buy.price * dec!(10000) < self.min_spread_bps
+            {
+                break;
+            }
+
+            let normalized_qty = sell
+                .qty
+                .min(buy.qty)
+                .min(*remaining_budget * buy_multiplier);
+            let trade_qty = normalized_qty / buy_multiplier;
+
+            let gross_profit = normalized_qty * (sell.price - buy.price);
+            let fee_cost = fees.fee_cost(
+                buy_exchange,
+                sell_exchange,
+                buy.price,
+                sell.price,
+                normalized_qty,
+            );
+            let profit = gross_profit - fee_cost;
+
+            // Deeper levels only get worse, so stop once fees eat the spread.
+            if profit <= Decimal::ZERO {
+                break;
+            }
+
+            trades.push(TradeLevel {
+                buy_price: buy.price,
+                sell_price: sell.price,
+                quantity: trade_qty,
+                normalized_quantity: normalized_qty,
+                gross_profit,
+                fee_cost,
+                profit,
+            });
+
+            total_profit += profit;
+            total_volume += trade_qty;
+            *remaining_budget -= trade_qty;
+
+            sell.qty -= normalized_qty;
+            buy.qty -= normalized_qty;
+            current_sell = if sell.qty > Decimal::ZERO {
+                Some(sell)
+            } else {
+                sell_iter.next()
+            };
+            current_buy = if buy.qty > Decimal::ZERO {
+                Some(buy)
+            } else {
+                buy_iter.next()
+            };
+        }
+
+        if total_profit <= Decimal::ZERO {
+            return None;
+        }
+        let opportunity = ArbitrageOpportunity {
+            buy_exchange: buy_exchange.clone(),
+            sell_exchange: sell_exchange.clone(),
+            symbol: sell_book.symbol.clone(),
+            trades,
+            total_profit,
+            total_volume,
+        };
+        debug_assert!(
+            opportunity.is_valid(),
+            "inconsistent opportunity: {opportunity:?}"
+        );
+        Some(opportunity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderLevel;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn level(price: Decimal, quantity: Decimal) -> OrderLevel {
+        OrderLevel { price, quantity }
+    }
+
+    #[test]
+    fn test_builder_fees_reject_sub_fee_opportunities() {
+        // A 0.001 spread on a 0.1 price: 100 bps gross, against 60 bps of fees per side.
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![level(dec!(0.100), dec!(10))],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![level(dec!(0.101), dec!(10))],
+            Vec::new(),
+        );
+        let fees = FeeConfig {
+            okex_taker_bps: dec!(60),
+            deribit_taker_bps: dec!(60),
+            ..FeeConfig::default()
+        };
+
+        let default = ArbitrageDetector::default();
+        assert_eq!(default, ArbitrageDetector::builder().build());
+        assert_eq!(default.detect_arbitrage(&okex_book, &deribit_book).len(), 1);
+
+        let detector = ArbitrageDetector::builder()
+            .with_max_depth(5)
+            .with_min_spread_bps(dec!(1))
+            .with_fees(fees.clone())
+            .build();
+        assert_eq!(detector.max_depth(), 5);
+        assert_eq!(detector.fee_config(), &fees);
+        assert!(
+            detector
+                .clone()
+                .detect_arbitrage(&okex_book, &deribit_book)
+                .is_empty()
+        );
+        assert!(
+            detector
+                .detect_with_max_position(&okex_book, &deribit_book, dec!(1))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_min_spread_bps_filters_narrow_spreads() {
+        // 0.00005 over 1.0 is 0.5 bps.
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![level(dec!(1.0), dec!(10))],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![level(dec!(1.00005), dec!(10))],
+            Vec::new(),
+        );
+
+        let detector = ArbitrageDetector::unlimited();
+        assert_eq!(detector.min_spread_bps(), Decimal::ZERO);
+        assert_eq!(
+            detector.detect_arbitrage(&okex_book, &deribit_book).len(),
+            1
+        );
+        assert!(
+            detector
+                .with_min_spread_bps(dec!(1))
+                .detect_arbitrage(&okex_book, &deribit_book)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_simple_single_level_arbitrage() {
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(100.0),
+            }],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(100.0),
+            }],
+            Vec::new(),
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+
+        assert_eq!(opportunity.trades.len(), 1);
+        assert_eq!(opportunity.total_volume, dec!(100.0));
+        assert_eq!(
+            opportunity.total_profit,
+            dec!(100.0) * (dec!(0.150) - dec!(0.140))
+        );
+    }
+
+    #[test]
+    fn test_multi_level_profit_accumulation() {
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.150),
+                    quantity: dec!(50.0),
+                },
+                OrderLevel {
+                    price: dec!(0.145),
+                    quantity: dec!(75.0),
+                },
+                OrderLevel {
+                    price: dec!(0.140),
+                    quantity: dec!(100.0),
+                },
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.135),
+                    quantity: dec!(30.0),
+                },
+                OrderLevel {
+                    price: dec!(0.138),
+                    quantity: dec!(40.0),
+                },
+                OrderLevel {
+                    price: dec!(0.142),
+                    quantity: dec!(200.0),
+                },
+            ],
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+        assert_eq!(opportunity.trades.len(), 4);
+
+        let expected_profit = dec!(30.0) * (dec!(0.150) - dec!(0.135))
+            + dec!(20.0) * (dec!(0.150) - dec!(0.138))
+            + dec!(20.0) * (dec!(0.145) - dec!(0.138))
+            + dec!(55.0) * (dec!(0.145) - dec!(0.142));
+
+        assert_eq!(opportunity.total_profit, expected_profit);
+        assert_eq!(opportunity.total_volume, dec!(125.0));
+    }
+
+    #[test]
+    fn test_partial_level_consumption_complex() {
+        let okex_book = OrderBook::from_levels(
+            "TEST".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.200),
+                    quantity: dec!(25.0),
+                }, // Small bid
+                OrderLevel {
+                    price: dec!(0.190),
+                    quantity: dec!(1000.0),
+                }, // Large bid
+                OrderLevel {
+                    price: dec!(0.180),
+                    quantity: dec!(500.0),
+                }, // Larger bid
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "TEST".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.170),
+                    quantity: dec!(100.0),
+                }, // Medium ask
+                OrderLevel {
+                    price: dec!(0.175),
+                    quantity: dec!(200.0),
+                }, // Larger ask
+                OrderLevel {
+                    price: dec!(0.185),
+                    quantity: dec!(2000.0),
+                }, // Very large ask
+            ],
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+
+        // Expected trades:
+        // 1. Buy 25 at 0.170, Sell at 0.200 = 25 * 0.030 = 0.75
+        // 2. Buy 75 at 0.170, Sell at 0.190 = 75 * 0.020 = 1.50
+        // 3. Buy 200 at 0.175, Sell at 0.190 = 200 * 0.015 = 3.00
+        // 4. Buy remaining at 0.185 vs 0.190 = some amount * 0.005
+
+        let expected_volume = dec!(25.0) + dec!(75.0) + dec!(200.0); // At least 300
+        let expected_min_profit =
+            dec!(25.0) * dec!(0.030) + dec!(75.0) * dec!(0.020) + dec!(200.0) * dec!(0.015);
+
+        assert!(opportunity.total_volume >= expected_volume);
+        assert!(opportunity.total_profit >= expected_min_profit);
+        assert!(opportunity.trades.len() >= 3);
+    }
+
+    #[test]
+    fn test_exactly_matching_quantities() {
+        // Scenario where quantities match exactly across multiple levels
+        let okex_book = OrderBook::from_levels(
+            "TEST".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.160),
+                    quantity: dec!(75.0),
+                },
+                OrderLevel {
+                    price: dec!(0.150),
+                    quantity: dec!(100.0),
+                },
+                OrderLevel {
+                    price: dec!(0.145),
+                    quantity: dec!(50.0),
+                },
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "TEST".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.140),
+                    quantity: dec!(50.0),
+                },
+                OrderLevel {
+                    price: dec!(0.135),
+                    quantity: dec!(100.0),
+                },
+                OrderLevel {
+                    price: dec!(0.130),
+                    quantity: dec!(75.0),
+                },
+            ],
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+
+        // Should have exactly 3 trades with perfect quantity matches
+        assert_eq!(opportunity.trades.len(), 3);
+
+        let expected_profit = dec!(75.0) * (dec!(0.160) - dec!(0.130))
+            + dec!(100.0) * (dec!(0.150) - dec!(0.135))
+            + dec!(50.0) * (dec!(0.145) - dec!(0.140));
+
+        assert_eq!(opportunity.total_volume, dec!(225.0));
+        assert_eq!(opportunity.total_profit, expected_profit);
+    }
+
+    #[test]
+    fn test_deep_order_book_many_levels() {
+        // Deep order book with many small levels
+        let okex_book = OrderBook::from_levels(
+            "DEEP-TEST".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.200),
+                    quantity: dec!(10.0),
+                },
+                OrderLevel {
+                    price: dec!(0.199),
+                    quantity: dec!(15.0),
+                },
+                OrderLevel {
+                    price: dec!(0.198),
+                    quantity: dec!(20.0),
+                },
+                OrderLevel {
+                    price: dec!(0.197),
+                    quantity: dec!(25.0),
+                },
+                OrderLevel {
+                    price: dec!(0.196),
+                    quantity: dec!(30.0),
+                },
+                OrderLevel {
+                    price: dec!(0.195),
+                    quantity: dec!(35.0),
+                },
+                OrderLevel {
+                    price: dec!(0.194),
+                    quantity: dec!(40.0),
+                },
+                OrderLevel {
+                    price: dec!(0.193),
+                    quantity: dec!(45.0),
+                },
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "DEEP-TEST".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.185),
+                    quantity: dec!(50.0),
+                },
+                OrderLevel {
+                    price: dec!(0.186),
+                    quantity: dec!(45.0),
+                },
+                OrderLevel {
+                    price: dec!(0.187),
+                    quantity: dec!(40.0),
+                },
+                OrderLevel {
+                    price: dec!(0.188),
+                    quantity: dec!(35.0),
+                },
+                OrderLevel {
+                    price: dec!(0.189),
+                    quantity: dec!(30.0),
+                },
+                OrderLevel {
+                    price: dec!(0.190),
+                    quantity: dec!(25.0),
+                },
+                OrderLevel {
+                    price: dec!(0.191),
+                    quantity: dec!(20.0),
+                },
+                OrderLevel {
+                    price: dec!(0.192),
+                    quantity: dec!(15.0),
+                },
+            ],
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+
+        // Should traverse multiple levels
+        assert!(opportunity.trades.len() >= 5);
+        assert!(opportunity.total_volume > dec!(100.0));
+        assert!(opportunity.total_profit > dec!(1.0));
+    }
+
+    #[test]
+    fn test_profit_precision_accumulation() {
+        // Test precision with small price differences
+        let okex_book = OrderBook::from_levels(
+            "PRECISION-TEST".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.123456789),
+                    quantity: dec!(1000000.0),
+                },
+                OrderLevel {
+                    price: dec!(0.123456788),
+                    quantity: dec!(2000000.0),
+                },
+                OrderLevel {
+                    price: dec!(0.123456787),
+                    quantity: dec!(1500000.0),
+                },
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "PRECISION-TEST".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.123456785),
+                    quantity: dec!(500000.0),
+                },
+                OrderLevel {
+                    price: dec!(0.123456786),
+                    quantity: dec!(1000000.0),
+                },
+                OrderLevel {
+                    price: dec!(0.123456787),
+                    quantity: dec!(3000000.0),
+                },
+            ],
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+
+        // Verify that small decimal differences are handled correctly
+        for (i, trade) in opportunity.trades.iter().enumerate() {
+            println!(
+                "Trade {}: {} * ({} - {}) = {}",
+                i + 1,
+                trade.quantity,
+                trade.sell_price,
+                trade.buy_price,
+                trade.profit
+            );
+        }
+
+        assert!(opportunity.total_profit > Decimal::ZERO);
+        assert!(opportunity.total_volume > dec!(500000.0));
+
+        // Ensure precision is maintained - should not lose decimal places
+        assert!(opportunity.total_profit.to_string().contains('.'));
+    }
+
+    #[test]
+    fn test_no_arbitrage_scenarios() {
+        // Scenario 1: Normal spread (no crossing)
+        let okex_book = OrderBook::from_levels(
+            "NO-ARB-1".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.130),
+                quantity: dec!(100.0),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.135),
+                quantity: dec!(100.0),
+            }],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "NO-ARB-1".to_string(),
+            Exchange::Deribit,
+            vec![OrderLevel {
+                price: dec!(0.129),
+                quantity: dec!(100.0),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.136),
+                quantity: dec!(100.0),
+            }],
+        );
+        assert!(
+            ArbitrageDetector::unlimited()
+                .detect_arbitrage(&okex_book, &deribit_book)
+                .is_empty()
+        );
+
+        // Scenario 2: Equal prices
+        let okex_book2 = OrderBook::from_levels(
+            "NO-ARB-2".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(100.0),
+            }],
+            Vec::new(),
+        );
+        let deribit_book2 = OrderBook::from_levels(
+            "NO-ARB-2".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(100.0),
+            }],
+        );
+        assert!(
+            ArbitrageDetector::unlimited()
+                .detect_arbitrage(&okex_book2, &deribit_book2)
+                .is_empty()
+        );
+
+        // Scenario 3: Empty order books
+        let okex_book3 = OrderBook::new("NO-ARB-3".to_string(), Exchange::Okex);
+        let deribit_book3 = OrderBook::new("NO-ARB-3".to_string(), Exchange::Deribit);
+        assert!(
+            ArbitrageDetector::unlimited()
+                .detect_arbitrage(&okex_book3, &deribit_book3)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_edge_case_scenarios() {
+        // Edge Case 1: Zero quantities
+        let okex_book = OrderBook::from_levels(
+            "EDGE-1".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.160),
+                quantity: dec!(0.0),
+            }],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "EDGE-1".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(100.0),
+            }],
+        );
+        let opportunities =
+            ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book);
+        assert!(opportunities.is_empty());
+
+        // Edge Case 2: Very small quantities
+        let okex_book2 = OrderBook::from_levels(
+            "EDGE-2".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.160),
+                quantity: dec!(0.001),
+            }],
+            Vec::new(),
+        );
+        let deribit_book2 = OrderBook::from_levels(
+            "EDGE-2".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(0.001),
+            }],
+        );
+        let opportunity2 =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book2, &deribit_book2)[0];
+        assert!(opportunity2.total_profit > Decimal::ZERO);
+        assert_eq!(opportunity2.total_volume, dec!(0.001));
+    }
+
+    #[test]
+    fn test_fees_reduce_level_profit() {
+        let okex_book = OrderBook::from_levels(
+            "FEES-1".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(100.0),
+            }],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "FEES-1".to_string(),
+            Exchange::Deribit,
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(100.0),
+            }],
+            Vec::new(),
+        );
+
+        let fees = FeeConfig {
+            okex_taker_bps: dec!(3),
+            deribit_taker_bps: dec!(3),
+            binance_taker_bps: dec!(3),
+            bybit_taker_bps: dec!(3),
+        };
+        let opportunity = &ArbitrageDetector::unlimited().detect_arbitrage_with_fees(
+            &okex_book,
+            &deribit_book,
+            &fees,
+        )[0];
+
+        let trade = &opportunity.trades[0];
+        let expected_fee = dec!(100.0) * (dec!(0.140) + dec!(0.150)) * dec!(3) / dec!(10000);
+        assert_eq!(
+            trade.gross_profit,
+            dec!(100.0) * (dec!(0.150) - dec!(0.140))
+        );
+        assert_eq!(trade.fee_cost, expected_fee);
+        assert_eq!(trade.profit, trade.gross_profit - expected_fee);
+        assert_eq!(opportunity.total_profit, trade.profit);
+    }
+
+    #[test]
+    fn test_fees_exceeding_spread() {
+        let okex_book = OrderBook::from_levels(
+            "FEES-2".to_string(),
+            Exchange::Okex,
+            vec![
+                OrderLevel {
+                    price: dec!(0.1500),
+                    quantity: dec!(10.0),
+                },
+                OrderLevel {
+                    price: dec!(0.1401),
+                    quantity: dec!(10.0),
+                },
+            ],
+            Vec::new(),
+        );
+        let deribit_book = OrderBook::from_levels(
+            "FEES-2".to_string(),
+            Exchange::Deribit,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.1400),
+                quantity: dec!(100.0),
+            }],
+        );
+
+        // 50 bps on each leg costs ~0.00145 per contract: the first level stays
+        // profitable, the thin second level does not.
+        let fees = FeeConfig {
+            okex_taker_bps: dec!(50),
+            deribit_taker_bps: dec!(50),
+            binance_taker_bps: dec!(50),
+            bybit_taker_bps: dec!(50),
+        };
+        let opportunity = &ArbitrageDetector::unlimited().detect_arbitrage_with_fees(
+            &okex_book,
+            &deribit_book,
+            &fees,
+        )[0];
+        assert_eq!(opportunity.trades.len(), 1);
+        assert_eq!(opportunity.total_volume, dec!(10.0));
+
+        let prohibitive = FeeConfig {
+            okex_taker_bps: dec!(500),
+            deribit_taker_bps: dec!(500),
+            binance_taker_bps: dec!(500),
+            bybit_taker_bps: dec!(500),
+        };
+        assert!(
+            ArbitrageDetector::unlimited()
+                .detect_arbitrage_with_fees(&okex_book, &deribit_book, &prohibitive)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_max_depth_limits_levels_walked() {
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![
+                level(dec!(0.140), dec!(10)),
+                level(dec!(0.145), dec!(10)),
+                level(dec!(0.150), dec!(10)),
+            ],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![level(dec!(0.160), dec!(100))],
+            Vec::new(),
+        );
+
+        let unlimited = ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book);
+        assert_eq!(unlimited[0].trades.len(), 3);
+
+        let shallow = ArbitrageDetector::new(2).detect_arbitrage(&okex_book, &deribit_book);
+        assert_eq!(shallow[0].trades.len(), 2);
+        assert_eq!(shallow[0].total_volume, dec!(20));
+
+        assert!(
+            ArbitrageDetector::new(0)
+                .detect_arbitrage(&okex_book, &deribit_book)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_empty_level_does_not_end_the_walk() {
+        // `bids`/`asks` are public, so a zero-quantity level can reach the detector without
+        // going through `update_bids`. The walk used to treat it as a zero-sized fill, find
+        // no profit and stop before the crossed level behind it.
+        let mut okex_book = OrderBook::new("ETH-USD-240427-3000-C".to_string(), Exchange::Okex);
+        let mut deribit_book = OrderBook::new("ETH-27APR24-3000-C".to_string(), Exchange::Deribit);
+        deribit_book.bids.insert(dec!(0.150), Decimal::ZERO);
+        deribit_book.bids.insert(dec!(0.149), dec!(10));
+        okex_book.asks.insert(dec!(0.140), dec!(10));
+
+        let opportunities =
+            ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book);
+        assert_eq!(opportunities.len(), 1);
+        let trades = &opportunities[0].trades;
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].sell_price, dec!(0.149));
+        assert_eq!(trades[0].quantity, dec!(10));
+    }
+
+    #[test]
+    fn test_partial_fills_carry_over_between_levels() {
+        // Each level is split across several fills on the other side, including an exact
+        // match that exhausts both levels at once.
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.140),
+                    quantity: dec!(10),
+                },
+                OrderLevel {
+                    price: dec!(0.145),
+                    quantity: dec!(15),
+                },
+                OrderLevel {
+                    price: dec!(0.150),
+                    quantity: dec!(20),
+                },
+            ],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![
+                OrderLevel {
+                    price: dec!(0.160),
+                    quantity: dec!(25),
+                },
+                OrderLevel {
+                    price: dec!(0.155),
+                    quantity: dec!(10),
+                },
+            ],
+            Vec::new(),
+        );
+
+        let opportunity =
+            &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+        let fills: Vec<_> = opportunity
+            .trades
+            .iter()
+            .map(|t| (t.buy_price, t.sell_price, t.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (dec!(0.140), dec!(0.160), dec!(10)),
+                (dec!(0.145), dec!(0.160), dec!(15)),
+                (dec!(0.150), dec!(0.155), dec!(10)),
+            ]
+        );
+        assert_eq!(opportunity.total_volume, dec!(35));
+    }
+
+    #[test]
+    fn test_quantities_normalized_across_contract_sizes() {
+        // 100 Okex contracts are 10 BTC; Deribit bids for 4 BTC.
+        let okex_book = OrderBook::from_levels(
+            "BTC-USD-251031-140000-P".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![level(dec!(0.140), dec!(100))],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "BTC-31OCT25-140000-P".to_string(),
+            Exchange::Deribit,
+            vec![level(dec!(0.150), dec!(4))],
+            Vec::new(),
+        );
+
+        let opportunities =
+            ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book);
+        assert_eq!(opportunities.len(), 1);
+        let trade = &opportunities[0].trades[0];
+        assert_eq!(trade.quantity, dec!(40));
+        assert_eq!(trade.normalized_quantity, dec!(4));
+        assert_eq!(trade.gross_profit, dec!(0.04));
+        assert_eq!(opportunities[0].total_volume, dec!(40));
+
+        // The position cap counts contracts bought.
+        let capped = ArbitrageDetector::unlimited().detect_with_max_position(
+            &okex_book,
+            &deribit_book,
+            dec!(20),
+        );
+        assert_eq!(capped[0].trades[0].normalized_quantity, dec!(2));
+    }
+
+    #[test]
+    fn test_detect_with_max_position_caps_volume() {
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![OrderLevel {
+                price: dec!(0.140),
+                quantity: dec!(200),
+            }],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![
+                OrderLevel {
+                    price: dec!(0.150),
+                    quantity: dec!(100),
+                },
+                OrderLevel {
+                    price: dec!(0.148),
+                    quantity: dec!(100),
+                },
+            ],
+            Vec::new(),
+        );
+
+        let full = &ArbitrageDetector::unlimited().detect_arbitrage(&okex_book, &deribit_book)[0];
+        assert_eq!(full.total_volume, dec!(200));
+
+        let capped = &ArbitrageDetector::unlimited().detect_with_max_position(
+            &okex_book,
+            &deribit_book,
+            dec!(50),
+        )[0];
+        assert_eq!(capped.total_volume, dec!(50));
+        assert_eq!(capped.trades.len(), 1);
+        assert_eq!(capped.trades[0].quantity, dec!(50));
+        assert_eq!(capped.trades[0].sell_price, dec!(0.150));
+        assert_eq!(capped.total_profit, dec!(0.5));
+
+        // A cap spanning levels takes the best one in full before dipping into the next
+        let capped = &ArbitrageDetector::unlimited().detect_with_max_position(
+            &okex_book,
+            &deribit_book,
+            dec!(150),
+        )[0];
+        assert_eq!(capped.total_volume, dec!(150));
+        assert_eq!(capped.trades.len(), 2);
+        assert_eq!(capped.trades[1].quantity, dec!(50));
+
+        assert!(
+            ArbitrageDetector::unlimited()
+                .detect_with_max_position(&okex_book, &deribit_book, Decimal::ZERO)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_both_directions_reported() {
+        // Only possible with a crossed book: Okex bids above its own ask and above Deribit's.
+        let okex_book = OrderBook::from_levels(
+            "ETH-USD-240427-3000-C".to_string(),
+            Exchange::Okex,
+            vec![OrderLevel {
+                price: dec!(0.160),
+                quantity: dec!(10),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.130),
+                quantity: dec!(10),
+            }],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "ETH-27APR24-3000-C".to_string(),
+            Exchange::Deribit,
+            vec![OrderLevel {
+                price: dec!(0.145),
+                quantity: dec!(10),
+            }],
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(10),
+            }],
+        );
+        // Cheap Okex fees, expensive Deribit fees: both directions still clear them
+        let fees = FeeConfig {
+            okex_taker_bps: dec!(1),
+            deribit_taker_bps: dec!(50),
+            binance_taker_bps: dec!(3),
+            bybit_taker_bps: dec!(3),
+        };
+
+        let opportunities = ArbitrageDetector::unlimited().detect_arbitrage_with_fees(
+            &okex_book,
+            &deribit_book,
+            &fees,
+        );
+        assert_eq!(opportunities.len(), 2);
+        assert_eq!(opportunities[0].buy_exchange, Exchange::Deribit);
+        assert_eq!(opportunities[0].sell_exchange, Exchange::Okex);
+        assert_eq!(opportunities[1].buy_exchange, Exchange::Okex);
+        assert_eq!(opportunities[1].sell_exchange, Exchange::Deribit);
+        assert!(
+            opportunities
+                .iter()
+                .all(|opp| opp.total_profit > Decimal::ZERO)
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use crate::orderbook::OrderLevel;
+    use proptest::prelude::*;
+
+    /// Prices on a 0.001 grid between 0.001 and 10.0.
+    const MAX_PRICE_TICKS: i64 = 10_000;
+
+    fn levels(
+        ticks: std::ops::Range<i64>,
+        max_levels: usize,
+    ) -> impl Strategy<Value = Vec<OrderLevel>> {
+        prop::collection::vec((ticks, 1i64..=10_000_000), 0..=max_levels).prop_map(|levels| {
+            levels
+                .into_iter()
+                .map(|(price, quantity)| OrderLevel {
+                    price: Decimal::new(price, 3),
+                    quantity: Decimal::new(quantity, 3),
+                })
+                .collect()
+        })
+    }
+
+    fn book_from(exchange: Exchange, bids: Vec<OrderLevel>, asks: Vec<OrderLevel>) -> OrderBook {
+        OrderBook::from_levels("BTC-USD-251031-140000-P".to_string(), exchange, bids, asks)
+    }
+
+    /// Any book, including ones crossed against themselves.
+    fn any_book(exchange: Exchange) -> impl Strategy<Value = OrderBook> {
+        (
+            levels(1..MAX_PRICE_TICKS + 1, 50),
+            levels(1..MAX_PRICE_TICKS + 1, 50),
+        )
+            .prop_map(move |(bids, asks)| book_from(exchange.clone(), bids, asks))
+    }
+
+    /// A book whose bids all sit strictly below its asks, as a consistent venue's would.
+    fn uncrossed_book(exchange: Exchange) -> impl Strategy<Value = OrderBook> {
+        (2..MAX_PRICE_TICKS).prop_flat_map(move |split| {
+            let exchange = exchange.clone();
+            (levels(1..split, 50), levels(split..MAX_PRICE_TICKS + 1, 50))
+                .prop_map(move |(bids, asks)| book_from(exchange.clone(), bids, asks))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn detected_opportunities_are_consistent(
+            book_a in any_book(Exchange::Okex),
+            book_b in any_book(Exchange::Deribit),
+        ) {
+            for opp in ArbitrageDetector::unlimited().detect_arbitrage(&book_a, &book_b) {
+                prop_assert!(opp.total_profit > Decimal::ZERO);
+                for trade in &opp.trades {
+                    prop_assert!(trade.buy_price < trade.sell_price);
+                }
+                prop_assert_eq!(
+                    opp.total_volume,
+                    opp.trades.iter().map(|t| t.quantity).sum::<Decimal>()
+                );
+                prop_assert_eq!(
+                    opp.total_profit,
+                    opp.trades.iter().map(|t| t.profit).sum::<Decimal>()
+                );
+            }
+        }
+
+        #[test]
+        fn detected_opportunities_are_valid(
+            book_a in any_book(Exchange::Okex),
+            book_b in any_book(Exchange::Deribit),
+            fee_bps in 0i64..50,
+            max_qty in 1i64..=100_000_000,
+            max_depth in 1usize..=60,
+        ) {
+            let fees = FeeConfig {
+                okex_taker_bps: Decimal::from(fee_bps),
+                deribit_taker_bps: Decimal::from(fee_bps),
+                ..FeeConfig::default()
+            };
+            for opp in ArbitrageDetector::new(max_depth).detect_with_max_position_and_fees(
+                &book_a,
+                &book_b,
+                &fees,
+                Decimal::new(max_qty, 3),
+            ) {
+                prop_assert!(opp.is_valid(), "{:?}", opp);
+            }
+        }
+
+        #[test]
+        fn at_most_one_direction_is_profitable(
+            book_a in uncrossed_book(Exchange::Okex),
+            book_b in uncrossed_book(Exchange::Deribit),
+        ) {
+            let fees = FeeConfig::default();
+            let detector = ArbitrageDetector::unlimited();
+            let sell_on_a = detector.check_direction(
+                &book_a,
+                &book_b,
+                &book_b.exchange,
+                &book_a.exchange,
+                &fees,
+                &mut Decimal::MAX.to_owned(),
+            );
+            let sell_on_b = detector.check_direction(
+                &book_b,
+                &book_a,
+                &book_a.exchange,
+                &book_b.exchange,
+                &fees,
+                &mut Decimal::MAX.to_owned(),
+            );
+            prop_assert!(sell_on_a.is_none() || sell_on_b.is_none());
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod book;
+mod detector;
+mod types;
+
+pub use book::{BookUpdateError, OrderBook, PriceImpactResult};
+pub use detector::{ArbitrageDetector, ArbitrageDetectorBuilder};
+pub use types::{
+    ArbitrageOpportunity, Exchange, ExecutionPlan, FeeConfig, OrderBookUpdate, OrderLevel,
+    OutputFormat, QuantityOverflow, RenderOptions, Side, SpotPrices, TradeInstruction, TradeLevel,
+    UnknownExchange,
+};
@@ -0,0 +1,1359 @@
+use crate::greeks::{self, IvSpread};
+use owo_colors::OwoColorize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tracing::info;
+
+/// One price level: the price and the quantity resting there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+/// Placeholder: a zero-quantity level at price zero. Set `price` and `quantity` before use.
+impl Default for OrderLevel {
+    fn default() -> Self {
+        Self {
+            price: Decimal::ZERO,
+            quantity: Decimal::ZERO,
+        }
+    }
+}
+
+/// A level's quantity could not be represented after arithmetic on it.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum QuantityOverflow {
+    #[error("quantity overflowed")]
+    Overflow,
+    #[error("cannot take {rhs} from a level holding {lhs}")]
+    Underflow { lhs: Decimal, rhs: Decimal },
+}
+
+impl OrderLevel {
+    /// Sums the quantities, keeping `self`'s price.
+    pub fn checked_add(self, rhs: OrderLevel) -> Result<Self, QuantityOverflow> {
+        let quantity = self
+            .quantity
+            .checked_add(rhs.quantity)
+            .ok_or(QuantityOverflow::Overflow)?;
+        Ok(Self { quantity, ..self })
+    }
+
+    /// Takes `rhs`'s quantity out of `self`, which must hold at least that much.
+    pub fn checked_sub(self, rhs: OrderLevel) -> Result<Self, QuantityOverflow> {
+        if self.quantity < rhs.quantity {
+            return Err(QuantityOverflow::Underflow {
+                lhs: self.quantity,
+                rhs: rhs.quantity,
+            });
+        }
+        Ok(Self {
+            quantity: self.quantity - rhs.quantity,
+            ..self
+        })
+    }
+
+    /// Scales the quantity, e.g. to convert between contract sizes.
+    pub fn checked_mul(self, factor: Decimal) -> Result<Self, QuantityOverflow> {
+        let quantity = self
+            .quantity
+            .checked_mul(factor)
+            .ok_or(QuantityOverflow::Overflow)?;
+        Ok(Self { quantity, ..self })
+    }
+}
+
+/// Merges two levels at the same price. Panics on a price mismatch or overflow; use
+/// `checked_add` to handle overflow.
+impl std::ops::Add for OrderLevel {
+    type Output = OrderLevel;
+
+    fn add(self, rhs: OrderLevel) -> OrderLevel {
+        assert_eq!(
+            self.price, rhs.price,
+            "cannot merge levels at different prices"
+        );
+        self.checked_add(rhs).unwrap()
+    }
+}
+
+/// Removes `rhs`'s quantity from a level at the same price. Panics on a price mismatch or if
+/// `rhs` holds more; use `checked_sub` to handle the latter.
+impl std::ops::Sub for OrderLevel {
+    type Output = OrderLevel;
+
+    fn sub(self, rhs: OrderLevel) -> OrderLevel {
+        assert_eq!(
+            self.price, rhs.price,
+            "cannot subtract levels at different prices"
+        );
+        self.checked_sub(rhs).unwrap()
+    }
+}
+
+/// Scales the quantity. Panics on overflow; use `checked_mul` to handle it.
+impl std::ops::Mul<Decimal> for OrderLevel {
+    type Output = OrderLevel;
+
+    fn mul(self, factor: Decimal) -> OrderLevel {
+        self.checked_mul(factor).unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum OrderBookUpdate {
+    Bids {
+        exchange: Exchange,
+        symbol: String,
+        levels: Vec<OrderLevel>,
+        /// When the websocket message carrying these levels was read.
+        received_at: Instant,
+    },
+    Asks {
+        exchange: Exchange,
+        symbol: String,
+        levels: Vec<OrderLevel>,
+        /// When the websocket message carrying these levels was read.
+        received_at: Instant,
+    },
+    /// Full book: replaces every level on both sides.
+    Snapshot {
+        exchange: Exchange,
+        symbol: String,
+        bids: Vec<OrderLevel>,
+        asks: Vec<OrderLevel>,
+        received_at: Instant,
+    },
+    /// Drop all levels, e.g. after a sequence gap made the local book inconsistent.
+    Clear { exchange: Exchange, symbol: String },
+    /// The venue acknowledged the subscription to `channel`; book data follows.
+    SubscriptionConfirmed {
+        exchange: Exchange,
+        symbol: String,
+        channel: String,
+    },
+    /// The connection carrying `symbol` failed; its subscription has to be confirmed again.
+    ConnectionError {
+        exchange: Exchange,
+        error: String,
+        symbol: String,
+    },
+}
+
+/// A supported venue.
+///
+/// More venues are expected, so the enum is `#[non_exhaustive]`: code outside this crate that
+/// matches on it needs a wildcard arm, e.g. `_ => "unknown"`, and keeps compiling when a venue
+/// is added. Inside the crate every match stays exhaustive, so adding a venue still points at
+/// each place that has to handle it.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Exchange {
+    /// Arbitrary; the default only exists so containers of exchange-keyed values can default.
+    #[default]
+    Okex,
+    Deribit,
+    Binance,
+    Bybit,
+    /// Several venues' books summed by `OrderBook::merged`; it has no feed of its own.
+    Aggregated,
+}
+
+impl OrderBookUpdate {
+    /// The venue the update came from.
+    pub fn exchange(&self) -> &Exchange {
+        match self {
+            OrderBookUpdate::Bids { exchange, .. }
+            | OrderBookUpdate::Asks { exchange, .. }
+            | OrderBookUpdate::Snapshot { exchange, .. }
+            | OrderBookUpdate::Clear { exchange, .. }
+            | OrderBookUpdate::SubscriptionConfirmed { exchange, .. }
+            | OrderBookUpdate::ConnectionError { exchange, .. } => exchange,
+        }
+    }
+
+    /// Whether the update carries price levels, as opposed to changing the book's state.
+    pub fn is_book_data(&self) -> bool {
+        matches!(
+            self,
+            OrderBookUpdate::Bids { .. }
+                | OrderBookUpdate::Asks { .. }
+                | OrderBookUpdate::Snapshot { .. }
+        )
+    }
+}
+
+impl Exchange {
+    /// Every supported venue.
+    pub fn all() -> &'static [Exchange] {
+        &[
+            Exchange::Okex,
+            Exchange::Deribit,
+            Exchange::Binance,
+            Exchange::Bybit,
+        ]
+    }
+}
+
+impl From<Exchange> for &'static str {
+    fn from(exchange: Exchange) -> Self {
+        match exchange {
+            Exchange::Okex => "Okex",
+            Exchange::Deribit => "Deribit",
+            Exchange::Binance => "Binance",
+            Exchange::Bybit => "Bybit",
+            Exchange::Aggregated => "Aggregated",
+        }
+    }
+}
+
+impl std::fmt::Display for Exchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.clone().into())
+    }
+}
+
+/// A string that names none of the supported exchanges.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("unknown exchange: {0}")]
+pub struct UnknownExchange(pub String);
+
+impl std::str::FromStr for Exchange {
+    type Err = UnknownExchange;
+
+    /// Case-insensitive; Okex also answers to its current name, OKX.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "okex" | "okx" => Ok(Exchange::Okex),
+            "deribit" => Ok(Exchange::Deribit),
+            "binance" => Ok(Exchange::Binance),
+            "bybit" => Ok(Exchange::Bybit),
+            _ => Err(UnknownExchange(s.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// One order to place when executing an opportunity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeInstruction {
+    pub exchange: Exchange,
+    pub side: Side,
+    pub price: Decimal,
+    /// In units of the underlying; divide by the venue's contract multiplier for contracts.
+    pub quantity: Decimal,
+    /// 1-indexed position in the execution order.
+    pub sequence: usize,
+}
+
+/// Ordered instructions for executing an opportunity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionPlan(pub Vec<TradeInstruction>);
+
+impl ExecutionPlan {
+    /// Checks that the plan is flat (buys match sells) and every price is positive.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(bad) = self.0.iter().find(|i| i.price <= Decimal::ZERO) {
+            return Err(format!(
+                "instruction {} has non-positive price {}",
+                bad.sequence, bad.price
+            ));
+        }
+        let volume = |side| -> Decimal {
+            self.0
+                .iter()
+                .filter(|i| i.side == side)
+                .map(|i| i.quantity)
+                .sum()
+        };
+        let (bought, sold) = (volume(Side::Buy), volume(Side::Sell));
+        if bought != sold {
+            return Err(format!(
+                "buy volume {bought} does not match sell volume {sold}"
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<TradeInstruction>> for ExecutionPlan {
+    fn from(instructions: Vec<TradeInstruction>) -> Self {
+        Self(instructions)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TradeLevel {
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+    /// Contracts bought, in the buy venue's contract size.
+    pub quantity: Decimal,
+    /// `quantity` in units of the underlying, which both legs trade and profits are priced in.
+    pub normalized_quantity: Decimal,
+    /// Spread profit before fees.
+    pub gross_profit: Decimal,
+    /// Round-trip taker fees paid on both legs.
+    pub fee_cost: Decimal,
+    /// Net profit after fees.
+    pub profit: Decimal,
+}
+
+/// Taker fees per exchange, expressed in basis points of the traded price.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeConfig {
+    pub okex_taker_bps: Decimal,
+    pub deribit_taker_bps: Decimal,
+    pub binance_taker_bps: Decimal,
+    pub bybit_taker_bps: Decimal,
+}
+
+impl FeeConfig {
+    pub fn taker_bps(&self, exchange: &Exchange) -> Decimal {
+        match exchange {
+            Exchange::Okex => self.okex_taker_bps,
+            Exchange::Deribit => self.deribit_taker_bps,
+            Exchange::Binance => self.binance_taker_bps,
+            Exchange::Bybit => self.bybit_taker_bps,
+            // Not a venue orders can be sent to.
+            Exchange::Aggregated => Decimal::ZERO,
+        }
+    }
+
+    pub(super) fn fee_cost(
+        &self,
+        buy_exchange: &Exchange,
+        sell_exchange: &Exchange,
+        buy_price: Decimal,
+        sell_price: Decimal,
+        quantity: Decimal,
+    ) -> Decimal {
+        let bps_scale = Decimal::from(10_000);
+        let buy_fee = buy_price * self.taker_bps(buy_exchange) / bps_scale;
+        let sell_fee = sell_price * self.taker_bps(sell_exchange) / bps_scale;
+        quantity * (buy_fee + sell_fee)
+    }
+}
+
+/// Ordered by `total_profit` first, so opportunities sort (and `max()` picks) by profit; ties
+/// fall back to the exchanges, symbol, volume and trades, keeping `==` consistent with `Hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageOpportunity {
+    pub buy_exchange: Exchange,
+    pub sell_exchange: Exchange,
+    pub symbol: String,
+    pub trades: Vec<TradeLevel>,
+    pub total_profit: Decimal,
+    pub total_volume: Decimal,
+}
+
+impl PartialEq for ArbitrageOpportunity {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for ArbitrageOpportunity {}
+
+impl PartialOrd for ArbitrageOpportunity {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArbitrageOpportunity {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.total_profit
+            .cmp(&other.total_profit)
+            .then_with(|| self.buy_exchange.cmp(&other.buy_exchange))
+            .then_with(|| self.sell_exchange.cmp(&other.sell_exchange))
+            .then_with(|| self.symbol.cmp(&other.symbol))
+            .then_with(|| self.total_volume.cmp(&other.total_volume))
+            .then_with(|| self.trades.cmp(&other.trades))
+    }
+}
+
+/// Placeholder: no trades, no profit, and both legs on the default exchange. Set the exchanges
+/// and `symbol` before use.
+impl Default for ArbitrageOpportunity {
+    fn default() -> Self {
+        Self {
+            buy_exchange: Exchange::default(),
+            sell_exchange: Exchange::default(),
+            symbol: String::new(),
+            trades: Vec::new(),
+            total_profit: Decimal::ZERO,
+            total_volume: Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// USD spot prices of the underlyings, used to show profits in USD.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpotPrices {
+    pub btc: Option<Decimal>,
+    pub eth: Option<Decimal>,
+}
+
+impl SpotPrices {
+    pub fn for_underlying(&self, underlying: &str) -> Option<Decimal> {
+        match underlying.to_uppercase().as_str() {
+            "BTC" => self.btc,
+            "ETH" => self.eth,
+            _ => None,
+        }
+    }
+}
+
+/// How `OutputFormat::Text` opportunities are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Highlight profits with ANSI colors.
+    pub color: bool,
+    /// One line per opportunity instead of one per execution step.
+    pub compact: bool,
+    pub spot_prices: SpotPrices,
+    /// Also show the legs' implied volatilities, discounting at this rate.
+    pub iv_risk_free_rate: Option<Decimal>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            color: true,
+            compact: false,
+            spot_prices: SpotPrices::default(),
+            iv_risk_free_rate: None,
+        }
+    }
+}
+
+/// `value` in green (bold for totals) when `color` is set, plain otherwise.
+fn paint_profit(value: Decimal, color: bool, bold: bool) -> String {
+    match (color, bold) {
+        (false, _) => value.to_string(),
+        (true, false) => value.green().to_string(),
+        (true, true) => value.green().bold().to_string(),
+    }
+}
+
+/// One CSV row per trade level; opportunity-level fields are repeated on every row.
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvTradeRow {
+    symbol: String,
+    buy_exchange: Exchange,
+    sell_exchange: Exchange,
+    step: usize,
+    buy_price: Decimal,
+    sell_price: Decimal,
+    quantity: Decimal,
+    normalized_quantity: Decimal,
+    gross_profit: Decimal,
+    fee_cost: Decimal,
+    profit: Decimal,
+}
+
+/// Hashes the execution plan's shape: totals, trade count and first level, so a changed plan
+/// with the same total profit still hashes differently.
+impl std::hash::Hash for ArbitrageOpportunity {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.buy_exchange.hash(state);
+        self.sell_exchange.hash(state);
+        self.symbol.hash(state);
+        self.total_profit.hash(state);
+        self.total_volume.hash(state);
+        self.trades.len().hash(state);
+        if let Some(first) = self.trades.first() {
+            first.buy_price.hash(state);
+            first.sell_price.hash(state);
+        }
+    }
+}
+
+impl std::fmt::Display for TradeLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} @ buy {} / sell {}: profit {} (gross {}, fees {})",
+            self.quantity,
+            self.buy_price,
+            self.sell_price,
+            self.profit,
+            self.gross_profit,
+            self.fee_cost
+        )
+    }
+}
+
+/// Multi-line summary: a header line, the totals, the spread of per-step profits, then one
+/// indented line per execution step.
+impl std::fmt::Display for ArbitrageOpportunity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Arbitrage on {}: buy on {}, sell on {}",
+            self.symbol, self.buy_exchange, self.sell_exchange
+        )?;
+        write!(
+            f,
+            "  Total profit: {}, volume: {}",
+            self.total_profit, self.total_volume
+        )?;
+        if let (Some(min), Some(max), Some(avg)) = (
+            self.min_trade_profit(),
+            self.max_trade_profit(),
+            self.avg_trade_profit(),
+        ) {
+            write!(f, "\n  Trade profit min/max/avg: {min}/{max}/{avg}")?;
+        }
+        for (i, trade) in self.trades.iter().enumerate() {
+            write!(f, "\n  Step {}: {trade}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl ArbitrageOpportunity {
+    /// Writes the plain-text summary (the `Display` form) followed by a newline.
+    pub fn show_arb_stats(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+        writeln!(out, "{self}")
+    }
+
+    /// `show_arb_stats` on stdout, bypassing the log formatting `render` uses.
+    pub fn print_arb_stats(&self) -> std::io::Result<()> {
+        self.show_arb_stats(&mut std::io::stdout().lock())
+    }
+
+    /// Logs the `Text` form. `output::write_opportunity` writes the JSON and CSV forms.
+    pub fn render(&self, options: &RenderOptions) {
+        if options.compact {
+            info!("{}", self.compact_summary(options, options.color));
+            return;
+        }
+        self.log_stats(options.color);
+        for line in self.text_annotations(options) {
+            info!("{line}");
+        }
+    }
+
+    /// The `Text` form `render` logs, as plain uncolored lines.
+    pub fn write_text(
+        &self,
+        out: &mut dyn std::io::Write,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        if options.compact {
+            return writeln!(out, "{}", self.compact_summary(options, false));
+        }
+        self.show_arb_stats(out)?;
+        for line in self.text_annotations(options) {
+            writeln!(out, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// `compact_line` followed by the USD profit and IV spread, when `options` asks for them.
+    fn compact_summary(&self, options: &RenderOptions, color: bool) -> String {
+        let mut line = self.compact_line(color);
+        if let Some(usd) = self.usd_profit(&options.spot_prices) {
+            line.push_str(&format!(" usd=~${usd}"));
+        }
+        if let Some(iv) = self.iv_spread(options) {
+            line.push_str(&format!(
+                " iv_bid={}% iv_ask={}%",
+                (iv.bid_iv * Decimal::ONE_HUNDRED).round_dp(2),
+                (iv.ask_iv * Decimal::ONE_HUNDRED).round_dp(2)
+            ));
+        }
+        line
+    }
+
+    /// The USD profit and IV spread lines shown after the full text form.
+    fn text_annotations(&self, options: &RenderOptions) -> Vec<String> {
+        let usd = self
+            .usd_profit(&options.spot_prices)
+            .map(|usd| format!("USD Profit: ~${usd}"));
+        let iv = self.iv_spread(options).map(|iv| iv.to_string());
+        usd.into_iter().chain(iv).collect()
+    }
+
+    /// Implied volatility spread to show, when `options` asks for it and it can be solved for.
+    fn iv_spread(&self, options: &RenderOptions) -> Option<IvSpread> {
+        let rate = options.iv_risk_free_rate?;
+        greeks::opportunity_iv_spread(self, &options.spot_prices, rate, chrono::Utc::now())
+    }
+
+    /// Total profit converted at the spot price of the symbol's underlying, to the cent.
+    pub fn usd_profit(&self, spot_prices: &SpotPrices) -> Option<Decimal> {
+        let underlying = self.symbol.split('-').next()?;
+        let spot = spot_prices.for_underlying(underlying)?;
+        Some((self.total_profit * spot).round_dp(2))
+    }
+
+    /// Largest `profit` of a single trade level.
+    pub fn max_trade_profit(&self) -> Option<Decimal> {
+        self.trades.iter().map(|trade| trade.profit).max()
+    }
+
+    /// Smallest `profit` of a single trade level.
+    pub fn min_trade_profit(&self) -> Option<Decimal> {
+        self.trades.iter().map(|trade| trade.profit).min()
+    }
+
+    pub fn avg_trade_profit(&self) -> Option<Decimal> {
+        if self.trades.is_empty() {
+            return None;
+        }
+        Some(self.total_profit / Decimal::from(self.trades.len()))
+    }
+
+    /// Cheap identity for de-duplicating repeated reports of the same opportunity.
+    pub fn fingerprint(&self) -> u64 {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether `trades` reads as one walk down both books: buy prices never fall, sell prices
+    /// never rise, every level crosses, and the levels' profits add up to `total_profit`.
+    pub fn is_valid(&self) -> bool {
+        let ordered = self.trades.windows(2).all(|pair| {
+            pair[1].buy_price >= pair[0].buy_price && pair[1].sell_price <= pair[0].sell_price
+        });
+        let crossed = self.trades.iter().all(|t| t.buy_price < t.sell_price);
+        let profit: Decimal = self.trades.iter().map(|t| t.profit).sum();
+        ordered && crossed && profit == self.total_profit
+    }
+
+    /// Each trade level as a buy immediately followed by its matching sell, so the position
+    /// stays flat between legs.
+    pub fn to_execution_plan(&self) -> Vec<TradeInstruction> {
+        self.trades
+            .iter()
+            .flat_map(|trade| {
+                [
+                    (&self.buy_exchange, Side::Buy, trade.buy_price),
+                    (&self.sell_exchange, Side::Sell, trade.sell_price),
+                ]
+                .map(|(exchange, side, price)| {
+                    (exchange.clone(), side, price, trade.normalized_quantity)
+                })
+            })
+            .enumerate()
+            .map(|(i, (exchange, side, price, quantity))| TradeInstruction {
+                exchange,
+                side,
+                price,
+                quantity,
+                sequence: i + 1,
+            })
+            .collect()
+    }
+
+    /// Total profit relative to `notional`, in basis points. `None` for a zero notional.
+    pub fn profit_bps(&self, notional: Decimal) -> Option<Decimal> {
+        Some(self.total_profit.checked_div(notional)? * Decimal::from(10_000))
+    }
+
+    /// Gross profit minus the taker fees of `fee_config`, charged on every step instead of
+    /// whatever fees detection used.
+    pub fn net_profit_after_fees(&self, fee_config: &FeeConfig) -> Decimal {
+        self.trades
+            .iter()
+            .map(|trade| {
+                trade.gross_profit
+                    - fee_config.fee_cost(
+                        &self.buy_exchange,
+                        &self.sell_exchange,
+                        trade.buy_price,
+                        trade.sell_price,
+                        trade.normalized_quantity,
+                    )
+            })
+            .sum()
+    }
+
+    /// Profit before any fees relative to `notional`, in basis points. `None` for a zero
+    /// notional.
+    pub fn gross_profit_bps(&self, notional: Decimal) -> Option<Decimal> {
+        let gross_profit: Decimal = self.trades.iter().map(|trade| trade.gross_profit).sum();
+        Some(gross_profit.checked_div(notional)? * Decimal::from(10_000))
+    }
+
+    /// `net_profit_after_fees` relative to `notional`, in basis points. `None` for a zero
+    /// notional.
+    pub fn net_profit_bps(&self, notional: Decimal, fee_config: &FeeConfig) -> Option<Decimal> {
+        Some(
+            self.net_profit_after_fees(fee_config)
+                .checked_div(notional)?
+                * Decimal::from(10_000),
+        )
+    }
+
+    /// Simple (non-compounded) annualized return on `notional` if the position is held to
+    /// expiry. `None` for a zero notional, or when the instrument expires today or has already
+    /// expired.
+    pub fn annualized_return(&self, notional: Decimal, days_to_expiry: i64) -> Option<Decimal> {
+        if days_to_expiry <= 0 {
+            return None;
+        }
+        Some(
+            self.total_profit.checked_div(notional)?
+                * (Decimal::from(365) / Decimal::from(days_to_expiry)),
+        )
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn to_csv(&self, with_header: bool) -> csv::Result<String> {
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(with_header)
+            .from_writer(Vec::new());
+        for (i, trade) in self.trades.iter().enumerate() {
+            writer.serialize(CsvTradeRow {
+                symbol: self.symbol.clone(),
+                buy_exchange: self.buy_exchange.clone(),
+                sell_exchange: self.sell_exchange.clone(),
+                step: i + 1,
+                buy_price: trade.buy_price,
+                sell_price: trade.sell_price,
+                quantity: trade.quantity,
+                normalized_quantity: trade.normalized_quantity,
+                gross_profit: trade.gross_profit,
+                fee_cost: trade.fee_cost,
+                profit: trade.profit,
+            })?;
+        }
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// `ARBIT <symbol> buy@<price> sell@<price> profit=<total>`, quoting the best level's prices.
+    pub fn compact_line(&self, color: bool) -> String {
+        let (buy_price, sell_price) = self
+            .trades
+            .first()
+            .map(|trade| (trade.buy_price, trade.sell_price))
+            .unwrap_or_default();
+        format!(
+            "ARBIT {} buy@{} sell@{} profit={}",
+            self.symbol,
+            buy_price,
+            sell_price,
+            paint_profit(self.total_profit, color, true)
+        )
+    }
+
+    fn log_stats(&self, color: bool) {
+        info!(
+            symbol = %self.symbol,
+            buy_exchange = %self.buy_exchange,
+            sell_exchange = %self.sell_exchange,
+            total_profit = %paint_profit(self.total_profit, color, true),
+            total_volume = %self.total_volume,
+            min_trade_profit = ?self.min_trade_profit(),
+            max_trade_profit = ?self.max_trade_profit(),
+            avg_trade_profit = ?self.avg_trade_profit(),
+            "arbitrage opportunity detected"
+        );
+
+        for (i, trade) in self.trades.iter().enumerate() {
+            info!(
+                symbol = %self.symbol,
+                step = i + 1,
+                buy_exchange = %self.buy_exchange,
+                sell_exchange = %self.sell_exchange,
+                quantity = %trade.quantity,
+                buy_price = %trade.buy_price,
+                sell_price = %trade.sell_price,
+                margin = %(trade.sell_price - trade.buy_price),
+                gross_profit = %trade.gross_profit,
+                fee_cost = %trade.fee_cost,
+                profit = %paint_profit(trade.profit, color, false),
+                "execution step"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        orderbook::{ArbitrageDetector, OrderBook},
+        test_fixtures::opportunity,
+    };
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn level(price: Decimal, quantity: Decimal) -> OrderLevel {
+        OrderLevel { price, quantity }
+    }
+
+    #[test]
+    fn test_defaults() {
+        let mut books = std::collections::HashMap::<Exchange, OrderBook>::new();
+        let book = books.entry(Exchange::Okex).or_default();
+        assert_eq!(book.exchange, Exchange::Okex);
+        assert!(book.symbol.is_empty());
+        assert!(book.best_bid().is_none() && book.best_ask().is_none());
+        assert!(book.instrument.is_none());
+
+        assert_eq!(OrderLevel::default(), level(Decimal::ZERO, Decimal::ZERO));
+
+        let opportunity = ArbitrageOpportunity::default();
+        assert!(opportunity.trades.is_empty());
+        assert_eq!(opportunity.total_profit, Decimal::ZERO);
+        assert_eq!(opportunity.max_trade_profit(), None);
+    }
+
+    #[test]
+    fn test_exchange_names_round_trip() {
+        for (name, exchange) in [
+            ("okex", Exchange::Okex),
+            ("OKEx", Exchange::Okex),
+            ("okx", Exchange::Okex),
+            ("OKX", Exchange::Okex),
+            ("deribit", Exchange::Deribit),
+            ("Deribit", Exchange::Deribit),
+            ("binance", Exchange::Binance),
+            ("BYBIT", Exchange::Bybit),
+        ] {
+            assert_eq!(name.parse::<Exchange>(), Ok(exchange));
+        }
+        for exchange in Exchange::all() {
+            assert_eq!(
+                exchange.to_string().parse::<Exchange>().as_ref(),
+                Ok(exchange)
+            );
+        }
+        assert_eq!(<&str>::from(Exchange::Bybit), "Bybit");
+
+        assert_eq!(
+            "kraken".parse::<Exchange>(),
+            Err(UnknownExchange("kraken".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_order_level_arithmetic() {
+        let merged = level(dec!(0.15), dec!(10)) + level(dec!(0.15), dec!(2.5));
+        assert_eq!(merged, level(dec!(0.15), dec!(12.5)));
+
+        let reduced = merged - level(dec!(0.15), dec!(12.5));
+        assert_eq!(reduced, level(dec!(0.15), dec!(0)));
+
+        assert_eq!(
+            level(dec!(0.15), dec!(4)) * dec!(0.1),
+            level(dec!(0.15), dec!(0.4))
+        );
+    }
+
+    #[test]
+    fn test_order_level_checked_arithmetic() {
+        assert_eq!(
+            level(dec!(0.15), Decimal::MAX).checked_add(level(dec!(0.15), dec!(1))),
+            Err(QuantityOverflow::Overflow)
+        );
+        assert_eq!(
+            level(dec!(0.15), dec!(1)).checked_sub(level(dec!(0.15), dec!(2))),
+            Err(QuantityOverflow::Underflow {
+                lhs: dec!(1),
+                rhs: dec!(2)
+            })
+        );
+        assert_eq!(
+            level(dec!(0.15), Decimal::MAX).checked_mul(dec!(2)),
+            Err(QuantityOverflow::Overflow)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "different prices")]
+    fn test_order_level_add_rejects_price_mismatch() {
+        let _ = level(dec!(0.15), dec!(1)) + level(dec!(0.16), dec!(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Underflow")]
+    fn test_order_level_sub_rejects_underflow() {
+        let _ = level(dec!(0.15), dec!(1)) - level(dec!(0.15), dec!(2));
+    }
+
+    /// Rebuilds an opportunity from CSV rows (with header) produced by `to_csv`. Totals are
+    /// recomputed from the trade rows.
+    fn from_csv(data: &str) -> csv::Result<Option<ArbitrageOpportunity>> {
+        let mut reader = csv::Reader::from_reader(data.as_bytes());
+        let mut opportunity: Option<ArbitrageOpportunity> = None;
+        for row in reader.deserialize() {
+            let row: CsvTradeRow = row?;
+            let opp = opportunity.get_or_insert_with(|| ArbitrageOpportunity {
+                buy_exchange: row.buy_exchange.clone(),
+                sell_exchange: row.sell_exchange.clone(),
+                symbol: row.symbol.clone(),
+                trades: Vec::new(),
+                total_profit: Decimal::ZERO,
+                total_volume: Decimal::ZERO,
+            });
+            opp.total_profit += row.profit;
+            opp.total_volume += row.quantity;
+            opp.trades.push(TradeLevel {
+                buy_price: row.buy_price,
+                sell_price: row.sell_price,
+                quantity: row.quantity,
+                normalized_quantity: row.normalized_quantity,
+                gross_profit: row.gross_profit,
+                fee_cost: row.fee_cost,
+                profit: row.profit,
+            });
+        }
+        Ok(opportunity)
+    }
+
+    fn sample_opportunity() -> ArbitrageOpportunity {
+        let okex_book = OrderBook::from_levels(
+            "BTC-USD-251031-140000-P".to_string(),
+            Exchange::Okex,
+            Vec::new(),
+            vec![
+                OrderLevel {
+                    price: dec!(0.140),
+                    quantity: dec!(30.0),
+                },
+                OrderLevel {
+                    price: dec!(0.145),
+                    quantity: dec!(50.0),
+                },
+            ],
+        );
+        let deribit_book = OrderBook::from_levels(
+            "BTC-31OCT25-140000-P".to_string(),
+            Exchange::Deribit,
+            vec![OrderLevel {
+                price: dec!(0.150),
+                quantity: dec!(60.0),
+            }],
+            Vec::new(),
+        );
+        let fees = FeeConfig {
+            okex_taker_bps: dec!(3),
+            deribit_taker_bps: dec!(3),
+            binance_taker_bps: dec!(3),
+            bybit_taker_bps: dec!(3),
+        };
+        ArbitrageDetector::unlimited()
+            .detect_arbitrage_with_fees(&okex_book, &deribit_book, &fees)
+            .remove(0)
+    }
+
+    #[test]
+    fn test_execution_plan_interleaves_legs() {
+        let opportunity = sample_opportunity();
+        let plan = opportunity.to_execution_plan();
+        let steps: Vec<_> = plan
+            .iter()
+            .map(|i| (i.sequence, i.exchange.clone(), i.side, i.price, i.quantity))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (1, Exchange::Okex, Side::Buy, dec!(0.140), dec!(3)),
+                (2, Exchange::Deribit, Side::Sell, dec!(0.150), dec!(3)),
+                (3, Exchange::Okex, Side::Buy, dec!(0.145), dec!(5)),
+                (4, Exchange::Deribit, Side::Sell, dec!(0.150), dec!(5)),
+            ]
+        );
+        // Both legs trade BTC; the Okex buys are 30 and 50 contracts of 0.1 BTC.
+        assert_eq!(opportunity.trades[0].quantity, dec!(30));
+        assert_eq!(opportunity.trades[1].quantity, dec!(50));
+        assert_eq!(ExecutionPlan::from(plan).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let opportunity = sample_opportunity();
+        assert!(opportunity.is_valid());
+
+        let mut reordered = opportunity.clone();
+        reordered.trades.reverse();
+        assert!(!reordered.is_valid());
+
+        let mut uncrossed = opportunity.clone();
+        uncrossed.trades[1].buy_price = uncrossed.trades[1].sell_price;
+        assert!(!uncrossed.is_valid());
+
+        let mut misreported = opportunity;
+        misreported.total_profit += dec!(0.001);
+        assert!(!misreported.is_valid());
+    }
+
+    #[test]
+    fn test_execution_plan_validation() {
+        let mut plan = ExecutionPlan(sample_opportunity().to_execution_plan());
+        plan.0[3].quantity = dec!(2);
+        assert_eq!(
+            plan.validate(),
+            Err("buy volume 8.00 does not match sell volume 5.00".to_string())
+        );
+
+        plan.0[2].price = Decimal::ZERO;
+        assert_eq!(
+            plan.validate(),
+            Err("instruction 3 has non-positive price 0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_execution_plan() {
+        let opportunity = sample_opportunity();
+        assert_eq!(opportunity.fingerprint(), opportunity.clone().fingerprint());
+
+        // Same symbol and total profit, but a different execution plan
+        let mut restructured = opportunity.clone();
+        restructured.trades.push(restructured.trades[0].clone());
+        assert_ne!(opportunity.fingerprint(), restructured.fingerprint());
+
+        let mut repriced = opportunity.clone();
+        repriced.trades[0].buy_price += dec!(0.001);
+        assert_ne!(opportunity.fingerprint(), repriced.fingerprint());
+
+        // Deeper levels are not part of the fingerprint
+        let mut deeper = opportunity.clone();
+        deeper.trades[1].fee_cost += dec!(0.001);
+        assert_eq!(opportunity.fingerprint(), deeper.fingerprint());
+    }
+
+    #[test]
+    fn test_trade_profit_distribution() {
+        let trade = |profit| TradeLevel {
+            buy_price: dec!(0.140),
+            sell_price: dec!(0.150),
+            quantity: dec!(100),
+            normalized_quantity: dec!(100),
+            gross_profit: profit,
+            fee_cost: Decimal::ZERO,
+            profit,
+        };
+        let mut opportunity = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "BTC-31OCT25-140000-P".to_string(),
+            trades: vec![trade(dec!(0.5)), trade(dec!(1.5))],
+            total_profit: dec!(2.0),
+            total_volume: dec!(200),
+        };
+        assert_eq!(opportunity.min_trade_profit(), Some(dec!(0.5)));
+        assert_eq!(opportunity.max_trade_profit(), Some(dec!(1.5)));
+        assert_eq!(opportunity.avg_trade_profit(), Some(dec!(1.0)));
+
+        opportunity.trades.clear();
+        assert_eq!(opportunity.min_trade_profit(), None);
+        assert_eq!(opportunity.max_trade_profit(), None);
+        assert_eq!(opportunity.avg_trade_profit(), None);
+    }
+
+    #[test]
+    fn test_opportunities_order_by_profit() {
+        let mut opportunities = [
+            opportunity("MID", dec!(0.2)),
+            opportunity("HIGH", dec!(0.5)),
+            opportunity("LOW", dec!(0.1)),
+        ];
+
+        assert_eq!(opportunities.iter().max().unwrap().symbol, "HIGH");
+        opportunities.sort();
+        let symbols: Vec<_> = opportunities.iter().map(|o| o.symbol.as_str()).collect();
+        assert_eq!(symbols, ["LOW", "MID", "HIGH"]);
+        assert_eq!(opportunity("A", dec!(0.1)), opportunity("A", dec!(0.10)));
+        assert_ne!(opportunity("A", dec!(0.1)), opportunity("B", dec!(0.1)));
+        assert!(opportunity("A", dec!(0.1)) < opportunity("B", dec!(0.1)));
+
+        let distinct: std::collections::HashSet<_> = [
+            opportunity("A", dec!(0.1)),
+            opportunity("B", dec!(0.1)),
+            opportunity("A", dec!(0.10)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(distinct.len(), 2);
+    }
+
+    #[test]
+    fn test_net_profit_after_fees() {
+        // Detected with 3 bps taker fees on both venues.
+        let opportunity = sample_opportunity();
+        let detection_fees = FeeConfig {
+            okex_taker_bps: dec!(3),
+            deribit_taker_bps: dec!(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            opportunity.net_profit_after_fees(&detection_fees),
+            opportunity.total_profit
+        );
+
+        let no_fees = FeeConfig::default();
+        let gross: Decimal = opportunity.trades.iter().map(|t| t.gross_profit).sum();
+        assert_eq!(opportunity.net_profit_after_fees(&no_fees), gross);
+        assert_eq!(
+            opportunity.net_profit_bps(dec!(10), &no_fees),
+            opportunity.gross_profit_bps(dec!(10))
+        );
+        assert!(
+            opportunity
+                .net_profit_bps(dec!(10), &detection_fees)
+                .unwrap()
+                < opportunity.gross_profit_bps(dec!(10)).unwrap()
+        );
+        assert_eq!(opportunity.gross_profit_bps(Decimal::ZERO), None);
+        assert_eq!(opportunity.net_profit_bps(Decimal::ZERO, &no_fees), None);
+
+        let zero_fee_opportunity = ArbitrageDetector::unlimited()
+            .detect_arbitrage(
+                &OrderBook::from_levels(
+                    "ETH-USD-240427-3000-C".to_string(),
+                    Exchange::Okex,
+                    Vec::new(),
+                    vec![level(dec!(0.140), dec!(10))],
+                ),
+                &OrderBook::from_levels(
+                    "ETH-27APR24-3000-C".to_string(),
+                    Exchange::Deribit,
+                    vec![level(dec!(0.150), dec!(10))],
+                    Vec::new(),
+                ),
+            )
+            .remove(0);
+        assert_eq!(
+            zero_fee_opportunity.net_profit_after_fees(&no_fees),
+            zero_fee_opportunity.total_profit
+        );
+    }
+
+    #[test]
+    fn test_show_arb_stats() {
+        let opportunity = sample_opportunity();
+        let mut out = Vec::new();
+        opportunity.show_arb_stats(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+
+        assert!(text.contains("BTC-31OCT25-140000-P"));
+        assert!(text.contains(&format!("Total profit: {}", opportunity.total_profit)));
+        assert_eq!(
+            text.lines().filter(|line| line.contains("Step ")).count(),
+            opportunity.trades.len()
+        );
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_display() {
+        let opportunity = sample_opportunity();
+        let text = format!("{opportunity}");
+        assert!(text.contains("BTC-31OCT25-140000-P"));
+        assert!(text.contains("buy on Okex"));
+        assert!(text.contains("sell on Deribit"));
+        assert!(text.contains(&opportunity.total_profit.to_string()));
+        // Header, totals and the per-step profit spread, then the steps.
+        assert_eq!(text.lines().count(), 3 + opportunity.trades.len());
+        assert!(text.contains("Trade profit min/max/avg: "));
+
+        let step = format!("{}", opportunity.trades[0]);
+        assert!(step.contains("buy 0.140"));
+        assert!(step.contains("sell 0.150"));
+        assert!(text.contains(&step));
+    }
+
+    #[test]
+    fn test_profit_bps_and_annualized_return() {
+        let opportunity = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "RETURN".to_string(),
+            trades: Vec::new(),
+            total_profit: dec!(0.01),
+            total_volume: dec!(1),
+        };
+
+        assert_eq!(opportunity.profit_bps(dec!(1)), Some(dec!(100)));
+        assert_eq!(opportunity.profit_bps(Decimal::ZERO), None);
+
+        // 1% over 30 days annualizes to 0.01 * 365 / 30 = 0.121666...
+        let annualized = opportunity.annualized_return(dec!(1), 30).unwrap();
+        assert_eq!(annualized.round_dp(6), dec!(0.121667));
+
+        assert!(opportunity.annualized_return(dec!(1), 0).is_none());
+        assert!(opportunity.annualized_return(dec!(1), -3).is_none());
+        assert!(opportunity.annualized_return(Decimal::ZERO, 30).is_none());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let opportunity = sample_opportunity();
+        let json = opportunity.to_json().unwrap();
+        assert!(!json.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<ArbitrageOpportunity>(&json).unwrap(),
+            opportunity,
+        );
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let opportunity = sample_opportunity();
+        let with_header = opportunity.to_csv(true).unwrap();
+        assert_eq!(with_header.lines().count(), opportunity.trades.len() + 1);
+        assert!(with_header.starts_with("symbol,buy_exchange,sell_exchange,step,"));
+        assert_eq!(from_csv(&with_header).unwrap().unwrap(), opportunity);
+
+        let without_header = opportunity.to_csv(false).unwrap();
+        assert_eq!(without_header.lines().count(), opportunity.trades.len());
+    }
+
+    #[test]
+    fn test_write_text() {
+        // Text is one-way: unlike JSON and CSV there is nothing to parse back, so check what
+        // it shows.
+        let opportunity = sample_opportunity();
+        let mut out = Vec::new();
+        opportunity
+            .write_text(&mut out, &RenderOptions::default())
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains(&opportunity.symbol));
+        assert!(text.contains(&format!(
+            "Total profit: {}, volume: {}",
+            opportunity.total_profit, opportunity.total_volume
+        )));
+        assert_eq!(
+            text.lines().filter(|line| line.contains("Step ")).count(),
+            opportunity.trades.len()
+        );
+
+        let compact = RenderOptions {
+            compact: true,
+            ..RenderOptions::default()
+        };
+        let mut out = Vec::new();
+        opportunity.write_text(&mut out, &compact).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert_eq!(line.lines().count(), 1);
+        assert!(line.contains(&opportunity.symbol));
+        assert!(line.contains(&format!("profit={}", opportunity.total_profit)));
+    }
+
+    #[test]
+    fn test_serde_round_trips() {
+        assert_eq!(serde_json::to_string(&Exchange::Okex).unwrap(), "\"okex\"");
+        assert_eq!(
+            serde_json::to_string(&Exchange::Deribit).unwrap(),
+            "\"deribit\""
+        );
+        for exchange in [Exchange::Okex, Exchange::Deribit, Exchange::Binance] {
+            let json = serde_json::to_string(&exchange).unwrap();
+            assert_eq!(serde_json::from_str::<Exchange>(&json).unwrap(), exchange);
+        }
+
+        let level = OrderLevel {
+            price: dec!(0.1425),
+            quantity: dec!(12.5),
+        };
+        let json = serde_json::to_string(&level).unwrap();
+        assert_eq!(serde_json::from_str::<OrderLevel>(&json).unwrap(), level);
+
+        let trade = |buy_price, quantity| TradeLevel {
+            buy_price,
+            sell_price: dec!(0.150),
+            quantity,
+            normalized_quantity: quantity,
+            gross_profit: (dec!(0.150) - buy_price) * quantity,
+            fee_cost: Decimal::ZERO,
+            profit: (dec!(0.150) - buy_price) * quantity,
+        };
+        let trades = vec![
+            trade(dec!(0.140), dec!(10)),
+            trade(dec!(0.142), dec!(5)),
+            trade(dec!(0.145), dec!(2)),
+        ];
+        let json = serde_json::to_string(&trades[0]).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TradeLevel>(&json).unwrap(),
+            trades[0]
+        );
+
+        let opportunity = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "BTC-USD-251031-140000-P".to_string(),
+            total_profit: trades.iter().map(|t| t.profit).sum(),
+            total_volume: trades.iter().map(|t| t.quantity).sum(),
+            trades,
+        };
+        let json = serde_json::to_string(&opportunity).unwrap();
+        assert!(json.contains("\"buy_exchange\":\"okex\""));
+        assert_eq!(
+            serde_json::from_str::<ArbitrageOpportunity>(&json).unwrap(),
+            opportunity,
+        );
+    }
+
+    #[test]
+    fn test_compact_line() {
+        let opportunity = sample_opportunity();
+        assert_eq!(
+            opportunity.compact_line(false),
+            format!(
+                "ARBIT BTC-31OCT25-140000-P buy@0.140 sell@0.150 profit={}",
+                opportunity.total_profit
+            )
+        );
+
+        let colored = opportunity.compact_line(true);
+        assert!(colored.contains("\u{1b}["));
+        assert!(colored.contains(&opportunity.total_profit.to_string()));
+    }
+
+    #[test]
+    fn test_usd_profit() {
+        let opportunity = ArbitrageOpportunity {
+            buy_exchange: Exchange::Okex,
+            sell_exchange: Exchange::Deribit,
+            symbol: "BTC-USD-251031-140000-P".to_string(),
+            trades: Vec::new(),
+            total_profit: dec!(0.0123),
+            total_volume: dec!(1),
+        };
+        let spot_prices = SpotPrices {
+            btc: Some(dec!(65000)),
+            eth: None,
+        };
+
+        assert_eq!(opportunity.usd_profit(&spot_prices), Some(dec!(799.50)));
+        assert_eq!(opportunity.usd_profit(&SpotPrices::default()), None);
+
+        let eth = ArbitrageOpportunity {
+            symbol: "ETH-27DEC24-3500-C".to_string(),
+            ..opportunity
+        };
+        assert_eq!(eth.usd_profit(&spot_prices), None);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::orderbook::{ArbitrageOpportunity, OutputFormat, RenderOptions};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Where reported opportunities go.
+pub enum Output {
+    /// Text is logged through `ArbitrageOpportunity::render`; JSON and CSV go to stdout.
+    Stdout { csv_header_written: bool },
+    /// Plain, uncolored renders appended to a file.
+    File {
+        writer: BufWriter<File>,
+        /// A CSV header is only written to a file that starts out empty.
+        csv_header_written: bool,
+    },
+}
+
+impl Output {
+    pub fn stdout() -> Self {
+        Self::Stdout {
+            csv_header_written: false,
+        }
+    }
+
+    /// Opens `path` for appending, creating it if needed.
+    pub fn append_to(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let csv_header_written = file.metadata()?.len() > 0;
+        Ok(Self::File {
+            writer: BufWriter::new(file),
+            csv_header_written,
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout { .. } => io::stdout().flush(),
+            Self::File { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+pub fn write_opportunity(
+    out: &mut Output,
+    opp: &ArbitrageOpportunity,
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    let mut stdout;
+    let (writer, csv_header_written): (&mut dyn Write, _) = match out {
+        Output::Stdout { .. } if format == OutputFormat::Text => {
+            opp.render(options);
+            return Ok(());
+        }
+        Output::Stdout { csv_header_written } => {
+            stdout = io::stdout().lock();
+            (&mut stdout, csv_header_written)
+        }
+        Output::File {
+            writer,
+            csv_header_written,
+        } => (writer, csv_header_written),
+    };
+
+    match format {
+        OutputFormat::Text => opp.write_text(writer, options),
+        OutputFormat::Json => writeln!(writer, "{}", opp.to_json()?),
+        OutputFormat::Csv => {
+            let rows = opp.to_csv(!*csv_header_written).map_err(io::Error::other)?;
+            *csv_header_written = true;
+            writer.write_all(rows.as_bytes())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{orderbook::SpotPrices, test_fixtures::opportunity_with_trade};
+    use rust_decimal_macros::dec;
+
+    const SYMBOL: &str = "BTC-31OCT25-140000-P";
+
+    #[test]
+    fn test_appends_csv_with_single_header() {
+        let path = std::env::temp_dir().join(format!("arb-output-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let options = RenderOptions::default();
+
+        for _ in 0..2 {
+            let mut out = Output::append_to(&path).unwrap();
+            write_opportunity(
+                &mut out,
+                &opportunity_with_trade(SYMBOL),
+                OutputFormat::Csv,
+                &options,
+            )
+            .unwrap();
+            out.flush().unwrap();
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("symbol,"));
+        assert!(lines[1].starts_with("BTC-31OCT25-140000-P,"));
+        assert_eq!(lines[1], lines[2]);
+    }
+
+    #[test]
+    fn test_text_output_is_uncolored() {
+        let path = std::env::temp_dir().join(format!("arb-output-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut out = Output::append_to(&path).unwrap();
+        write_opportunity(
+            &mut out,
+            &opportunity_with_trade(SYMBOL),
+            OutputFormat::Text,
+            &RenderOptions::default(),
+        )
+        .unwrap();
+        out.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(contents.contains("buy on Okex"));
+        assert!(!contents.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_text_output_shows_trade_profit_spread() {
+        let path =
+            std::env::temp_dir().join(format!("arb-output-spread-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut out = Output::append_to(&path).unwrap();
+        write_opportunity(
+            &mut out,
+            &opportunity_with_trade(SYMBOL),
+            OutputFormat::Text,
+            &RenderOptions::default(),
+        )
+        .unwrap();
+        out.flush().unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(
+            contents.contains("Trade profit min/max/avg: 0.1/0.1/0.1"),
+            "{contents}"
+        );
+    }
+
+    #[test]
+    fn test_text_output_shows_usd_profit() {
+        let options = RenderOptions {
+            spot_prices: SpotPrices {
+                btc: Some(dec!(60000)),
+                eth: None,
+            },
+            ..RenderOptions::default()
+        };
+        for compact in [false, true] {
+            let path = std::env::temp_dir().join(format!(
+                "arb-output-usd-{compact}-{}.txt",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_file(&path);
+
+            let mut out = Output::append_to(&path).unwrap();
+            let options = RenderOptions { compact, ..options };
+            write_opportunity(
+                &mut out,
+                &opportunity_with_trade(SYMBOL),
+                OutputFormat::Text,
+                &options,
+            )
+            .unwrap();
+            out.flush().unwrap();
+
+            let contents = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert!(contents.contains("~$6000"), "{contents}");
+        }
+    }
+}
@@ -1,20 +1,179 @@
-use chrono::{NaiveDate, ParseError};
-use std::collections::HashMap;
+use crate::orderbook::Exchange;
+use chrono::{Datelike, Days, NaiveDate, ParseError, Weekday};
+use regex::Regex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, sync::LazyLock};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+static OKEX_SYMBOL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z0-9_]+-[A-Z]+-\d{6}-\d+(\.\d+)?-[CP]$").unwrap());
+static DERIBIT_SYMBOL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z0-9_]+-\d{2}[A-Z]{3}\d{2}-\d+(\.\d+)?-[CP]$").unwrap());
+static BINANCE_SYMBOL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z0-9_]+-\d{6}-\d+(\.\d+)?-[CP]$").unwrap());
+
+/// Underlyings with listed options on the supported venues, including Deribit's USDC-settled
+/// linear ones.
+pub const SUPPORTED_UNDERLYINGS: &[&str] = &[
+    "BTC", "ETH", "SOL", "XRP", "BTC_USDC", "ETH_USDC", "SOL_USDC", "XRP_USDC",
+];
+
+/// Month abbreviations used in Deribit's `DDMMMYY` expiries; small enough to scan linearly.
+pub const MONTH_ABBREVS: [(&str, u32); 12] = [
+    ("JAN", 1),
+    ("FEB", 2),
+    ("MAR", 3),
+    ("APR", 4),
+    ("MAY", 5),
+    ("JUN", 6),
+    ("JUL", 7),
+    ("AUG", 8),
+    ("SEP", 9),
+    ("OCT", 10),
+    ("NOV", 11),
+    ("DEC", 12),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParsedInstrument {
     pub underlying: String,
     pub expiry_date: NaiveDate,
-    pub strike: u32,
+    /// Some assets list fractional strikes (e.g. `3500.5`).
+    pub strike: Decimal,
     pub option_type: OptionType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl ParsedInstrument {
+    /// Underlying units per Okex contract: 0.1 BTC for BTC options, one unit otherwise.
+    pub fn okex_contract_multiplier(&self) -> Decimal {
+        match self.underlying.as_str() {
+            "BTC" => Decimal::new(1, 1),
+            _ => Decimal::ONE,
+        }
+    }
+
+    /// Underlying units per Deribit contract: always one.
+    pub fn deribit_contract_multiplier(&self) -> Decimal {
+        Decimal::ONE
+    }
+
+    /// Underlying units per contract on `exchange`; Binance and Bybit contracts are one unit,
+    /// and so are an aggregated book's.
+    pub fn contract_multiplier(&self, exchange: &Exchange) -> Decimal {
+        match exchange {
+            Exchange::Okex => self.okex_contract_multiplier(),
+            Exchange::Deribit => self.deribit_contract_multiplier(),
+            Exchange::Binance | Exchange::Bybit | Exchange::Aggregated => Decimal::ONE,
+        }
+    }
+
+    /// Whole days until expiry, negative once the expiry date has passed.
+    pub fn days_to_expiry(&self, now: NaiveDate) -> i64 {
+        (self.expiry_date - now).num_days()
+    }
+
+    /// An instrument is still live on its expiry date.
+    pub fn is_expired(&self, now: NaiveDate) -> bool {
+        self.days_to_expiry(now) < 0
+    }
+
+    /// Okex form, e.g. `BTC-USD-241227-56000-C`.
+    pub fn to_okex_symbol(&self) -> String {
+        format!(
+            "{}-USD-{}-{}-{}",
+            self.underlying,
+            self.expiry_date.format("%y%m%d"),
+            self.strike.normalize(),
+            self.option_type.code()
+        )
+    }
+
+    /// Deribit form, e.g. `BTC-27DEC24-56000-C`.
+    pub fn to_deribit_symbol(&self) -> String {
+        let (month, _) = MONTH_ABBREVS[self.expiry_date.month0() as usize];
+        format!(
+            "{}-{:02}{month}{:02}-{}-{}",
+            self.underlying,
+            self.expiry_date.day(),
+            self.expiry_date.year() % 100,
+            self.strike.normalize(),
+            self.option_type.code()
+        )
+    }
+
+    /// Listing cycle of the expiry, judged from today's date.
+    pub fn classify_expiry(&self) -> ExpiryClass {
+        self.classify_expiry_at(chrono::Utc::now().date_naive())
+    }
+
+    /// Listing cycle of the expiry as seen on `today`. Month-end Fridays are monthly (quarterly
+    /// in March, June, September and December); other Fridays are weekly within a week of
+    /// `today` and bi-weekly within two.
+    pub fn classify_expiry_at(&self, today: NaiveDate) -> ExpiryClass {
+        let expiry = self.expiry_date;
+        if expiry.weekday() != Weekday::Fri {
+            return ExpiryClass::Other;
+        }
+        let last_of_month = (expiry + Days::new(7)).month() != expiry.month();
+        match (last_of_month, self.days_to_expiry(today)) {
+            (true, _) if expiry.month().is_multiple_of(3) => ExpiryClass::Quarterly,
+            (true, _) => ExpiryClass::Monthly,
+            (false, ..=7) => ExpiryClass::Weekly,
+            (false, 8..=14) => ExpiryClass::BiWeekly,
+            (false, _) => ExpiryClass::Other,
+        }
+    }
+}
+
+/// Okex listing cycle an expiry belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExpiryClass {
+    Weekly,
+    BiWeekly,
+    Monthly,
+    Quarterly,
+    Other,
+}
+
+/// Canonical venue-independent form, e.g. `BTC-20241227-56000-C`.
+impl std::fmt::Display for ParsedInstrument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}-{}",
+            self.underlying,
+            self.expiry_date.format("%Y%m%d"),
+            self.strike.normalize(),
+            self.option_type.code()
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OptionType {
     Call,
     Put,
 }
 
+impl OptionType {
+    /// Single-letter form used in symbols.
+    fn code(&self) -> char {
+        match self {
+            OptionType::Call => 'C',
+            OptionType::Put => 'P',
+        }
+    }
+}
+
+impl std::fmt::Display for OptionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionType::Call => write!(f, "Call"),
+            OptionType::Put => write!(f, "Put"),
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum InstrumentParseError {
     #[error("Invalid format: {0}")]
@@ -27,6 +186,199 @@ pub enum InstrumentParseError {
     InvalidOptionType(String),
     #[error("Insufficient components in symbol")]
     InsufficientComponents,
+    #[error("Cannot infer exchange for symbol: {0}")]
+    UnknownExchange(String),
+    #[error("Unsupported underlying: {0}")]
+    UnsupportedUnderlying(String),
+    #[error("Need symbols on at least two venues, got {0}")]
+    TooFewVenues(usize),
+    #[error("Error parsing '{symbol}': {source}")]
+    WithContext {
+        source: Box<InstrumentParseError>,
+        symbol: String,
+    },
+}
+
+impl InstrumentParseError {
+    /// Names the symbol that failed to parse. Errors that already carry context are unchanged.
+    pub fn with_context(self, symbol: &str) -> Self {
+        match self {
+            Self::WithContext { .. } => self,
+            source => Self::WithContext {
+                source: Box::new(source),
+                symbol: symbol.to_string(),
+            },
+        }
+    }
+}
+
+/// The same instrument as listed on each venue we monitor; any two or more venues may be set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SymbolPair {
+    pub okex: Option<String>,
+    pub deribit: Option<String>,
+    pub binance: Option<String>,
+    pub bybit: Option<String>,
+}
+
+impl SymbolPair {
+    /// The symbol slot for `exchange`, or `None` for `Exchange::Aggregated`, which has no feed.
+    pub fn symbol_mut(&mut self, exchange: &Exchange) -> Option<&mut Option<String>> {
+        match exchange {
+            Exchange::Okex => Some(&mut self.okex),
+            Exchange::Deribit => Some(&mut self.deribit),
+            Exchange::Binance => Some(&mut self.binance),
+            Exchange::Bybit => Some(&mut self.bybit),
+            Exchange::Aggregated => None,
+        }
+    }
+
+    pub fn venues(&self) -> Vec<(Exchange, String)> {
+        [
+            (Exchange::Okex, &self.okex),
+            (Exchange::Deribit, &self.deribit),
+            (Exchange::Binance, &self.binance),
+            (Exchange::Bybit, &self.bybit),
+        ]
+        .into_iter()
+        .filter_map(|(exchange, symbol)| Some((exchange, symbol.clone()?)))
+        .collect()
+    }
+
+    /// True when every venue's symbol parses to the same instrument.
+    pub fn is_same_instrument(&self) -> Result<bool, InstrumentParseError> {
+        Ok(self.mismatch()?.is_none())
+    }
+
+    /// How the first pair of neighbouring venues whose instruments differ disagrees, or `None`
+    /// when every venue lists the same instrument.
+    pub fn mismatch(&self) -> Result<Option<InstrumentMismatch>, InstrumentParseError> {
+        let venues = self.venues();
+        if venues.len() < 2 {
+            return Err(InstrumentParseError::TooFewVenues(venues.len()));
+        }
+        for pair in venues.windows(2) {
+            let ((a_exchange, a_symbol), (b_exchange, b_symbol)) = (&pair[0], &pair[1]);
+            let mismatch = InstrumentValidator::compare_instruments_for_exchanges(
+                a_exchange, a_symbol, b_exchange, b_symbol,
+            )?;
+            if !mismatch.is_exact_match() {
+                return Ok(Some(mismatch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Which fields of two parsed instruments differ, with both instruments for reporting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstrumentMismatch {
+    pub underlying_mismatch: bool,
+    pub expiry_mismatch: bool,
+    pub strike_mismatch: bool,
+    pub type_mismatch: bool,
+    pub a: ParsedInstrument,
+    pub b: ParsedInstrument,
+}
+
+impl InstrumentMismatch {
+    pub fn new(a: ParsedInstrument, b: ParsedInstrument) -> Self {
+        Self {
+            underlying_mismatch: a.underlying != b.underlying,
+            expiry_mismatch: a.expiry_date != b.expiry_date,
+            strike_mismatch: a.strike != b.strike,
+            type_mismatch: a.option_type != b.option_type,
+            a,
+            b,
+        }
+    }
+
+    pub fn is_exact_match(&self) -> bool {
+        !(self.underlying_mismatch
+            || self.expiry_mismatch
+            || self.strike_mismatch
+            || self.type_mismatch)
+    }
+}
+
+/// Each differing field with both values, e.g. `strikes differ: 56000 vs 60000`.
+impl std::fmt::Display for InstrumentMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (a, b) = (&self.a, &self.b);
+        let reasons = [
+            (
+                self.underlying_mismatch,
+                format!("underlyings differ: {} vs {}", a.underlying, b.underlying),
+            ),
+            (
+                self.expiry_mismatch,
+                format!("expiries differ: {} vs {}", a.expiry_date, b.expiry_date),
+            ),
+            (
+                self.strike_mismatch,
+                format!("strikes differ: {} vs {}", a.strike, b.strike),
+            ),
+            (
+                self.type_mismatch,
+                format!(
+                    "option types differ: {} vs {}",
+                    a.option_type, b.option_type
+                ),
+            ),
+        ];
+        let reasons: Vec<String> = reasons
+            .into_iter()
+            .filter_map(|(differs, reason)| differs.then_some(reason))
+            .collect();
+        if reasons.is_empty() {
+            f.write_str("instruments match")
+        } else {
+            f.write_str(&reasons.join(", "))
+        }
+    }
+}
+
+impl std::fmt::Display for SymbolPair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (exchange, symbol)) in self.venues().iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{exchange}:{symbol}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a symbols file with one `okex_symbol,deribit_symbol[,binance_symbol[,bybit_symbol]]`
+/// entry per line; leave the Binance field empty to list only Bybit. Blank lines and lines
+/// starting with `#` are ignored.
+pub fn parse_symbols_file(contents: &str) -> Result<Vec<SymbolPair>, InstrumentParseError> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let optional = |index: usize| {
+                fields
+                    .get(index)
+                    .filter(|field| !field.is_empty())
+                    .map(|field| field.to_string())
+            };
+            match fields.as_slice() {
+                [okex, deribit, ..] if fields.len() <= 4 => Ok(SymbolPair {
+                    okex: Some(okex.to_string()),
+                    deribit: Some(deribit.to_string()),
+                    binance: optional(2),
+                    bybit: optional(3),
+                }),
+                _ => Err(InstrumentParseError::InvalidFormat(format!(
+                    "Expected okex_symbol,deribit_symbol[,binance_symbol[,bybit_symbol]], got: {line}",
+                ))),
+            }
+        })
+        .collect()
 }
 
 pub struct InstrumentValidator;
@@ -36,10 +388,140 @@ impl InstrumentValidator {
         okex_symbol: &str,
         deribit_symbol: &str,
     ) -> Result<bool, InstrumentParseError> {
-        let okex_parsed = Self::parse_okex_symbol(okex_symbol)?;
-        let deribit_parsed = Self::parse_deribit_symbol(deribit_symbol)?;
+        Ok(Self::compare_instruments(okex_symbol, deribit_symbol)?.is_exact_match())
+    }
+
+    /// Parses each symbol with its own venue's format and compares the instruments.
+    pub fn are_same_instrument_for_exchanges(
+        a_exchange: &Exchange,
+        a_symbol: &str,
+        b_exchange: &Exchange,
+        b_symbol: &str,
+    ) -> Result<bool, InstrumentParseError> {
+        Ok(
+            Self::compare_instruments_for_exchanges(a_exchange, a_symbol, b_exchange, b_symbol)?
+                .is_exact_match(),
+        )
+    }
+
+    /// Which fields of the Okex and Deribit instruments differ.
+    pub fn compare_instruments(
+        okex_symbol: &str,
+        deribit_symbol: &str,
+    ) -> Result<InstrumentMismatch, InstrumentParseError> {
+        Self::compare_instruments_for_exchanges(
+            &Exchange::Okex,
+            okex_symbol,
+            &Exchange::Deribit,
+            deribit_symbol,
+        )
+    }
+
+    pub fn compare_instruments_for_exchanges(
+        a_exchange: &Exchange,
+        a_symbol: &str,
+        b_exchange: &Exchange,
+        b_symbol: &str,
+    ) -> Result<InstrumentMismatch, InstrumentParseError> {
+        Ok(InstrumentMismatch::new(
+            Self::parse_symbol(Some(a_exchange), a_symbol)?,
+            Self::parse_symbol(Some(b_exchange), b_symbol)?,
+        ))
+    }
+
+    /// Compares only the underlying asset, e.g. to group every BTC option together.
+    pub fn are_same_underlying(
+        okex_symbol: &str,
+        deribit_symbol: &str,
+    ) -> Result<bool, InstrumentParseError> {
+        let okex_parsed = Self::parse_symbol(Some(&Exchange::Okex), okex_symbol)?;
+        let deribit_parsed = Self::parse_symbol(Some(&Exchange::Deribit), deribit_symbol)?;
+
+        Ok(okex_parsed.underlying == deribit_parsed.underlying)
+    }
+
+    /// The Okex symbol for the same instrument as `deribit_sym`.
+    pub fn normalize_to_okex(deribit_sym: &str) -> Result<String, InstrumentParseError> {
+        Ok(Self::parse_symbol(Some(&Exchange::Deribit), deribit_sym)?.to_okex_symbol())
+    }
 
-        Ok(okex_parsed == deribit_parsed)
+    /// The Deribit symbol for the same instrument as `okex_sym`.
+    pub fn normalize_to_deribit(okex_sym: &str) -> Result<String, InstrumentParseError> {
+        Ok(Self::parse_symbol(Some(&Exchange::Okex), okex_sym)?.to_deribit_symbol())
+    }
+
+    /// Compares only the expiry date, e.g. to bucket instruments by expiry.
+    pub fn are_same_expiry(
+        okex_symbol: &str,
+        deribit_symbol: &str,
+    ) -> Result<bool, InstrumentParseError> {
+        let okex_parsed = Self::parse_symbol(Some(&Exchange::Okex), okex_symbol)?;
+        let deribit_parsed = Self::parse_symbol(Some(&Exchange::Deribit), deribit_symbol)?;
+
+        Ok(okex_parsed.expiry_date == deribit_parsed.expiry_date)
+    }
+
+    /// Whether the Okex, Deribit and Binance symbols all name the same instrument.
+    pub fn are_same_instrument_with_binance(
+        okex_symbol: &str,
+        deribit_symbol: &str,
+        binance_symbol: &str,
+    ) -> Result<bool, InstrumentParseError> {
+        let binance_parsed = Self::parse_symbol(Some(&Exchange::Binance), binance_symbol)?;
+        let okex_parsed = Self::parse_symbol(Some(&Exchange::Okex), okex_symbol)?;
+
+        Ok(
+            Self::are_same_instrument(okex_symbol, deribit_symbol)?
+                && okex_parsed == binance_parsed,
+        )
+    }
+
+    /// Like `are_same_instrument_with_binance`, with Bybit as the third venue.
+    pub fn are_same_instrument_with_bybit(
+        okex_symbol: &str,
+        deribit_symbol: &str,
+        bybit_symbol: &str,
+    ) -> Result<bool, InstrumentParseError> {
+        let bybit_parsed = Self::parse_symbol(Some(&Exchange::Bybit), bybit_symbol)?;
+        let okex_parsed = Self::parse_symbol(Some(&Exchange::Okex), okex_symbol)?;
+
+        Ok(Self::are_same_instrument(okex_symbol, deribit_symbol)? && okex_parsed == bybit_parsed)
+    }
+
+    /// Parses `symbol` with the given exchange's format, inferring the exchange from the symbol
+    /// shape when `exchange` is `None`.
+    pub fn parse_symbol(
+        exchange: Option<&Exchange>,
+        symbol: &str,
+    ) -> Result<ParsedInstrument, InstrumentParseError> {
+        let exchange = match exchange {
+            Some(exchange) => exchange.clone(),
+            None => Self::detect_exchange(symbol)
+                .ok_or_else(|| InstrumentParseError::UnknownExchange(symbol.to_string()))?,
+        };
+
+        match exchange {
+            Exchange::Okex => Self::parse_okex_symbol(symbol),
+            Exchange::Deribit => Self::parse_deribit_symbol(symbol),
+            Exchange::Binance => Self::parse_binance_symbol(symbol),
+            Exchange::Bybit => Self::parse_bybit_symbol(symbol),
+            // An aggregated book is named after one of its venues' symbols.
+            Exchange::Aggregated => Self::parse_symbol(None, symbol),
+        }
+        .map_err(|e| e.with_context(symbol))
+    }
+
+    pub fn detect_exchange(symbol: &str) -> Option<Exchange> {
+        let symbol = symbol.to_uppercase();
+        if OKEX_SYMBOL.is_match(&symbol) {
+            Some(Exchange::Okex)
+        } else if DERIBIT_SYMBOL.is_match(&symbol) {
+            Some(Exchange::Deribit)
+        } else if BINANCE_SYMBOL.is_match(&symbol) {
+            Some(Exchange::Binance)
+        } else {
+            None
+        }
     }
 
     fn parse_okex_symbol(symbol: &str) -> Result<ParsedInstrument, InstrumentParseError> {
@@ -53,10 +535,9 @@ impl InstrumentValidator {
         let strike_str = parts[3];
         let option_type_str = parts[4];
 
-        let underlying = base.to_uppercase();
+        let underlying = Self::parse_underlying(base)?;
         let expiry_date = Self::parse_okex_date(date_str)?;
-        let strike: u32 = strike_str
-            .parse()
+        let strike = Decimal::from_str(strike_str)
             .map_err(|_| InstrumentParseError::InvalidStrike(strike_str.to_string()))?;
         let option_type = match option_type_str.to_uppercase().as_str() {
             "C" => OptionType::Call,
@@ -77,19 +558,28 @@ impl InstrumentValidator {
     }
 
     fn parse_deribit_symbol(symbol: &str) -> Result<ParsedInstrument, InstrumentParseError> {
+        Self::parse_ddmmmyy_format(symbol)
+    }
+
+    /// Bybit lists options with the same `BTC-27DEC24-56000-C` shape as Deribit.
+    pub fn parse_bybit_symbol(symbol: &str) -> Result<ParsedInstrument, InstrumentParseError> {
+        Self::parse_ddmmmyy_format(symbol)
+    }
+
+    /// `{UNDERLYING}-{DDMMMYY}-{STRIKE}-{C|P}`, shared by Deribit and Bybit.
+    fn parse_ddmmmyy_format(symbol: &str) -> Result<ParsedInstrument, InstrumentParseError> {
         let parts: Vec<&str> = symbol.split('-').collect();
         if parts.len() < 4 {
             return Err(InstrumentParseError::InsufficientComponents);
         }
 
-        let underlying = parts[0].to_uppercase();
+        let underlying = Self::parse_underlying(parts[0])?;
         let date_str = parts[1];
         let strike_str = parts[2];
         let option_type_str = parts[3];
 
         let expiry_date = Self::parse_deribit_date(date_str)?;
-        let strike: u32 = strike_str
-            .parse()
+        let strike = Decimal::from_str(strike_str)
             .map_err(|_| InstrumentParseError::InvalidStrike(strike_str.to_string()))?;
         let option_type = match option_type_str.to_uppercase().as_str() {
             "C" => OptionType::Call,
@@ -109,8 +599,50 @@ impl InstrumentValidator {
         })
     }
 
+    fn parse_binance_symbol(symbol: &str) -> Result<ParsedInstrument, InstrumentParseError> {
+        let parts: Vec<&str> = symbol.split('-').collect();
+        if parts.len() < 4 {
+            return Err(InstrumentParseError::InsufficientComponents);
+        }
+
+        let underlying = Self::parse_underlying(parts[0])?;
+        let date_str = parts[1];
+        let strike_str = parts[2];
+        let option_type_str = parts[3];
+
+        // Binance uses the same YYMMDD expiry encoding as Okex.
+        let expiry_date = Self::parse_okex_date(date_str)?;
+        let strike = Decimal::from_str(strike_str)
+            .map_err(|_| InstrumentParseError::InvalidStrike(strike_str.to_string()))?;
+        let option_type = match option_type_str.to_uppercase().as_str() {
+            "C" => OptionType::Call,
+            "P" => OptionType::Put,
+            _ => {
+                return Err(InstrumentParseError::InvalidOptionType(
+                    option_type_str.to_string(),
+                ));
+            }
+        };
+
+        Ok(ParsedInstrument {
+            underlying,
+            expiry_date,
+            strike,
+            option_type,
+        })
+    }
+
+    fn parse_underlying(underlying: &str) -> Result<String, InstrumentParseError> {
+        let underlying = underlying.to_uppercase();
+        if SUPPORTED_UNDERLYINGS.contains(&underlying.as_str()) {
+            Ok(underlying)
+        } else {
+            Err(InstrumentParseError::UnsupportedUnderlying(underlying))
+        }
+    }
+
     fn parse_okex_date(date_str: &str) -> Result<NaiveDate, InstrumentParseError> {
-        if date_str.len() != 6 {
+        if date_str.len() != 6 || !date_str.is_ascii() {
             return Err(InstrumentParseError::InvalidFormat(format!(
                 "Expected 6-digit date, got: {date_str}",
             )));
@@ -147,69 +679,68 @@ impl InstrumentValidator {
     }
 
     fn parse_deribit_date(date_str: &str) -> Result<NaiveDate, InstrumentParseError> {
-        if date_str.len() < 7 {
-            return Err(InstrumentParseError::InvalidFormat(format!(
-                "Expected format DDMMMYY, got: {date_str}",
-            )));
-        }
+        parse_deribit_date_with(date_str, month_from_abbrev)
+    }
+}
 
-        let day_str = &date_str[0..2];
-        let month_str = &date_str[2..5];
-        let year_str = &date_str[5..7];
+/// The month number of a `MONTH_ABBREVS` entry, ignoring ASCII case.
+pub fn month_from_abbrev(abbrev: &str) -> Option<u32> {
+    MONTH_ABBREVS
+        .iter()
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(abbrev))
+        .map(|&(_, month)| month)
+}
 
-        let day: u32 = day_str
-            .parse()
-            .map_err(|_| InstrumentParseError::InvalidFormat(format!("Invalid day: {day_str}")))?;
+/// `DDMMMYY` parsing with the month lookup passed in, so `benches/parsing.rs` can compare
+/// lookups through the real parser.
+#[doc(hidden)]
+pub fn parse_deribit_date_with(
+    date_str: &str,
+    month_of: impl Fn(&str) -> Option<u32>,
+) -> Result<NaiveDate, InstrumentParseError> {
+    if date_str.len() < 7 || !date_str.is_ascii() {
+        return Err(InstrumentParseError::InvalidFormat(format!(
+            "Expected format DDMMMYY, got: {date_str}",
+        )));
+    }
 
-        let year: i32 = year_str.parse().map_err(|_| {
-            InstrumentParseError::InvalidFormat(format!("Invalid year: {year_str}"))
-        })?;
+    let day_str = &date_str[0..2];
+    let month_str = &date_str[2..5];
+    let year_str = &date_str[5..7];
 
-        let month_map: HashMap<&str, u32> = [
-            ("JAN", 1),
-            ("FEB", 2),
-            ("MAR", 3),
-            ("APR", 4),
-            ("MAY", 5),
-            ("JUN", 6),
-            ("JUL", 7),
-            ("AUG", 8),
-            ("SEP", 9),
-            ("OCT", 10),
-            ("NOV", 11),
-            ("DEC", 12),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+    let day: u32 = day_str
+        .parse()
+        .map_err(|_| InstrumentParseError::InvalidFormat(format!("Invalid day: {day_str}")))?;
 
-        let month = *month_map
-            .get(month_str.to_uppercase().as_str())
-            .ok_or_else(|| {
-                InstrumentParseError::InvalidFormat(format!("Invalid month: {month_str}"))
-            })?;
+    let year: i32 = year_str
+        .parse()
+        .map_err(|_| InstrumentParseError::InvalidFormat(format!("Invalid year: {year_str}")))?;
 
-        // Convert 2-digit year to 4-digit (assuming 20XX for years 00-99)
-        let full_year = if (0..=99).contains(&year) {
-            2000 + year
-        } else {
-            return Err(InstrumentParseError::InvalidFormat(format!(
-                "Invalid year: {year}",
-            )));
-        };
+    let month = month_of(month_str).ok_or_else(|| {
+        InstrumentParseError::InvalidFormat(format!("Invalid month: {month_str}"))
+    })?;
 
-        NaiveDate::from_ymd_opt(full_year, month, day).ok_or_else(|| {
-            InstrumentParseError::InvalidFormat(format!(
-                "Invalid date: {full_year}-{month:02}-{day:02}",
-            ))
-        })
-    }
+    // Convert 2-digit year to 4-digit (assuming 20XX for years 00-99)
+    let full_year = if (0..=99).contains(&year) {
+        2000 + year
+    } else {
+        return Err(InstrumentParseError::InvalidFormat(format!(
+            "Invalid year: {year}",
+        )));
+    };
+
+    NaiveDate::from_ymd_opt(full_year, month, day).ok_or_else(|| {
+        InstrumentParseError::InvalidFormat(format!(
+            "Invalid date: {full_year}-{month:02}-{day:02}",
+        ))
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use chrono::{Datelike, NaiveDate};
+    use rust_decimal_macros::dec;
 
     #[test]
     fn test_same_instruments() {
@@ -235,6 +766,20 @@ mod tests {
         assert!(!InstrumentValidator::are_same_instrument(okex, deribit).unwrap());
     }
 
+    #[test]
+    fn test_same_underlying_and_expiry() {
+        let okex = "BTC-USD-240427-56000-C";
+        let deribit = "BTC-27APR24-70000-P";
+
+        assert!(InstrumentValidator::are_same_underlying(okex, deribit).unwrap());
+        assert!(InstrumentValidator::are_same_expiry(okex, deribit).unwrap());
+        assert!(!InstrumentValidator::are_same_instrument(okex, deribit).unwrap());
+
+        assert!(!InstrumentValidator::are_same_underlying(okex, "ETH-27APR24-3500-C").unwrap());
+        assert!(!InstrumentValidator::are_same_expiry(okex, "BTC-28APR24-56000-C").unwrap());
+        assert!(InstrumentValidator::are_same_expiry(okex, "BTC-INVALID").is_err());
+    }
+
     #[test]
     fn test_different_option_types() {
         let okex = "BTC-USD-240427-56000-C";
@@ -251,6 +796,205 @@ mod tests {
         assert!(InstrumentValidator::are_same_instrument(okex, deribit).unwrap());
     }
 
+    #[test]
+    fn test_binance_symbol() {
+        let parsed = InstrumentValidator::parse_binance_symbol("BTC-251031-140000-P").unwrap();
+        assert_eq!(parsed.underlying, "BTC");
+        assert_eq!(
+            parsed.expiry_date,
+            NaiveDate::from_ymd_opt(2025, 10, 31).unwrap()
+        );
+        assert_eq!(parsed.strike, dec!(140000));
+        assert_eq!(parsed.option_type, OptionType::Put);
+
+        assert!(
+            InstrumentValidator::are_same_instrument_with_binance(
+                "BTC-USD-251031-140000-P",
+                "BTC-31OCT25-140000-P",
+                "BTC-251031-140000-P",
+            )
+            .unwrap()
+        );
+        assert!(
+            !InstrumentValidator::are_same_instrument_with_binance(
+                "BTC-USD-251031-140000-P",
+                "BTC-31OCT25-140000-P",
+                "BTC-251031-140000-C",
+            )
+            .unwrap()
+        );
+        assert!(InstrumentValidator::parse_binance_symbol("BTC-251031-140000").is_err());
+    }
+
+    #[test]
+    fn test_parse_symbols_file() {
+        let contents = "\
+# okex,deribit[,binance[,bybit]]
+BTC-USD-251031-140000-P,BTC-31OCT25-140000-P
+
+BTC-USD-251226-100000-C, BTC-26DEC25-100000-C ,BTC-251226-100000-C
+BTC-USD-251226-90000-C,BTC-26DEC25-90000-C,,BTC-26DEC25-90000-C
+";
+        let pairs = parse_symbols_file(contents).unwrap();
+        assert_eq!(
+            pairs,
+            vec![
+                SymbolPair {
+                    okex: Some("BTC-USD-251031-140000-P".to_string()),
+                    deribit: Some("BTC-31OCT25-140000-P".to_string()),
+                    binance: None,
+                    bybit: None,
+                },
+                SymbolPair {
+                    okex: Some("BTC-USD-251226-100000-C".to_string()),
+                    deribit: Some("BTC-26DEC25-100000-C".to_string()),
+                    binance: Some("BTC-251226-100000-C".to_string()),
+                    bybit: None,
+                },
+                SymbolPair {
+                    okex: Some("BTC-USD-251226-90000-C".to_string()),
+                    deribit: Some("BTC-26DEC25-90000-C".to_string()),
+                    binance: None,
+                    bybit: Some("BTC-26DEC25-90000-C".to_string()),
+                },
+            ]
+        );
+        assert!(pairs.iter().all(|p| p.is_same_instrument().unwrap()));
+
+        assert!(parse_symbols_file("BTC-USD-251031-140000-P").is_err());
+        assert!(parse_symbols_file("a,b,c,d,e").is_err());
+    }
+
+    #[test]
+    fn test_detect_exchange() {
+        assert_eq!(
+            InstrumentValidator::detect_exchange("BTC-USD-240427-56000-C"),
+            Some(Exchange::Okex)
+        );
+        assert_eq!(
+            InstrumentValidator::detect_exchange("BTC-27APR24-56000-C"),
+            Some(Exchange::Deribit)
+        );
+        assert_eq!(
+            InstrumentValidator::detect_exchange("BTC-240427-56000-C"),
+            Some(Exchange::Binance)
+        );
+        // Lowercase symbols are accepted by the parsers, so detection is case-insensitive too
+        assert_eq!(
+            InstrumentValidator::detect_exchange("eth-usd-240427-3000-p"),
+            Some(Exchange::Okex)
+        );
+
+        // Looks like Okex but the date has 7 digits
+        assert_eq!(
+            InstrumentValidator::detect_exchange("BTC-USD-2404271-56000-C"),
+            None
+        );
+        // Deribit-like date but a currency segment that only Okex uses
+        assert_eq!(
+            InstrumentValidator::detect_exchange("BTC-USD-27APR24-56000-C"),
+            None
+        );
+        assert_eq!(InstrumentValidator::detect_exchange("BTC-PERPETUAL"), None);
+    }
+
+    #[test]
+    fn test_parse_symbol_dispatch() {
+        let inferred = InstrumentValidator::parse_symbol(None, "BTC-27APR24-56000-C").unwrap();
+        let explicit =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Okex), "BTC-USD-240427-56000-C")
+                .unwrap();
+        assert_eq!(inferred, explicit);
+
+        assert!(matches!(
+            InstrumentValidator::parse_symbol(None, "BTC-PERPETUAL"),
+            Err(InstrumentParseError::UnknownExchange(_))
+        ));
+        // An explicit exchange wins over the symbol shape
+        assert!(
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "BTC-USD-240427-56000-C")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_contract_multipliers() {
+        let btc = InstrumentValidator::parse_okex_symbol("BTC-USD-251031-140000-P").unwrap();
+        assert_eq!(btc.okex_contract_multiplier(), dec!(0.1));
+        assert_eq!(btc.deribit_contract_multiplier(), dec!(1));
+        assert_eq!(btc.contract_multiplier(&Exchange::Bybit), dec!(1));
+
+        let eth = InstrumentValidator::parse_deribit_symbol("ETH-27DEC24-3500-C").unwrap();
+        assert_eq!(eth.contract_multiplier(&Exchange::Okex), dec!(1));
+        assert_eq!(eth.contract_multiplier(&Exchange::Deribit), dec!(1));
+    }
+
+    #[test]
+    fn test_days_to_expiry() {
+        // Leap year: 2024-02-28 -> 2024-03-01 spans Feb 29
+        let parsed = InstrumentValidator::parse_okex_symbol("BTC-USD-240301-56000-C").unwrap();
+        let feb_28 = NaiveDate::from_ymd_opt(2024, 2, 28).unwrap();
+        assert_eq!(parsed.days_to_expiry(feb_28), 2);
+        assert!(!parsed.is_expired(feb_28));
+
+        // Non-leap year spans one day less
+        let parsed = InstrumentValidator::parse_okex_symbol("BTC-USD-250301-56000-C").unwrap();
+        let feb_28 = NaiveDate::from_ymd_opt(2025, 2, 28).unwrap();
+        assert_eq!(parsed.days_to_expiry(feb_28), 1);
+
+        // Expiry day itself is zero days out and not yet expired
+        let expiry = parsed.expiry_date;
+        assert_eq!(parsed.days_to_expiry(expiry), 0);
+        assert!(!parsed.is_expired(expiry));
+
+        let day_after = expiry.succ_opt().unwrap();
+        assert_eq!(parsed.days_to_expiry(day_after), -1);
+        assert!(parsed.is_expired(day_after));
+    }
+
+    #[test]
+    fn test_symbol_round_trip() {
+        for deribit in [
+            "BTC-31OCT25-140000-P",
+            "ETH-05JAN24-3500-C",
+            "SOL-13JUN25-150.5-P",
+        ] {
+            let okex = InstrumentValidator::normalize_to_okex(deribit).unwrap();
+            let parsed = InstrumentValidator::parse_symbol(Some(&Exchange::Okex), &okex).unwrap();
+            assert_eq!(parsed.to_deribit_symbol(), deribit);
+        }
+
+        assert_eq!(
+            InstrumentValidator::normalize_to_okex("BTC-31OCT25-140000-P").unwrap(),
+            "BTC-USD-251031-140000-P"
+        );
+        assert_eq!(
+            InstrumentValidator::normalize_to_deribit("ETH-USD-240105-3500-C").unwrap(),
+            "ETH-05JAN24-3500-C"
+        );
+        assert!(InstrumentValidator::normalize_to_deribit("BTC-31OCT25-140000-P").is_err());
+    }
+
+    #[test]
+    fn test_classify_expiry() {
+        let today = NaiveDate::from_ymd_opt(2024, 4, 8).unwrap();
+        let class = |symbol| {
+            InstrumentValidator::parse_okex_symbol(symbol)
+                .unwrap()
+                .classify_expiry_at(today)
+        };
+        // Last Friday of March 2024
+        assert_eq!(class("BTC-USD-240329-56000-C"), ExpiryClass::Quarterly);
+        // Last Friday of April 2024
+        assert_eq!(class("BTC-USD-240426-56000-C"), ExpiryClass::Monthly);
+        assert_eq!(class("BTC-USD-240412-56000-C"), ExpiryClass::Weekly);
+        assert_eq!(class("BTC-USD-240419-56000-C"), ExpiryClass::BiWeekly);
+        // A Friday more than two weeks out that is not month-end
+        assert_eq!(class("BTC-USD-240517-56000-C"), ExpiryClass::Other);
+        // Daily expiries fall on other weekdays
+        assert_eq!(class("BTC-USD-240409-56000-C"), ExpiryClass::Other);
+    }
+
     #[test]
     fn test_date_parsing() {
         let date1 = InstrumentValidator::parse_okex_date("240427").unwrap();
@@ -275,6 +1019,83 @@ mod tests {
 
         // Test invalid option type
         assert!(InstrumentValidator::parse_okex_symbol("BTC-USD-240427-56000-X").is_err());
+
+        // Multibyte characters must not be sliced through.
+        assert!(
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "BTC-1éAPR24-50000-C")
+                .is_err()
+        );
+        assert!(
+            InstrumentValidator::parse_symbol(Some(&Exchange::Okex), "BTC-USD-2é042-50000-C")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_symbol() {
+        let err =
+            InstrumentValidator::are_same_instrument("BTC-USD-2404-56000-C", "BTC-27APR24-56000-C")
+                .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error parsing 'BTC-USD-2404-56000-C': Invalid format: Expected 6-digit date, got: 2404"
+        );
+
+        let err =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "BTC-27APR24-56000-X")
+                .unwrap_err();
+        assert!(matches!(
+            &err,
+            InstrumentParseError::WithContext { source, symbol }
+                if symbol == "BTC-27APR24-56000-X"
+                    && matches!(**source, InstrumentParseError::InvalidOptionType(_))
+        ));
+        // Wrapping twice keeps the innermost symbol
+        assert_eq!(err.to_string(), err.with_context("other").to_string());
+    }
+
+    #[test]
+    fn test_unsupported_underlying() {
+        assert!(matches!(
+            InstrumentValidator::parse_okex_symbol("DOGE-USD-240427-1-C"),
+            Err(InstrumentParseError::UnsupportedUnderlying(u)) if u == "DOGE"
+        ));
+        assert!(matches!(
+            InstrumentValidator::parse_deribit_symbol("x1-27APR24-1-P"),
+            Err(InstrumentParseError::UnsupportedUnderlying(u)) if u == "X1"
+        ));
+        assert!(matches!(
+            InstrumentValidator::parse_deribit_symbol("-27APR24-1-P"),
+            Err(InstrumentParseError::UnsupportedUnderlying(u)) if u.is_empty()
+        ));
+        assert!(matches!(
+            InstrumentValidator::parse_binance_symbol("ADA-240427-1-C"),
+            Err(InstrumentParseError::UnsupportedUnderlying(_))
+        ));
+        for underlying in ["BTC", "eth", "SOL_USDC", "XRP"] {
+            let symbol = format!("{underlying}-27APR24-100-C");
+            let parsed = InstrumentValidator::parse_deribit_symbol(&symbol).unwrap();
+            assert_eq!(parsed.underlying, underlying.to_uppercase());
+        }
+        assert_eq!(
+            InstrumentValidator::detect_exchange("SOL_USDC-27APR24-100-C"),
+            Some(Exchange::Deribit)
+        );
+    }
+
+    #[test]
+    fn test_display_canonical_form() {
+        let okex =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Okex), "BTC-USD-241227-56000-C")
+                .unwrap();
+        let deribit =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "ETH-27DEC24-3500.5-P")
+                .unwrap();
+
+        assert_eq!(okex.to_string(), "BTC-20241227-56000-C");
+        assert_eq!(deribit.to_string(), "ETH-20241227-3500.5-P");
+        assert_eq!(OptionType::Call.to_string(), "Call");
+        assert_eq!(OptionType::Put.to_string(), "Put");
     }
 
     #[test]
@@ -286,7 +1107,7 @@ mod tests {
             okex_parsed.expiry_date,
             NaiveDate::from_ymd_opt(2024, 4, 27).unwrap()
         );
-        assert_eq!(okex_parsed.strike, 56000);
+        assert_eq!(okex_parsed.strike, dec!(56000));
         assert_eq!(okex_parsed.option_type, OptionType::Call);
 
         let deribit_parsed =
@@ -297,7 +1118,7 @@ mod tests {
             deribit_parsed.expiry_date,
             NaiveDate::from_ymd_opt(2024, 4, 27).unwrap()
         );
-        assert_eq!(deribit_parsed.strike, 56000);
+        assert_eq!(deribit_parsed.strike, dec!(56000));
         assert_eq!(deribit_parsed.option_type, OptionType::Call);
 
         assert_eq!(okex_parsed, deribit_parsed);
@@ -333,4 +1154,158 @@ mod tests {
             assert_eq!(parsed.month(), expected_month);
         }
     }
+
+    #[test]
+    fn test_decimal_strikes() {
+        assert!(
+            InstrumentValidator::are_same_instrument(
+                "BTC-USD-241227-56000-C",
+                "BTC-27DEC24-56000-C"
+            )
+            .unwrap()
+        );
+        assert_eq!(Decimal::from_str("56000").unwrap(), dec!(56000));
+
+        let okex =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Okex), "ETH-USD-241227-3500.5-C")
+                .unwrap();
+        let deribit =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "ETH-27DEC24-3500.5-C")
+                .unwrap();
+        assert_eq!(okex.strike, dec!(3500.5));
+        assert_eq!(okex, deribit);
+        assert_eq!(
+            InstrumentValidator::detect_exchange("ETH-27DEC24-3500.5-C"),
+            Some(Exchange::Deribit)
+        );
+
+        // Trailing zeros do not make a different strike
+        assert!(
+            InstrumentValidator::are_same_instrument(
+                "ETH-USD-241227-3500.50-C",
+                "ETH-27DEC24-3500.5-C"
+            )
+            .unwrap()
+        );
+        assert!(matches!(
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "ETH-27DEC24-35x0-C"),
+            Err(InstrumentParseError::WithContext { source, .. })
+                if matches!(*source, InstrumentParseError::InvalidStrike(_))
+        ));
+    }
+
+    #[test]
+    fn test_parsed_instrument_serde_round_trip() {
+        let parsed =
+            InstrumentValidator::parse_symbol(Some(&Exchange::Deribit), "ETH-27DEC24-3500.5-P")
+                .unwrap();
+        let json = serde_json::to_string(&parsed).unwrap();
+        assert!(json.contains("\"expiry_date\":\"2024-12-27\""));
+        assert_eq!(
+            serde_json::from_str::<ParsedInstrument>(&json).unwrap(),
+            parsed
+        );
+    }
+
+    #[test]
+    fn test_compare_instruments_names_differing_fields() {
+        let mismatch = InstrumentValidator::compare_instruments(
+            "BTC-USD-240427-56000-C",
+            "ETH-27APR24-60000-C",
+        )
+        .unwrap();
+        assert!(mismatch.underlying_mismatch && mismatch.strike_mismatch);
+        assert!(!mismatch.expiry_mismatch && !mismatch.type_mismatch);
+        assert!(!mismatch.is_exact_match());
+        assert_eq!(
+            mismatch.to_string(),
+            "underlyings differ: BTC vs ETH, strikes differ: 56000 vs 60000"
+        );
+
+        let exact = InstrumentValidator::compare_instruments(
+            "BTC-USD-240427-56000-C",
+            "BTC-27APR24-56000-C",
+        )
+        .unwrap();
+        assert!(exact.is_exact_match());
+
+        let pair = SymbolPair {
+            okex: Some("BTC-USD-240427-56000-C".to_string()),
+            deribit: Some("BTC-27APR24-56000-C".to_string()),
+            bybit: Some("BTC-26APR24-56000-P".to_string()),
+            ..SymbolPair::default()
+        };
+        assert_eq!(
+            pair.mismatch().unwrap().unwrap().to_string(),
+            "expiries differ: 2024-04-27 vs 2024-04-26, option types differ: Call vs Put"
+        );
+    }
+
+    #[test]
+    fn test_are_same_instrument_for_exchanges() {
+        assert!(
+            InstrumentValidator::are_same_instrument_for_exchanges(
+                &Exchange::Bybit,
+                "BTC-27DEC24-56000-C",
+                &Exchange::Binance,
+                "BTC-241227-56000-C",
+            )
+            .unwrap()
+        );
+        // Each symbol is parsed with its own venue's format.
+        assert!(
+            InstrumentValidator::are_same_instrument_for_exchanges(
+                &Exchange::Okex,
+                "BTC-27DEC24-56000-C",
+                &Exchange::Deribit,
+                "BTC-27DEC24-56000-C",
+            )
+            .is_err()
+        );
+
+        let single = SymbolPair {
+            deribit: Some("BTC-27DEC24-56000-C".to_string()),
+            ..SymbolPair::default()
+        };
+        assert!(matches!(
+            single.is_same_instrument(),
+            Err(InstrumentParseError::TooFewVenues(1))
+        ));
+    }
+
+    #[test]
+    fn test_bybit_symbol_pair() {
+        let pair = SymbolPair {
+            okex: Some("BTC-USD-241227-56000-C".to_string()),
+            deribit: Some("BTC-27DEC24-56000-C".to_string()),
+            binance: None,
+            bybit: Some("BTC-27DEC24-56000-C".to_string()),
+        };
+        assert!(pair.is_same_instrument().unwrap());
+        assert_eq!(pair.venues().last().unwrap().0, Exchange::Bybit);
+
+        let mismatched = SymbolPair {
+            bybit: Some("BTC-27DEC24-60000-C".to_string()),
+            ..pair
+        };
+        assert!(!mismatched.is_same_instrument().unwrap());
+
+        assert!(
+            InstrumentValidator::are_same_instrument_with_bybit(
+                "BTC-USD-241227-56000-C",
+                "BTC-27DEC24-56000-C",
+                "BTC-27DEC24-56000-C",
+            )
+            .unwrap()
+        );
+        assert!(
+            !InstrumentValidator::are_same_instrument_with_bybit(
+                "BTC-USD-241227-56000-C",
+                "BTC-27DEC24-56000-C",
+                "BTC-27DEC24-56000-P",
+            )
+            .unwrap()
+        );
+        assert!(InstrumentValidator::parse_bybit_symbol("BTC-27DEC24-56000").is_err());
+    }
 }
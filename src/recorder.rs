@@ -0,0 +1,471 @@
+use crate::orderbook::{Exchange, OrderBookUpdate, OrderLevel};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+const MAGIC: &[u8; 4] = b"OARB";
+const VERSION: u8 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("Recording I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not a market data recording (bad magic header)")]
+    BadMagic,
+    #[error("Unsupported recording version {0} (expected {VERSION})")]
+    UnsupportedVersion(u8),
+    #[error("Corrupt recording: {0}")]
+    Encoding(#[from] bincode::Error),
+}
+
+/// Levels are stored in `Decimal`'s own 16-byte form; the crate-wide serde impl goes through
+/// `f64`, which would not replay exactly.
+#[derive(Serialize, Deserialize)]
+struct RecordedLevel {
+    price: [u8; 16],
+    quantity: [u8; 16],
+}
+
+impl From<&OrderLevel> for RecordedLevel {
+    fn from(level: &OrderLevel) -> Self {
+        Self {
+            price: level.price.serialize(),
+            quantity: level.quantity.serialize(),
+        }
+    }
+}
+
+impl From<RecordedLevel> for OrderLevel {
+    fn from(level: RecordedLevel) -> Self {
+        Self {
+            price: Decimal::deserialize(level.price),
+            quantity: Decimal::deserialize(level.quantity),
+        }
+    }
+}
+
+/// On-disk form of `OrderBookUpdate`, which carries an `Instant` that cannot be serialized.
+#[derive(Serialize, Deserialize)]
+enum RecordedUpdate {
+    Bids {
+        exchange: Exchange,
+        symbol: String,
+        levels: Vec<RecordedLevel>,
+    },
+    Asks {
+        exchange: Exchange,
+        symbol: String,
+        levels: Vec<RecordedLevel>,
+    },
+    Snapshot {
+        exchange: Exchange,
+        symbol: String,
+        bids: Vec<RecordedLevel>,
+        asks: Vec<RecordedLevel>,
+    },
+    Clear {
+        exchange: Exchange,
+        symbol: String,
+    },
+    ConnectionError {
+        exchange: Exchange,
+        error: String,
+    },
+    // Appended last so recordings made before it still decode.
+    SubscriptionConfirmed {
+        exchange: Exchange,
+        symbol: String,
+        channel: String,
+    },
+    /// Replaces `ConnectionError`, which older recordings still use, now that errors name the
+    /// book they concern.
+    SymbolConnectionError {
+        exchange: Exchange,
+        error: String,
+        symbol: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    /// Time since the recording started.
+    offset_micros: u64,
+    update: RecordedUpdate,
+}
+
+fn record_levels(levels: &[OrderLevel]) -> Vec<RecordedLevel> {
+    levels.iter().map(RecordedLevel::from).collect()
+}
+
+fn replay_levels(levels: Vec<RecordedLevel>) -> Vec<OrderLevel> {
+    levels.into_iter().map(OrderLevel::from).collect()
+}
+
+/// Appends order book updates to a recording, timestamped relative to when it was created.
+pub struct RecordingWriter<W: Write = BufWriter<File>> {
+    inner: W,
+    started_at: Instant,
+}
+
+impl RecordingWriter {
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> RecordingWriter<W> {
+    pub fn new(mut inner: W) -> Result<Self, RecordingError> {
+        inner.write_all(MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        Ok(Self {
+            inner,
+            started_at: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, update: &OrderBookUpdate) -> Result<(), RecordingError> {
+        let (update, at) = match update {
+            OrderBookUpdate::Bids {
+                exchange,
+                symbol,
+                levels,
+                received_at,
+            } => (
+                RecordedUpdate::Bids {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    levels: record_levels(levels),
+                },
+                *received_at,
+            ),
+            OrderBookUpdate::Asks {
+                exchange,
+                symbol,
+                levels,
+                received_at,
+            } => (
+                RecordedUpdate::Asks {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    levels: record_levels(levels),
+                },
+                *received_at,
+            ),
+            OrderBookUpdate::Snapshot {
+                exchange,
+                symbol,
+                bids,
+                asks,
+                received_at,
+            } => (
+                RecordedUpdate::Snapshot {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    bids: record_levels(bids),
+                    asks: record_levels(asks),
+                },
+                *received_at,
+            ),
+            OrderBookUpdate::Clear { exchange, symbol } => (
+                RecordedUpdate::Clear {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                },
+                Instant::now(),
+            ),
+            OrderBookUpdate::SubscriptionConfirmed {
+                exchange,
+                symbol,
+                channel,
+            } => (
+                RecordedUpdate::SubscriptionConfirmed {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    channel: channel.clone(),
+                },
+                Instant::now(),
+            ),
+            OrderBookUpdate::ConnectionError {
+                exchange,
+                error,
+                symbol,
+            } => (
+                RecordedUpdate::SymbolConnectionError {
+                    exchange: exchange.clone(),
+                    error: error.clone(),
+                    symbol: symbol.clone(),
+                },
+                Instant::now(),
+            ),
+        };
+
+        let record = Record {
+            offset_micros: at.saturating_duration_since(self.started_at).as_micros() as u64,
+            update,
+        };
+        bincode::serialize_into(&mut self.inner, &record)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), RecordingError> {
+        Ok(self.inner.flush()?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads a recording back as `(offset, update)` pairs, with `received_at` set to the time each
+/// update is read.
+pub struct RecordingReader<R: Read = BufReader<File>> {
+    inner: R,
+}
+
+impl RecordingReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, RecordingError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// Fails early if `inner` does not start with a recording header of a supported version.
+    pub fn new(mut inner: R) -> Result<Self, RecordingError> {
+        let mut header = [0u8; 5];
+        inner.read_exact(&mut header).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => RecordingError::BadMagic,
+            _ => RecordingError::Io(e),
+        })?;
+        if &header[..4] != MAGIC {
+            return Err(RecordingError::BadMagic);
+        }
+        if header[4] != VERSION {
+            return Err(RecordingError::UnsupportedVersion(header[4]));
+        }
+        Ok(Self { inner })
+    }
+}
+
+impl<R: Read> Iterator for RecordingReader<R> {
+    type Item = Result<(Duration, OrderBookUpdate), RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record: Record = match bincode::deserialize_from(&mut self.inner) {
+            Ok(record) => record,
+            Err(e) => match *e {
+                // Clean end of file, or a record cut short by the recorder being killed.
+                bincode::ErrorKind::Io(ref io)
+                    if io.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return None;
+                }
+                _ => return Some(Err(e.into())),
+            },
+        };
+
+        let received_at = Instant::now();
+        let update = match record.update {
+            RecordedUpdate::Bids {
+                exchange,
+                symbol,
+                levels,
+            } => OrderBookUpdate::Bids {
+                exchange,
+                symbol,
+                levels: replay_levels(levels),
+                received_at,
+            },
+            RecordedUpdate::Asks {
+                exchange,
+                symbol,
+                levels,
+            } => OrderBookUpdate::Asks {
+                exchange,
+                symbol,
+                levels: replay_levels(levels),
+                received_at,
+            },
+            RecordedUpdate::Snapshot {
+                exchange,
+                symbol,
+                bids,
+                asks,
+            } => OrderBookUpdate::Snapshot {
+                exchange,
+                symbol,
+                bids: replay_levels(bids),
+                asks: replay_levels(asks),
+                received_at,
+            },
+            RecordedUpdate::Clear { exchange, symbol } => {
+                OrderBookUpdate::Clear { exchange, symbol }
+            }
+            // Older recordings do not say which book failed. Replayed books count as subscribed
+            // regardless, so the empty symbol matches none.
+            RecordedUpdate::ConnectionError { exchange, error } => {
+                OrderBookUpdate::ConnectionError {
+                    exchange,
+                    error,
+                    symbol: String::new(),
+                }
+            }
+            RecordedUpdate::SymbolConnectionError {
+                exchange,
+                error,
+                symbol,
+            } => OrderBookUpdate::ConnectionError {
+                exchange,
+                error,
+                symbol,
+            },
+            RecordedUpdate::SubscriptionConfirmed {
+                exchange,
+                symbol,
+                channel,
+            } => OrderBookUpdate::SubscriptionConfirmed {
+                exchange,
+                symbol,
+                channel,
+            },
+        };
+
+        Some(Ok((Duration::from_micros(record.offset_micros), update)))
+    }
+}
+
+/// Feeds a recording into `tx` with its original pacing divided by `speed` (`2.0` replays twice
+/// as fast). Returns once the recording is exhausted or the receiver is dropped.
+pub async fn replay<R: Read>(
+    reader: RecordingReader<R>,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    speed: f64,
+) -> Result<(), RecordingError> {
+    let started_at = tokio::time::Instant::now();
+    for record in reader {
+        let (offset, update) = record?;
+        tokio::time::sleep_until(started_at + offset.div_f64(speed)).await;
+        // Unlike a live feed, a recording can wait for the main loop to catch up.
+        if tx.send(update).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn levels() -> Vec<OrderLevel> {
+        vec![
+            OrderLevel {
+                price: dec!(0.1425),
+                quantity: dec!(12.5),
+            },
+            OrderLevel {
+                price: dec!(0.143),
+                quantity: dec!(0),
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay_round_trip() {
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        writer
+            .write(&OrderBookUpdate::Bids {
+                exchange: Exchange::Okex,
+                symbol: "BTC-USD-251031-140000-P".to_string(),
+                levels: levels(),
+                received_at: Instant::now(),
+            })
+            .unwrap();
+        writer
+            .write(&OrderBookUpdate::Clear {
+                exchange: Exchange::Deribit,
+                symbol: "BTC-31OCT25-140000-P".to_string(),
+            })
+            .unwrap();
+        let bytes = writer.into_inner();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let reader = RecordingReader::new(bytes.as_slice()).unwrap();
+        replay(reader, tx, 10.0).await.unwrap();
+
+        match rx.recv().await.unwrap() {
+            OrderBookUpdate::Bids {
+                exchange,
+                symbol,
+                levels: replayed,
+                ..
+            } => {
+                assert_eq!(exchange, Exchange::Okex);
+                assert_eq!(symbol, "BTC-USD-251031-140000-P");
+                assert_eq!(replayed, levels());
+            }
+            other => panic!("unexpected update {other:?}"),
+        }
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            OrderBookUpdate::Clear {
+                exchange: Exchange::Deribit,
+                ..
+            }
+        ));
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn test_connection_errors_keep_their_symbol() {
+        let mut writer = RecordingWriter::new(Vec::new()).unwrap();
+        writer
+            .write(&OrderBookUpdate::ConnectionError {
+                exchange: Exchange::Okex,
+                error: "closed".to_string(),
+                symbol: "BTC-USD-251031-140000-P".to_string(),
+            })
+            .unwrap();
+        // As written before errors named their book.
+        let legacy = Record {
+            offset_micros: 0,
+            update: RecordedUpdate::ConnectionError {
+                exchange: Exchange::Deribit,
+                error: "closed".to_string(),
+            },
+        };
+        bincode::serialize_into(&mut writer.inner, &legacy).unwrap();
+        let bytes = writer.into_inner();
+
+        let symbols: Vec<String> = RecordingReader::new(bytes.as_slice())
+            .unwrap()
+            .map(|record| match record.unwrap().1 {
+                OrderBookUpdate::ConnectionError { symbol, .. } => symbol,
+                other => panic!("unexpected update {other:?}"),
+            })
+            .collect();
+        assert_eq!(symbols, ["BTC-USD-251031-140000-P", ""]);
+    }
+
+    #[test]
+    fn test_rejects_foreign_and_future_files() {
+        assert!(matches!(
+            RecordingReader::new(&b"PK\x03\x04\x00"[..]),
+            Err(RecordingError::BadMagic)
+        ));
+        assert!(matches!(
+            RecordingReader::new(&b"OA"[..]),
+            Err(RecordingError::BadMagic)
+        ));
+        assert!(matches!(
+            RecordingReader::new(&b"OARB\x02"[..]),
+            Err(RecordingError::UnsupportedVersion(2))
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+use crate::orderbook::{BookUpdateError, Exchange, OrderBook, OrderBookUpdate};
+use std::collections::{HashMap, HashSet};
+
+/// A book's venue and symbol.
+pub type BookKey = (Exchange, String);
+
+/// Every book being streamed, and which of them the venue has confirmed a subscription for.
+#[derive(Debug, Clone, Default)]
+pub struct BookRegistry {
+    books: HashMap<BookKey, OrderBook>,
+    subscribed: HashSet<BookKey>,
+    /// Books treated as subscribed regardless of confirmations and connection errors.
+    pinned: HashSet<BookKey>,
+    /// Passed on to `OrderBook::with_max_depth` for every book created.
+    max_book_depth: Option<usize>,
+}
+
+impl BookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_book_depth = Some(depth);
+        self
+    }
+
+    /// Counts `keys` as subscribed from the start, e.g. for recordings that predate
+    /// subscription confirmations.
+    pub fn assume_subscribed(mut self, keys: impl IntoIterator<Item = BookKey>) -> Self {
+        self.pinned.extend(keys);
+        self
+    }
+
+    /// Applies book data to its book, creating the book on first sight, and tracks
+    /// subscription confirmations and connection errors. Returns the book the update concerns,
+    /// or `None` for a connection error.
+    pub fn apply_update(
+        &mut self,
+        update: &OrderBookUpdate,
+    ) -> Result<Option<BookKey>, BookUpdateError> {
+        match update {
+            OrderBookUpdate::Bids {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Asks {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Snapshot {
+                exchange, symbol, ..
+            }
+            | OrderBookUpdate::Clear { exchange, symbol } => {
+                let key = (exchange.clone(), symbol.clone());
+                let max_book_depth = self.max_book_depth;
+                self.books
+                    .entry(key.clone())
+                    .or_insert_with(|| {
+                        let book = OrderBook::new(symbol.clone(), exchange.clone());
+                        match max_book_depth {
+                            Some(depth) => book.with_max_depth(depth),
+                            None => book,
+                        }
+                    })
+                    .apply_update(update)?;
+                Ok(Some(key))
+            }
+            OrderBookUpdate::SubscriptionConfirmed {
+                exchange, symbol, ..
+            } => {
+                let key = (exchange.clone(), symbol.clone());
+                self.subscribed.insert(key.clone());
+                Ok(Some(key))
+            }
+            OrderBookUpdate::ConnectionError {
+                exchange, symbol, ..
+            } => {
+                // Every book has its own connection, which resubscribes after reconnecting and
+                // confirms again; the other books on the venue are unaffected.
+                self.subscribed.remove(&(exchange.clone(), symbol.clone()));
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn get(&self, key: &BookKey) -> Option<&OrderBook> {
+        self.books.get(key)
+    }
+
+    pub fn contains(&self, key: &BookKey) -> bool {
+        self.books.contains_key(key)
+    }
+
+    pub fn is_subscribed(&self, key: &BookKey) -> bool {
+        self.subscribed.contains(key) || self.pinned.contains(key)
+    }
+
+    /// Both books, once both exist and both venues have confirmed their subscriptions. A full
+    /// book is never compared against one that has not started streaming.
+    pub fn ready_pair(&self, a: &BookKey, b: &BookKey) -> Option<(&OrderBook, &OrderBook)> {
+        if !self.is_subscribed(a) || !self.is_subscribed(b) {
+            return None;
+        }
+        Some((self.books.get(a)?, self.books.get(b)?))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&BookKey, &OrderBook)> {
+        self.books.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderLevel;
+    use rust_decimal_macros::dec;
+    use std::time::Instant;
+
+    fn key(exchange: Exchange, symbol: &str) -> BookKey {
+        (exchange, symbol.to_string())
+    }
+
+    fn bids((exchange, symbol): &BookKey) -> OrderBookUpdate {
+        OrderBookUpdate::Bids {
+            exchange: exchange.clone(),
+            symbol: symbol.clone(),
+            levels: vec![OrderLevel {
+                price: dec!(0.1),
+                quantity: dec!(1),
+            }],
+            received_at: Instant::now(),
+        }
+    }
+
+    fn confirmed((exchange, symbol): &BookKey) -> OrderBookUpdate {
+        OrderBookUpdate::SubscriptionConfirmed {
+            exchange: exchange.clone(),
+            symbol: symbol.clone(),
+            channel: "books".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ready_pair_needs_both_books_subscribed() {
+        let okex = key(Exchange::Okex, "BTC-USD-251031-140000-P");
+        let deribit = key(Exchange::Deribit, "BTC-31OCT25-140000-P");
+        let mut registry = BookRegistry::new().with_max_depth(10);
+
+        assert_eq!(registry.apply_update(&bids(&okex)), Ok(Some(okex.clone())));
+        registry.apply_update(&confirmed(&okex)).unwrap();
+        assert_eq!(registry.get(&okex).unwrap().max_depth, Some(10));
+        assert!(registry.ready_pair(&okex, &deribit).is_none());
+
+        registry.apply_update(&bids(&deribit)).unwrap();
+        assert!(registry.ready_pair(&okex, &deribit).is_none());
+
+        registry.apply_update(&confirmed(&deribit)).unwrap();
+        let (a, b) = registry.ready_pair(&okex, &deribit).unwrap();
+        assert_eq!(
+            (&a.exchange, &b.exchange),
+            (&Exchange::Okex, &Exchange::Deribit)
+        );
+
+        let error = OrderBookUpdate::ConnectionError {
+            exchange: Exchange::Deribit,
+            error: "closed".to_string(),
+            symbol: deribit.1.clone(),
+        };
+        assert_eq!(registry.apply_update(&error), Ok(None));
+        assert!(registry.is_subscribed(&okex));
+        assert!(registry.ready_pair(&okex, &deribit).is_none());
+    }
+
+    #[test]
+    fn test_assumed_subscriptions_survive_connection_errors() {
+        let okex = key(Exchange::Okex, "BTC-USD-251031-140000-P");
+        let deribit = key(Exchange::Deribit, "BTC-31OCT25-140000-P");
+        let mut registry = BookRegistry::new().assume_subscribed([okex.clone(), deribit.clone()]);
+        registry.apply_update(&bids(&okex)).unwrap();
+        registry.apply_update(&bids(&deribit)).unwrap();
+        registry
+            .apply_update(&OrderBookUpdate::ConnectionError {
+                exchange: Exchange::Okex,
+                error: "closed".to_string(),
+                symbol: okex.1.clone(),
+            })
+            .unwrap();
+        assert!(registry.ready_pair(&okex, &deribit).is_some());
+    }
+
+    #[test]
+    fn test_connection_error_only_unsubscribes_its_symbol() {
+        let put = key(Exchange::Okex, "BTC-USD-251031-140000-P");
+        let call = key(Exchange::Okex, "BTC-USD-251031-140000-C");
+        let deribit = key(Exchange::Deribit, "BTC-31OCT25-140000-C");
+        let mut registry = BookRegistry::new();
+        for book in [&put, &call, &deribit] {
+            registry.apply_update(&bids(book)).unwrap();
+            registry.apply_update(&confirmed(book)).unwrap();
+        }
+
+        registry
+            .apply_update(&OrderBookUpdate::ConnectionError {
+                exchange: Exchange::Okex,
+                error: "REST poll failed".to_string(),
+                symbol: put.1.clone(),
+            })
+            .unwrap();
+        assert!(!registry.is_subscribed(&put));
+        assert!(registry.ready_pair(&call, &deribit).is_some());
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{
+    exchanges::{
+        DeribitOrderBookData, ExchangeAdapter, Sent, parse_deribit_levels, parse_okex_levels,
+        send_update,
+    },
+    metrics::Metrics,
+    orderbook::{Exchange, OrderBookUpdate, OrderLevel},
+};
+use anyhow::{Result, anyhow};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use std::{sync::Arc, time::Instant};
+use tokio::{sync::mpsc, time::Duration};
+use tracing::info;
+
+/// Levels requested per side, matching the default websocket depth.
+const REST_BOOK_DEPTH: u32 = 20;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Deserialize, Debug)]
+struct OkexRestResponse {
+    /// `"0"` on success.
+    code: String,
+    msg: String,
+    data: Vec<OkexRestBook>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OkexRestBook {
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DeribitRestResponse {
+    result: DeribitOrderBookData,
+}
+
+type Levels = (Vec<OrderLevel>, Vec<OrderLevel>);
+
+/// Bids and asks from a `GET /api/v5/market/books` response body.
+fn parse_okex_rest_book(body: &str) -> Result<Levels> {
+    let resp: OkexRestResponse = serde_json::from_str(body)?;
+    if resp.code != "0" {
+        return Err(anyhow!("Okex error {}: {}", resp.code, resp.msg));
+    }
+    let book = resp
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Okex returned no order book"))?;
+    Ok((parse_okex_levels(book.bids), parse_okex_levels(book.asks)))
+}
+
+/// Bids and asks from a `public/get_order_book` response body.
+fn parse_deribit_rest_book(body: &str) -> Result<Levels> {
+    let resp: DeribitRestResponse = serde_json::from_str(body)?;
+    Ok((
+        parse_deribit_levels(resp.result.bids),
+        parse_deribit_levels(resp.result.asks),
+    ))
+}
+
+fn client() -> reqwest::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+}
+
+fn deribit_book_url(symbol: &str) -> String {
+    format!(
+        "https://www.deribit.com/api/v2/public/get_order_book?instrument_name={symbol}&depth={REST_BOOK_DEPTH}"
+    )
+}
+
+/// Fetches `symbol`'s Deribit book once and pushes it as a `Snapshot`, so the book has real
+/// levels while the websocket is still connecting.
+pub async fn deribit_rest_snapshot(
+    symbol: &str,
+    tx: &mpsc::Sender<OrderBookUpdate>,
+    metrics: &Metrics,
+) -> Result<()> {
+    let body = client()?
+        .get(deribit_book_url(symbol))
+        .send()
+        .await?
+        .text()
+        .await?;
+    let received_at = Instant::now();
+    metrics.record_message(&Exchange::Deribit, body.len());
+    let (bids, asks) = parse_deribit_rest_book(&body)?;
+    send_update(
+        tx,
+        OrderBookUpdate::Snapshot {
+            exchange: Exchange::Deribit,
+            symbol: symbol.to_string(),
+            bids,
+            asks,
+            received_at,
+        },
+        metrics,
+    )
+    .await;
+    Ok(())
+}
+
+/// Polls `url` every `interval` and pushes each parsed book as a `Snapshot`. Failed polls are
+/// reported as `ConnectionError`s and retried on the next tick. Returns once the receiver is
+/// dropped.
+async fn poll_task(
+    exchange: Exchange,
+    symbol: String,
+    url: String,
+    interval: Duration,
+    parse: fn(&str) -> Result<Levels>,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
+) -> Result<()> {
+    let client = client()?;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    info!(%exchange, interval_ms = interval.as_millis() as u64, "polling REST order book");
+    // Polling has no subscribe step, so the first good response after a failure stands in for
+    // the confirmation.
+    let mut confirmed = false;
+
+    loop {
+        ticker.tick().await;
+        let body = match client.get(&url).send().await {
+            Ok(resp) => resp.text().await,
+            Err(e) => Err(e),
+        };
+        let received_at = Instant::now();
+        let parsed = body.map_err(anyhow::Error::from).and_then(|body| {
+            metrics.record_message(&exchange, body.len());
+            parse(&body)
+        });
+        if parsed.is_ok() && !confirmed {
+            confirmed = true;
+            send_update(
+                &tx,
+                OrderBookUpdate::SubscriptionConfirmed {
+                    exchange: exchange.clone(),
+                    symbol: symbol.clone(),
+                    channel: url.clone(),
+                },
+                &metrics,
+            )
+            .await;
+        }
+        let update = match parsed {
+            Ok((bids, asks)) => OrderBookUpdate::Snapshot {
+                exchange: exchange.clone(),
+                symbol: symbol.clone(),
+                bids,
+                asks,
+                received_at,
+            },
+            Err(e) => {
+                confirmed = false;
+                OrderBookUpdate::ConnectionError {
+                    exchange: exchange.clone(),
+                    error: format!("REST poll failed: {e}"),
+                    symbol: symbol.clone(),
+                }
+            }
+        };
+        if send_update(&tx, update, &metrics).await == Sent::Closed {
+            return Ok(());
+        }
+    }
+}
+
+pub async fn okex_rest_task(
+    symbol: String,
+    interval: Duration,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
+) -> Result<()> {
+    let url =
+        format!("https://www.okx.com/api/v5/market/books?instId={symbol}&sz={REST_BOOK_DEPTH}");
+    poll_task(
+        Exchange::Okex,
+        symbol,
+        url,
+        interval,
+        parse_okex_rest_book,
+        tx,
+        metrics,
+    )
+    .await
+}
+
+pub async fn deribit_rest_task(
+    symbol: String,
+    interval: Duration,
+    tx: mpsc::Sender<OrderBookUpdate>,
+    metrics: Arc<Metrics>,
+) -> Result<()> {
+    let url = deribit_book_url(&symbol);
+    poll_task(
+        Exchange::Deribit,
+        symbol,
+        url,
+        interval,
+        parse_deribit_rest_book,
+        tx,
+        metrics,
+    )
+    .await
+}
+
+/// Polls a venue's REST order book endpoint instead of streaming it.
+pub struct RestAdapter {
+    exchange: Exchange,
+    interval: Duration,
+}
+
+impl ExchangeAdapter for RestAdapter {
+    fn exchange(&self) -> Exchange {
+        self.exchange.clone()
+    }
+
+    fn connect(
+        &self,
+        symbol: String,
+        tx: mpsc::Sender<OrderBookUpdate>,
+        metrics: Arc<Metrics>,
+    ) -> BoxFuture<'static, Result<()>> {
+        // `rest_adapter_for` only builds Okex and Deribit adapters.
+        match self.exchange {
+            Exchange::Okex => Box::pin(okex_rest_task(symbol, self.interval, tx, metrics)),
+            _ => Box::pin(deribit_rest_task(symbol, self.interval, tx, metrics)),
+        }
+    }
+}
+
+/// REST polling adapter for `exchange`, or `None` for venues that only have a websocket feed
+/// here.
+pub fn rest_adapter_for(
+    exchange: &Exchange,
+    interval: Duration,
+) -> Option<Box<dyn ExchangeAdapter>> {
+    match exchange {
+        Exchange::Okex | Exchange::Deribit => Some(Box::new(RestAdapter {
+            exchange: exchange.clone(),
+            interval,
+        })),
+        Exchange::Binance | Exchange::Bybit | Exchange::Aggregated => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_okex_rest_book() {
+        let body = r#"{"code":"0","msg":"","data":[{"asks":[["0.142","8","0","1"],["0.1425","3","0","1"]],"bids":[["0.1405","12","0","2"]],"ts":"1761000000000"}]}"#;
+        let (bids, asks) = parse_okex_rest_book(body).unwrap();
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].price, dec!(0.1405));
+        assert_eq!(bids[0].quantity, dec!(12));
+        assert_eq!(asks.len(), 2);
+        assert_eq!(asks[1].price, dec!(0.1425));
+
+        let error = r#"{"code":"51001","msg":"Instrument ID does not exist","data":[]}"#;
+        let message = parse_okex_rest_book(error).unwrap_err().to_string();
+        assert!(message.contains("Instrument ID does not exist"));
+    }
+
+    #[test]
+    fn test_parse_deribit_rest_book() {
+        let body = r#"{"jsonrpc":"2.0","result":{"timestamp":1761000000000,"instrument_name":"BTC-31OCT25-140000-P","bids":[[0.1405,12.0]],"asks":[[0.142,8.0],[0.1425,3.5]],"best_bid_price":0.1405},"usIn":1,"usOut":2}"#;
+        let (bids, asks) = parse_deribit_rest_book(body).unwrap();
+        assert_eq!(bids[0].price, dec!(0.1405));
+        assert_eq!(asks[1].quantity, dec!(3.5));
+        assert!(
+            deribit_book_url("BTC-31OCT25-140000-P")
+                .ends_with("get_order_book?instrument_name=BTC-31OCT25-140000-P&depth=20")
+        );
+    }
+
+    #[test]
+    fn test_rest_adapter_for() {
+        let interval = Duration::from_millis(500);
+        assert_eq!(
+            rest_adapter_for(&Exchange::Okex, interval)
+                .unwrap()
+                .exchange(),
+            Exchange::Okex
+        );
+        assert!(rest_adapter_for(&Exchange::Bybit, interval).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::orderbook::ArbitrageOpportunity;
+use rust_decimal::Decimal;
+use tracing::info;
+
+/// Portfolio-level view across every monitored symbol pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScannerReport {
+    /// Pairs with at least two venues' books to compare.
+    pub active_symbols: usize,
+    /// Pairs with at least one currently profitable opportunity.
+    pub symbols_with_arb: usize,
+    pub total_expected_profit: Decimal,
+    /// Highest `total_profit` among the current opportunities.
+    pub best_opportunity: Option<ArbitrageOpportunity>,
+    /// Opportunities reported since the previous report, including ones already gone. Left at
+    /// 0 by `compute`; the caller fills it from its `OpportunityStore`.
+    pub recent_opportunities: usize,
+}
+
+impl ScannerReport {
+    /// `opportunities` yields the current opportunities of each pair, one slice per pair.
+    pub fn compute<'a>(
+        active_symbols: usize,
+        opportunities: impl IntoIterator<Item = &'a [ArbitrageOpportunity]>,
+    ) -> Self {
+        let mut report = Self {
+            active_symbols,
+            symbols_with_arb: 0,
+            total_expected_profit: Decimal::ZERO,
+            best_opportunity: None,
+            recent_opportunities: 0,
+        };
+        for pair_opportunities in opportunities {
+            if pair_opportunities.is_empty() {
+                continue;
+            }
+            report.symbols_with_arb += 1;
+            for opp in pair_opportunities {
+                report.total_expected_profit += opp.total_profit;
+                if report
+                    .best_opportunity
+                    .as_ref()
+                    .is_none_or(|best| opp.total_profit > best.total_profit)
+                {
+                    report.best_opportunity = Some(opp.clone());
+                }
+            }
+        }
+        report
+    }
+
+    pub fn log(&self) {
+        info!(
+            active_symbols = self.active_symbols,
+            symbols_with_arb = self.symbols_with_arb,
+            total_expected_profit = %self.total_expected_profit,
+            best_symbol = self.best_opportunity.as_ref().map(|opp| opp.symbol.as_str()),
+            best_profit = ?self.best_opportunity.as_ref().map(|opp| opp.total_profit),
+            recent_opportunities = self.recent_opportunities,
+            "scanner report"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::opportunity;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_aggregates_across_pairs() {
+        let btc = vec![opportunity("BTC-31OCT25-140000-P", dec!(0.2))];
+        let eth = vec![
+            opportunity("ETH-31OCT25-4000-C", dec!(0.5)),
+            opportunity("ETH-31OCT25-4000-C", dec!(0.1)),
+        ];
+        let idle = Vec::new();
+
+        let report = ScannerReport::compute(3, [btc.as_slice(), eth.as_slice(), idle.as_slice()]);
+        assert_eq!(report.active_symbols, 3);
+        assert_eq!(report.symbols_with_arb, 2);
+        assert_eq!(report.total_expected_profit, dec!(0.8));
+        assert_eq!(report.best_opportunity.unwrap().total_profit, dec!(0.5));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = ScannerReport::compute(0, []);
+        assert_eq!(report.symbols_with_arb, 0);
+        assert_eq!(report.total_expected_profit, Decimal::ZERO);
+        assert!(report.best_opportunity.is_none());
+    }
+}
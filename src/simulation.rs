@@ -0,0 +1,126 @@
+use crate::orderbook::{Exchange, OrderBookUpdate, OrderLevel};
+use rust_decimal::Decimal;
+use std::time::Instant;
+use tokio::{
+    sync::mpsc,
+    time::{Duration, sleep},
+};
+
+/// A scripted feed standing in for a venue's websocket: each update is sent after waiting its
+/// delay.
+#[derive(Debug, Clone)]
+pub struct SimulatedExchange {
+    pub exchange: Exchange,
+    pub script: Vec<(Duration, OrderBookUpdate)>,
+}
+
+impl SimulatedExchange {
+    pub fn builder(exchange: Exchange, symbol: impl Into<String>) -> SimulatedExchangeBuilder {
+        SimulatedExchangeBuilder {
+            exchange,
+            symbol: symbol.into(),
+            pending_delay: Duration::ZERO,
+            script: Vec::new(),
+        }
+    }
+
+    /// Plays the script into `tx`, stopping early if the receiver is dropped.
+    pub async fn run(&self, tx: mpsc::Sender<OrderBookUpdate>) {
+        for (delay, update) in &self.script {
+            if !delay.is_zero() {
+                sleep(*delay).await;
+            }
+            if tx.send(update.clone()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Builds a `SimulatedExchange` script one level at a time.
+pub struct SimulatedExchangeBuilder {
+    exchange: Exchange,
+    symbol: String,
+    /// Delay to put before the next update.
+    pending_delay: Duration,
+    script: Vec<(Duration, OrderBookUpdate)>,
+}
+
+impl SimulatedExchangeBuilder {
+    /// Sets one bid level; a zero quantity removes it.
+    pub fn bid(self, price: Decimal, quantity: Decimal) -> Self {
+        let update = OrderBookUpdate::Bids {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            levels: vec![OrderLevel { price, quantity }],
+            received_at: Instant::now(),
+        };
+        self.push(update)
+    }
+
+    /// Sets one ask level; a zero quantity removes it.
+    pub fn ask(self, price: Decimal, quantity: Decimal) -> Self {
+        let update = OrderBookUpdate::Asks {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            levels: vec![OrderLevel { price, quantity }],
+            received_at: Instant::now(),
+        };
+        self.push(update)
+    }
+
+    /// Waits `delay` before the next update. Consecutive pauses add up.
+    pub fn pause(mut self, delay: Duration) -> Self {
+        self.pending_delay += delay;
+        self
+    }
+
+    pub fn build(self) -> SimulatedExchange {
+        SimulatedExchange {
+            exchange: self.exchange,
+            script: self.script,
+        }
+    }
+
+    fn push(mut self, update: OrderBookUpdate) -> Self {
+        self.script
+            .push((std::mem::take(&mut self.pending_delay), update));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_replays_script_with_delays() {
+        let simulated = SimulatedExchange::builder(Exchange::Deribit, "SIM")
+            .bid(dec!(0.1), dec!(1))
+            .pause(Duration::from_millis(200))
+            .pause(Duration::from_millis(300))
+            .ask(dec!(0.2), dec!(2))
+            .build();
+        assert_eq!(simulated.script[0].0, Duration::ZERO);
+        assert_eq!(simulated.script[1].0, Duration::from_millis(500));
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let start = tokio::time::Instant::now();
+        simulated.run(tx).await;
+
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Bids {
+                exchange: Exchange::Deribit,
+                ..
+            })
+        ));
+        assert!(matches!(
+            rx.recv().await,
+            Some(OrderBookUpdate::Asks { .. })
+        ));
+        assert!(rx.recv().await.is_none());
+        assert_eq!(start.elapsed(), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::orderbook::ArbitrageOpportunity;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
+
+/// Reported opportunities keyed by when they were found, kept for `max_age`.
+#[derive(Debug, Clone)]
+pub struct OpportunityStore {
+    inner: BTreeMap<SystemTime, ArbitrageOpportunity>,
+    max_age: Duration,
+}
+
+impl OpportunityStore {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            inner: BTreeMap::new(),
+            max_age,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn insert(&mut self, opp: ArbitrageOpportunity) {
+        self.insert_at(SystemTime::now(), opp);
+    }
+
+    fn insert_at(&mut self, mut at: SystemTime, opp: ArbitrageOpportunity) {
+        // Detection can report several opportunities within the clock's resolution; keep each
+        // by nudging later ones forward.
+        while self.inner.contains_key(&at) {
+            at += Duration::from_nanos(1);
+        }
+        self.inner.insert(at, opp);
+    }
+
+    /// Drops entries older than `max_age`.
+    pub fn prune(&mut self) {
+        let cutoff = cutoff(self.max_age);
+        self.inner = self.inner.split_off(&cutoff);
+    }
+
+    /// Entries found at or after `t`, oldest first.
+    pub fn since(&self, t: SystemTime) -> impl Iterator<Item = &ArbitrageOpportunity> {
+        self.inner.range(t..).map(|(_, opp)| opp)
+    }
+
+    /// Entries found within `dur` of now.
+    pub fn count_last(&self, dur: Duration) -> usize {
+        self.since(cutoff(dur)).count()
+    }
+}
+
+fn cutoff(age: Duration) -> SystemTime {
+    SystemTime::now()
+        .checked_sub(age)
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::opportunity;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    const SYMBOL: &str = "BTC-USD-251031-140000-P";
+
+    #[test]
+    fn test_prune_and_windows() {
+        let mut store = OpportunityStore::new(Duration::from_secs(300));
+        let now = SystemTime::now();
+        store.insert_at(
+            now - Duration::from_secs(600),
+            opportunity(SYMBOL, dec!(0.1)),
+        );
+        store.insert_at(
+            now - Duration::from_secs(120),
+            opportunity(SYMBOL, dec!(0.2)),
+        );
+        store.insert(opportunity(SYMBOL, dec!(0.3)));
+        assert_eq!(store.len(), 3);
+
+        assert_eq!(store.count_last(Duration::from_secs(60)), 1);
+        let recent: Vec<Decimal> = store
+            .since(now - Duration::from_secs(180))
+            .map(|opp| opp.total_profit)
+            .collect();
+        assert_eq!(recent, [dec!(0.2), dec!(0.3)]);
+
+        store.prune();
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.count_last(Duration::from_secs(3600)), 2);
+    }
+
+    #[test]
+    fn test_same_instant_keeps_both() {
+        let mut store = OpportunityStore::new(Duration::from_secs(60));
+        let now = SystemTime::now();
+        store.insert_at(now, opportunity(SYMBOL, dec!(0.1)));
+        store.insert_at(now, opportunity(SYMBOL, dec!(0.2)));
+        assert_eq!(store.since(now).count(), 2);
+    }
+}
@@ -0,0 +1,33 @@
+//! Shared fixtures for unit tests.
+
+use crate::orderbook::{ArbitrageOpportunity, Exchange, TradeLevel};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// An Okex → Deribit opportunity on `symbol` with no trade levels.
+pub(crate) fn opportunity(symbol: &str, total_profit: Decimal) -> ArbitrageOpportunity {
+    ArbitrageOpportunity {
+        buy_exchange: Exchange::Okex,
+        sell_exchange: Exchange::Deribit,
+        symbol: symbol.to_string(),
+        trades: Vec::new(),
+        total_profit,
+        total_volume: dec!(10),
+    }
+}
+
+/// Like [`opportunity`], with a single 10-contract level bought at 0.140 and sold at 0.150.
+pub(crate) fn opportunity_with_trade(symbol: &str) -> ArbitrageOpportunity {
+    ArbitrageOpportunity {
+        trades: vec![TradeLevel {
+            buy_price: dec!(0.140),
+            sell_price: dec!(0.150),
+            quantity: dec!(10),
+            normalized_quantity: dec!(10),
+            gross_profit: dec!(0.1),
+            fee_cost: dec!(0),
+            profit: dec!(0.1),
+        }],
+        ..opportunity(symbol, dec!(0.1))
+    }
+}
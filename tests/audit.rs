@@ -0,0 +1,115 @@
+//! Supply-chain checks on the locked dependency graph. They need `cargo-audit` and `cargo-deny`
+//! installed and network access to the RustSec advisory database, so they only run on request:
+//! `cargo test --test audit -- --ignored`.
+
+use serde_json::Value;
+use std::{collections::HashSet, path::PathBuf, process::Command};
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Crates `Cargo.toml` depends on directly, including dev and target-specific dependencies.
+fn direct_dependencies() -> HashSet<String> {
+    let manifest: toml::Table = std::fs::read_to_string(manifest_dir().join("Cargo.toml"))
+        .unwrap()
+        .parse()
+        .unwrap();
+    let mut tables = vec![&manifest];
+    if let Some(targets) = manifest.get("target").and_then(toml::Value::as_table) {
+        tables.extend(targets.values().filter_map(toml::Value::as_table));
+    }
+    tables
+        .into_iter()
+        .flat_map(|table| {
+            ["dependencies", "dev-dependencies", "build-dependencies"]
+                .into_iter()
+                .filter_map(|section| table.get(section)?.as_table())
+        })
+        .flat_map(|dependencies| {
+            // A renamed dependency is advised against under its package name.
+            dependencies.iter().map(|(name, spec)| {
+                spec.get("package")
+                    .and_then(toml::Value::as_str)
+                    .unwrap_or(name)
+                    .to_string()
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_direct_dependencies_cover_every_section() {
+    let direct = direct_dependencies();
+    for name in ["tokio", "rusqlite", "proptest", "nix"] {
+        assert!(direct.contains(name), "{name} missing from {direct:?}");
+    }
+    assert!(!direct.contains("tokio-macros"));
+}
+
+#[test]
+#[ignore = "needs cargo-audit and network access to the RustSec advisory database"]
+fn test_no_advisories_for_direct_dependencies() {
+    let output = Command::new(env!("CARGO"))
+        .args(["audit", "--json", "--file"])
+        .arg(manifest_dir().join("Cargo.lock"))
+        .output()
+        .expect("failed to run cargo");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+        panic!("cargo audit printed no JSON report ({e}); is cargo-audit installed?\n{stderr}")
+    });
+
+    let vulnerabilities = report["vulnerabilities"]["list"]
+        .as_array()
+        .expect("report has a vulnerability list");
+    assert_eq!(
+        report["vulnerabilities"]["count"].as_u64(),
+        Some(vulnerabilities.len() as u64)
+    );
+    // cargo audit fails exactly when it finds a vulnerability, anywhere in the graph.
+    assert_eq!(
+        output.status.success(),
+        vulnerabilities.is_empty(),
+        "{stderr}"
+    );
+
+    let direct = direct_dependencies();
+    let affecting: Vec<String> = vulnerabilities
+        .iter()
+        .filter(|vulnerability| {
+            vulnerability["package"]["name"]
+                .as_str()
+                .is_some_and(|name| direct.contains(name))
+        })
+        .map(|vulnerability| {
+            format!(
+                "{} in {} {}",
+                vulnerability["advisory"]["id"].as_str().unwrap_or("?"),
+                vulnerability["package"]["name"].as_str().unwrap_or("?"),
+                vulnerability["package"]["version"].as_str().unwrap_or("?"),
+            )
+        })
+        .collect();
+    assert!(
+        affecting.is_empty(),
+        "RustSec advisories affect direct dependencies: {affecting:?}"
+    );
+}
+
+#[test]
+#[ignore = "needs cargo-deny and network access to the RustSec advisory database"]
+fn test_cargo_deny_check() {
+    let output = Command::new(env!("CARGO"))
+        .arg("deny")
+        .arg("--manifest-path")
+        .arg(manifest_dir().join("Cargo.toml"))
+        .arg("check")
+        .output()
+        .expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "cargo deny check failed; is cargo-deny installed?\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
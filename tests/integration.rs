@@ -0,0 +1,62 @@
+//! Needs the `simulation` feature: `cargo test --features simulation --test integration`.
+
+use options_arbitrage::{
+    orderbook::{ArbitrageDetector, Exchange},
+    registry::BookRegistry,
+    simulation::SimulatedExchange,
+};
+use rust_decimal_macros::dec;
+use tokio::{
+    sync::mpsc,
+    time::{Duration, Instant, timeout_at},
+};
+
+const OKEX_SYMBOL: &str = "ETH-USD-240427-3000-C";
+const DERIBIT_SYMBOL: &str = "ETH-27APR24-3000-C";
+
+/// Feeds two simulated venues through the same registry and apply-then-detect loop the binary
+/// runs, for one second.
+#[tokio::test(start_paused = true)]
+async fn test_simulated_exchanges_produce_an_opportunity() {
+    let okex = SimulatedExchange::builder(Exchange::Okex, OKEX_SYMBOL)
+        .bid(dec!(0.040), dec!(10))
+        .pause(Duration::from_millis(100))
+        .ask(dec!(0.050), dec!(10))
+        .pause(Duration::from_millis(300))
+        .ask(dec!(0.044), dec!(5))
+        .build();
+    let deribit = SimulatedExchange::builder(Exchange::Deribit, DERIBIT_SYMBOL)
+        .pause(Duration::from_millis(50))
+        .bid(dec!(0.042), dec!(10))
+        .ask(dec!(0.052), dec!(10))
+        .pause(Duration::from_millis(400))
+        .bid(dec!(0.046), dec!(8))
+        .build();
+
+    let (tx, mut rx) = mpsc::channel(16);
+    for simulated in [okex, deribit] {
+        let tx = tx.clone();
+        tokio::spawn(async move { simulated.run(tx).await });
+    }
+    drop(tx);
+
+    let detector = ArbitrageDetector::new(20);
+    let okex_key = (Exchange::Okex, OKEX_SYMBOL.to_string());
+    let deribit_key = (Exchange::Deribit, DERIBIT_SYMBOL.to_string());
+    // Simulated venues have no subscribe step to confirm.
+    let mut registry =
+        BookRegistry::new().assume_subscribed([okex_key.clone(), deribit_key.clone()]);
+    let mut opportunities = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while let Ok(Some(update)) = timeout_at(deadline, rx.recv()).await {
+        registry.apply_update(&update).unwrap();
+        if let Some((okex, deribit)) = registry.ready_pair(&okex_key, &deribit_key) {
+            opportunities.extend(detector.detect_arbitrage(okex, deribit));
+        }
+    }
+
+    assert!(!opportunities.is_empty());
+    assert!(opportunities.iter().all(|opportunity| {
+        opportunity.buy_exchange == Exchange::Okex && opportunity.sell_exchange == Exchange::Deribit
+    }));
+}
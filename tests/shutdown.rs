@@ -0,0 +1,89 @@
+#![cfg(unix)]
+
+use nix::{
+    sys::signal::{Signal, kill},
+    unistd::Pid,
+};
+use options_arbitrage::{
+    orderbook::{Exchange, OrderBookUpdate},
+    recorder::RecordingWriter,
+};
+use std::{
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+const OKEX_SYMBOL: &str = "BTC-USD-991231-140000-P";
+const DERIBIT_SYMBOL: &str = "BTC-31DEC99-140000-P";
+
+/// A recording whose second update is an hour in, so replaying it keeps the run going without
+/// connecting to any exchange.
+fn long_recording(path: &PathBuf) {
+    let mut writer = RecordingWriter::create(path).unwrap();
+    let now = Instant::now();
+    for received_at in [now, now + Duration::from_secs(3600)] {
+        writer
+            .write(&OrderBookUpdate::Snapshot {
+                exchange: Exchange::Okex,
+                symbol: OKEX_SYMBOL.to_string(),
+                bids: Vec::new(),
+                asks: Vec::new(),
+                received_at,
+            })
+            .unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+#[test]
+fn test_sigterm_exits_cleanly() {
+    let dir = std::env::temp_dir();
+    let recording = dir.join(format!("shutdown-{}.rec", std::process::id()));
+    let output = dir.join(format!("shutdown-{}.out", std::process::id()));
+    long_recording(&recording);
+
+    // Writing opportunities to a file moves the logs to stderr.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_options-arbitrage"))
+        .arg(format!("--symbol-a={OKEX_SYMBOL}"))
+        .arg(format!("--symbol-b={DERIBIT_SYMBOL}"))
+        .arg("--replay")
+        .arg(&recording)
+        .arg("--output-file")
+        .arg(&output)
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // The signal handler is installed before anything is logged, so the first replay line
+    // means SIGTERM will be handled.
+    let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines();
+    let ready = stderr
+        .by_ref()
+        .map_while(Result::ok)
+        .any(|line| line.contains("Replaying recording"));
+    assert!(ready, "exited before replaying");
+    // Keep draining so the child never blocks on a full pipe while shutting down.
+    thread::spawn(move || stderr.for_each(drop));
+
+    kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let status = loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            break status;
+        }
+        if Instant::now() > deadline {
+            child.kill().unwrap();
+            panic!("still running 10s after SIGTERM");
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+    let _ = std::fs::remove_file(&recording);
+    let _ = std::fs::remove_file(&output);
+    assert!(status.success(), "exited with {status}");
+}